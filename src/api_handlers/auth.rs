@@ -1,8 +1,9 @@
 use axum::extract::{State, Json};
 use axum::routing::{get, post};
-use axum::response::IntoResponse;
 use axum::Router;
+use axum::http::request::Parts;
 use axum::http::StatusCode;
+use crate::api_handlers::session::{self, CurrentUser};
 use crate::models::{User, AuthResponse, AuthRequest};
 use crate::db::AppState;
 use std::sync::Arc;
@@ -10,6 +11,7 @@ use std::sync::Arc;
 pub fn router(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/login", post(login))
+        .route("/logout", post(logout))
         .route("/me", get(get_me))
         .route("/list", get(list_users))
         .with_state(state)
@@ -42,31 +44,24 @@ async fn login(
         },
     );
 
-    match user_res {
-        Ok(user) => Ok(Json(AuthResponse {
-            user,
-            token: "fake-jwt-token".to_string(),
-        })),
-        Err(_) => Err(StatusCode::UNAUTHORIZED),
-    }
+    let user = user_res.map_err(|_| StatusCode::UNAUTHORIZED)?;
+    let token = session::create_session(&conn, &user.id, state.clock.now())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(AuthResponse { user, token }))
 }
 
-async fn get_me() -> impl IntoResponse {
-    // Mock session check
-    let user = User {
-        id: "user-client-1".to_string(),
-        name: "John Doe".to_string(),
-        email: "john.doe@strata.com".to_string(),
-        role: "client".to_string(),
-        strata_id: Some("strata-1".to_string()),
-        position: Some("Strata President".to_string()),
-        phone: Some("(604) 311-2222".to_string()),
-        cell_phone: Some("(604) 333-4444".to_string()),
-        must_change_password: false,
-        created_at: "2024-03-01T10:00:00Z".to_string(),
-    };
+async fn logout(State(state): State<Arc<AppState>>, parts: Parts) -> StatusCode {
+    if let Some(token) = session::bearer_token(&parts) {
+        let conn = state.conn.lock().unwrap();
+        if session::end_session(&conn, token).is_err() {
+            return StatusCode::INTERNAL_SERVER_ERROR;
+        }
+    }
+    StatusCode::NO_CONTENT
+}
 
-    Json(user)
+async fn get_me(user: CurrentUser) -> Json<User> {
+    Json(user.0)
 }
 
 pub async fn list_users(
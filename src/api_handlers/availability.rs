@@ -0,0 +1,486 @@
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{get, put};
+use axum::{Json, Router};
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime};
+use crate::api_handlers::business_calendar::{self, BusinessCalendar, COMPANY_PROVINCE};
+use crate::api_handlers::clock::{self, Clock};
+use crate::api_handlers::ecs_scheduler::AvailabilitySlot;
+use crate::api_handlers::milestones;
+use crate::api_handlers::session::CurrentUser;
+use crate::db::AppState;
+use crate::models::{hhmm, CompanyClosure, InspectorUnavailability, WorkingHours};
+use rusqlite::{Connection, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+// Inspectors without configured hours work Monday to Friday, 9 to 5
+const DEFAULT_START: NaiveTime = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+const DEFAULT_END: NaiveTime = NaiveTime::from_hms_opt(17, 0, 0).unwrap();
+
+#[derive(Deserialize)]
+pub struct HolidayQuery {
+    year: Option<i32>,
+    province: Option<String>, // defaults to the company's
+}
+
+#[derive(Serialize)]
+pub struct Holiday {
+    date: String,
+    name: &'static str,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnavailabilityQuery {
+    inspector_id: Option<String>,
+}
+
+pub fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/working-hours/:inspector_id", get(get_working_hours).put(set_working_hours))
+        .route("/unavailability", get(list_unavailability).post(create_unavailability))
+        .route("/unavailability/:id", put(update_unavailability).delete(delete_unavailability))
+        .route("/closures", get(list_closures).post(create_closure))
+        .route("/closures/:id", put(update_closure).delete(delete_closure))
+        .route("/holidays", get(list_holidays))
+        .with_state(state)
+}
+
+fn require_admin(user: &CurrentUser) -> Result<(), StatusCode> {
+    if user.is_admin() {
+        Ok(())
+    } else {
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, StatusCode> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| StatusCode::BAD_REQUEST)
+}
+
+// --- Working hours ---
+
+async fn get_working_hours(
+    State(state): State<Arc<AppState>>,
+    Path(inspector_id): Path<String>,
+) -> Result<Json<Vec<WorkingHours>>, StatusCode> {
+    let conn = state.conn.lock().unwrap();
+    load_working_hours(&conn, &inspector_id)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+// Replaces the inspector's whole week; days left out are days off
+async fn set_working_hours(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(inspector_id): Path<String>,
+    Json(payload): Json<Vec<WorkingHours>>,
+) -> Result<Json<Vec<WorkingHours>>, StatusCode> {
+    require_admin(&user)?;
+    for hours in &payload {
+        if hours.weekday > 6 || hours.start_time >= hours.end_time {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    let mut conn = state.conn.lock().unwrap();
+    if !save_working_hours(&mut conn, &inspector_id, &payload).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    load_working_hours(&conn, &inspector_id)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+// Replaces the stored week and marks it as configured. False, with nothing
+// saved, when the inspector does not exist.
+fn save_working_hours(conn: &mut Connection, inspector_id: &str, week: &[WorkingHours]) -> rusqlite::Result<bool> {
+    let tx = conn.transaction()?;
+    if tx.execute("UPDATE users SET working_hours_set = 1 WHERE id = ?1", [inspector_id])? == 0 {
+        return Ok(false);
+    }
+    tx.execute("DELETE FROM inspector_working_hours WHERE inspector_id = ?1", [inspector_id])?;
+    for hours in week {
+        tx.execute(
+            "INSERT OR REPLACE INTO inspector_working_hours (inspector_id, weekday, start_time, end_time)
+             VALUES (?1, ?2, ?3, ?4)",
+            (
+                inspector_id,
+                hours.weekday,
+                hours.start_time.format(hhmm::FORMAT).to_string(),
+                hours.end_time.format(hhmm::FORMAT).to_string(),
+            ),
+        )?;
+    }
+    tx.commit()?;
+    Ok(true)
+}
+
+// Configured hours, or the default week when none have ever been saved. Rows
+// whose times do not parse are treated as days off.
+pub fn load_working_hours(conn: &Connection, inspector_id: &str) -> rusqlite::Result<Vec<WorkingHours>> {
+    let rows: Vec<(u8, String, String)> = conn
+        .prepare(
+            "SELECT weekday, start_time, end_time FROM inspector_working_hours
+             WHERE inspector_id = ?1 ORDER BY weekday",
+        )?
+        .query_map([inspector_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    let hours = rows
+        .iter()
+        .filter_map(|(weekday, start, end)| {
+            Some(WorkingHours {
+                weekday: *weekday,
+                start_time: clock::parse_local_time(start)?,
+                end_time: clock::parse_local_time(end)?,
+            })
+        })
+        .collect();
+
+    if !rows.is_empty() {
+        return Ok(hours);
+    }
+    let configured: Option<bool> = conn
+        .query_row("SELECT working_hours_set FROM users WHERE id = ?1", [inspector_id], |row| row.get(0))
+        .optional()?;
+    if configured == Some(true) {
+        return Ok(hours);
+    }
+    Ok((0..5)
+        .map(|weekday| WorkingHours {
+            weekday,
+            start_time: DEFAULT_START,
+            end_time: DEFAULT_END,
+        })
+        .collect())
+}
+
+// --- Unavailability blocks ---
+
+async fn list_unavailability(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<UnavailabilityQuery>,
+) -> Result<Json<Vec<InspectorUnavailability>>, StatusCode> {
+    let conn = state.conn.lock().unwrap();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, inspector_id, start_date, end_date, reason FROM inspector_unavailability
+             WHERE ?1 IS NULL OR inspector_id = ?1
+             ORDER BY start_date",
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let blocks = stmt
+        .query_map([&params.inspector_id], |row| {
+            Ok(InspectorUnavailability {
+                id: row.get(0)?,
+                inspector_id: row.get(1)?,
+                start_date: row.get(2)?,
+                end_date: row.get(3)?,
+                reason: row.get(4)?,
+            })
+        })
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(Json(blocks))
+}
+
+fn validate_block(block: &InspectorUnavailability) -> Result<(), StatusCode> {
+    if parse_date(&block.start_date)? > parse_date(&block.end_date)? {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(())
+}
+
+async fn create_unavailability(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Json(mut payload): Json<InspectorUnavailability>,
+) -> Result<(StatusCode, Json<InspectorUnavailability>), StatusCode> {
+    require_admin(&user)?;
+    validate_block(&payload)?;
+    payload.id = uuid::Uuid::new_v4().to_string();
+
+    let conn = state.conn.lock().unwrap();
+    conn.execute(
+        "INSERT INTO inspector_unavailability (id, inspector_id, start_date, end_date, reason)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        (&payload.id, &payload.inspector_id, &payload.start_date, &payload.end_date, &payload.reason),
+    )
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok((StatusCode::CREATED, Json(payload)))
+}
+
+async fn update_unavailability(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+    Json(mut payload): Json<InspectorUnavailability>,
+) -> Result<Json<InspectorUnavailability>, StatusCode> {
+    require_admin(&user)?;
+    validate_block(&payload)?;
+    payload.id = id;
+
+    let conn = state.conn.lock().unwrap();
+    let changed = conn
+        .execute(
+            "UPDATE inspector_unavailability SET inspector_id = ?1, start_date = ?2, end_date = ?3, reason = ?4
+             WHERE id = ?5",
+            (&payload.inspector_id, &payload.start_date, &payload.end_date, &payload.reason, &payload.id),
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if changed == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(Json(payload))
+}
+
+async fn delete_unavailability(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    require_admin(&user)?;
+    let conn = state.conn.lock().unwrap();
+    let changed = conn
+        .execute("DELETE FROM inspector_unavailability WHERE id = ?1", [&id])
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if changed == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// --- Company closures ---
+
+async fn list_closures(State(state): State<Arc<AppState>>) -> Result<Json<Vec<CompanyClosure>>, StatusCode> {
+    let conn = state.conn.lock().unwrap();
+    load_closures(&conn)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+pub fn load_closures(conn: &Connection) -> rusqlite::Result<Vec<CompanyClosure>> {
+    conn.prepare("SELECT id, name, date, recurring FROM company_closures ORDER BY substr(date, 6), date")?
+        .query_map([], |row| {
+            Ok(CompanyClosure {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                date: row.get(2)?,
+                recurring: row.get::<_, i32>(3)? != 0,
+            })
+        })?
+        .collect()
+}
+
+async fn create_closure(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Json(mut payload): Json<CompanyClosure>,
+) -> Result<(StatusCode, Json<CompanyClosure>), StatusCode> {
+    require_admin(&user)?;
+    parse_date(&payload.date)?;
+    payload.id = uuid::Uuid::new_v4().to_string();
+
+    let mut conn = state.conn.lock().unwrap();
+    let tx = conn.transaction().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    tx.execute(
+        "INSERT INTO company_closures (id, name, date, recurring) VALUES (?1, ?2, ?3, ?4)",
+        (&payload.id, &payload.name, &payload.date, payload.recurring),
+    )
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    refresh_deadlines(tx, state.clock.as_ref())?;
+
+    Ok((StatusCode::CREATED, Json(payload)))
+}
+
+async fn update_closure(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+    Json(mut payload): Json<CompanyClosure>,
+) -> Result<Json<CompanyClosure>, StatusCode> {
+    require_admin(&user)?;
+    parse_date(&payload.date)?;
+    payload.id = id;
+
+    let mut conn = state.conn.lock().unwrap();
+    let tx = conn.transaction().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let changed = tx
+        .execute(
+            "UPDATE company_closures SET name = ?1, date = ?2, recurring = ?3 WHERE id = ?4",
+            (&payload.name, &payload.date, payload.recurring, &payload.id),
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if changed == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    refresh_deadlines(tx, state.clock.as_ref())?;
+
+    Ok(Json(payload))
+}
+
+async fn delete_closure(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    require_admin(&user)?;
+    let mut conn = state.conn.lock().unwrap();
+    let tx = conn.transaction().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let changed = tx
+        .execute("DELETE FROM company_closures WHERE id = ?1", [&id])
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if changed == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    refresh_deadlines(tx, state.clock.as_ref())?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Closures shift every business-day deadline, so each open request's stored
+// milestones are recomputed before the change commits
+fn refresh_deadlines(tx: Transaction, clock: &dyn Clock) -> Result<(), StatusCode> {
+    milestones::refresh(&tx, None, clock).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    tx.commit().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+// Statutory holidays for a year, on the days they are observed
+async fn list_holidays(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<HolidayQuery>,
+) -> Result<Json<Vec<Holiday>>, StatusCode> {
+    let year = params.year.unwrap_or_else(|| state.clock.today_in(clock::DEFAULT_TIME_ZONE).year());
+    let province = params.province.as_deref().unwrap_or(COMPANY_PROVINCE);
+    let rules = business_calendar::rules_for(province).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(
+        rules
+            .holidays(year)
+            .into_iter()
+            .map(|(date, name)| Holiday { date: date.format("%Y-%m-%d").to_string(), name })
+            .collect(),
+    ))
+}
+
+// --- Slot computation ---
+
+pub fn is_closed(closures: &[CompanyClosure], date: NaiveDate) -> bool {
+    closures.iter().any(|closure| {
+        let Ok(closed) = NaiveDate::parse_from_str(&closure.date, "%Y-%m-%d") else {
+            return false;
+        };
+        if closure.recurring {
+            closed.month() == date.month() && closed.day() == date.day()
+        } else {
+            closed == date
+        }
+    })
+}
+
+// One entry per inspector per day in [from, to], blocked on statutory holidays
+// and company closures, when the inspector is on leave, or when it is not one
+// of their working days.
+pub fn inspector_days(conn: &Connection, from: NaiveDate, to: NaiveDate) -> rusqlite::Result<Vec<AvailabilitySlot>> {
+    let calendar = BusinessCalendar::load(conn, Some(COMPANY_PROVINCE))?;
+    let inspectors: Vec<String> = conn
+        .prepare("SELECT id FROM users WHERE role = 'inspector' ORDER BY id")?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let mut days = Vec::new();
+    for inspector_id in inspectors {
+        let working = load_working_hours(conn, &inspector_id)?;
+        let mut date = from;
+        while date <= to {
+            let formatted = date.format("%Y-%m-%d").to_string();
+            let on_leave: Option<i64> = conn
+                .query_row(
+                    "SELECT 1 FROM inspector_unavailability
+                     WHERE inspector_id = ?1 AND start_date <= ?2 AND end_date >= ?2",
+                    (&inspector_id, &formatted),
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let weekday = date.weekday().num_days_from_monday() as u8;
+            let hours = working
+                .iter()
+                .find(|hours| hours.weekday == weekday)
+                .map(|hours| (hours.start_time, hours.end_time));
+            days.push(AvailabilitySlot {
+                inspector_id: inspector_id.clone(),
+                date: formatted,
+                is_blocked: hours.is_none() || on_leave.is_some() || calendar.is_closed(date),
+                start_time: hours.map(|(start, _)| start),
+                end_time: hours.map(|(_, end)| end),
+            });
+            date += Duration::days(1);
+        }
+    }
+    Ok(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inspector_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id TEXT PRIMARY KEY, working_hours_set INTEGER NOT NULL DEFAULT 0);
+             CREATE TABLE inspector_working_hours (
+                 inspector_id TEXT NOT NULL, weekday INTEGER NOT NULL, start_time TEXT NOT NULL,
+                 end_time TEXT NOT NULL, PRIMARY KEY(inspector_id, weekday));
+             INSERT INTO users (id) VALUES ('inspector');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn week(conn: &Connection) -> Vec<(u8, String, String)> {
+        load_working_hours(conn, "inspector")
+            .unwrap()
+            .iter()
+            .map(|h| {
+                let format = |time: NaiveTime| time.format(hhmm::FORMAT).to_string();
+                (h.weekday, format(h.start_time), format(h.end_time))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn unconfigured_inspectors_work_the_default_week() {
+        let conn = inspector_db();
+        let days = week(&conn);
+        assert_eq!(days.iter().map(|d| d.0).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+        assert_eq!((days[0].1.as_str(), days[0].2.as_str()), ("09:00", "17:00"));
+    }
+
+    #[test]
+    fn saved_weeks_round_trip_including_an_empty_one() {
+        let mut conn = inspector_db();
+        let saturday = WorkingHours {
+            weekday: 5,
+            start_time: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+            end_time: NaiveTime::from_hms_opt(12, 30, 0).unwrap(),
+        };
+        assert!(save_working_hours(&mut conn, "inspector", &[saturday]).unwrap());
+        assert_eq!(week(&conn), vec![(5, "08:00".to_string(), "12:30".to_string())]);
+
+        assert!(save_working_hours(&mut conn, "inspector", &[]).unwrap());
+        assert_eq!(week(&conn), vec![]);
+    }
+
+    #[test]
+    fn unknown_inspectors_are_not_saved() {
+        let mut conn = inspector_db();
+        assert!(!save_working_hours(&mut conn, "nobody", &[]).unwrap());
+        assert_eq!(load_working_hours(&conn, "nobody").unwrap().len(), 5);
+    }
+}
@@ -0,0 +1,214 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use crate::api_handlers::availability;
+use crate::models::CompanyClosure;
+use rusqlite::Connection;
+
+// Where the company works; inspections and slots follow its holidays
+pub const COMPANY_PROVINCE: &str = "BC";
+
+// Statutory holidays for one province. Add an implementation and a match arm
+// in `rules_for` to support another.
+pub trait HolidayRules: Send + Sync {
+    // Every holiday in `year`, on the day it is observed
+    fn holidays(&self, year: i32) -> Vec<(NaiveDate, &'static str)>;
+}
+
+// Employment Standards Act of British Columbia, s. 1 "statutory holiday"
+pub struct BritishColumbia;
+
+impl HolidayRules for BritishColumbia {
+    fn holidays(&self, year: i32) -> Vec<(NaiveDate, &'static str)> {
+        let mut holidays = Vec::new();
+        let date = |month, day| NaiveDate::from_ymd_opt(year, month, day);
+
+        // Moveable holidays first so fixed ones landing on a weekend can be
+        // moved past them
+        if let Some(easter) = easter_sunday(year) {
+            holidays.push((easter - Duration::days(2), "Good Friday"));
+        }
+        if year >= 2013 {
+            let third = if year >= 2019 { 3 } else { 2 };
+            holidays.extend(NaiveDate::from_weekday_of_month_opt(year, 2, Weekday::Mon, third).map(|d| (d, "Family Day")));
+        }
+        // Victoria Day: the last Monday before May 25
+        if let Some(may_24) = date(5, 24) {
+            let since_monday = may_24.weekday().num_days_from_monday() as i64;
+            holidays.push((may_24 - Duration::days(since_monday), "Victoria Day"));
+        }
+        holidays.extend(NaiveDate::from_weekday_of_month_opt(year, 8, Weekday::Mon, 1).map(|d| (d, "British Columbia Day")));
+        holidays.extend(NaiveDate::from_weekday_of_month_opt(year, 9, Weekday::Mon, 1).map(|d| (d, "Labour Day")));
+        holidays.extend(NaiveDate::from_weekday_of_month_opt(year, 10, Weekday::Mon, 2).map(|d| (d, "Thanksgiving Day")));
+
+        let mut fixed = vec![
+            (date(1, 1), "New Year's Day"),
+            (date(7, 1), "Canada Day"),
+            (date(11, 11), "Remembrance Day"),
+            (date(12, 25), "Christmas Day"),
+        ];
+        if year >= 2023 {
+            fixed.push((date(9, 30), "National Day for Truth and Reconciliation"));
+        }
+        // On a weekend the next working day is given instead
+        for (day, name) in fixed {
+            let Some(mut observed) = day else { continue };
+            while is_weekend(observed) || holidays.iter().any(|(d, _)| *d == observed) {
+                observed += Duration::days(1);
+            }
+            holidays.push((observed, name));
+        }
+
+        holidays.sort();
+        holidays
+    }
+}
+
+pub fn rules_for(province: &str) -> Option<Box<dyn HolidayRules>> {
+    match province.trim().to_uppercase().as_str() {
+        "BC" | "B.C." | "BRITISH COLUMBIA" => Some(Box::new(BritishColumbia)),
+        _ => None,
+    }
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+// Anonymous Gregorian algorithm (Meeus/Jones/Butcher)
+fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+// Working days: weekdays that are neither a statutory holiday nor a company
+// closure
+pub struct BusinessCalendar {
+    rules: Box<dyn HolidayRules>,
+    closures: Vec<CompanyClosure>,
+}
+
+impl BusinessCalendar {
+    // Unknown or missing provinces fall back to the company's own
+    pub fn new(province: Option<&str>, closures: Vec<CompanyClosure>) -> Self {
+        let rules = province.and_then(rules_for).unwrap_or_else(|| Box::new(BritishColumbia));
+        Self { rules, closures }
+    }
+
+    pub fn load(conn: &Connection, province: Option<&str>) -> rusqlite::Result<Self> {
+        Ok(Self::new(province, availability::load_closures(conn)?))
+    }
+
+    // Holiday or closure; weekends are left to each inspector's working hours
+    pub fn is_closed(&self, date: NaiveDate) -> bool {
+        availability::is_closed(&self.closures, date)
+            || self.rules.holidays(date.year()).iter().any(|(holiday, _)| *holiday == date)
+    }
+
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        !is_weekend(date) && !self.is_closed(date)
+    }
+
+    // Deadlines falling on a day off move back to the last working day before
+    pub fn on_or_before(&self, date: NaiveDate) -> NaiveDate {
+        let mut day = date;
+        while !self.is_business_day(day) {
+            day -= Duration::days(1);
+        }
+        day
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn holiday(year: i32, name: &str) -> NaiveDate {
+        BritishColumbia
+            .holidays(year)
+            .into_iter()
+            .find(|(_, holiday)| *holiday == name)
+            .map(|(date, _)| date)
+            .unwrap()
+    }
+
+    #[test]
+    fn easter_matches_published_dates() {
+        // Including the earliest (March 22) and latest (April 25) possible dates
+        let published = [
+            (1818, 3, 22),
+            (2000, 4, 23),
+            (2008, 3, 23),
+            (2019, 4, 21),
+            (2024, 3, 31),
+            (2025, 4, 20),
+            (2026, 4, 5),
+            (2038, 4, 25),
+        ];
+        for (year, month, date) in published {
+            assert_eq!(easter_sunday(year), Some(day(year, month, date)), "Easter {}", year);
+        }
+    }
+
+    #[test]
+    fn good_friday_falls_two_days_before_easter() {
+        for year in 2000..2050 {
+            let good_friday = holiday(year, "Good Friday");
+            assert_eq!(good_friday.weekday(), Weekday::Fri);
+            assert_eq!(Some(good_friday + Duration::days(2)), easter_sunday(year));
+        }
+    }
+
+    #[test]
+    fn easter_monday_is_a_working_day_in_bc() {
+        let calendar = BusinessCalendar::new(Some("BC"), Vec::new());
+        // Easter 2024 fell on the last day of March, so the Monday is in April
+        assert!(calendar.is_business_day(day(2024, 4, 1)));
+        assert!(calendar.is_business_day(day(2026, 4, 6)));
+        assert!(!calendar.is_business_day(day(2026, 4, 3)));
+    }
+
+    #[test]
+    fn canada_day_on_a_weekend_moves_to_monday() {
+        // Saturday in 2023 and 2028, Sunday in 2029
+        assert_eq!(holiday(2023, "Canada Day"), day(2023, 7, 3));
+        assert_eq!(holiday(2028, "Canada Day"), day(2028, 7, 3));
+        assert_eq!(holiday(2029, "Canada Day"), day(2029, 7, 2));
+        assert_eq!(holiday(2026, "Canada Day"), day(2026, 7, 1));
+    }
+
+    #[test]
+    fn rolls_back_across_a_long_weekend() {
+        let calendar = BusinessCalendar::new(Some("BC"), Vec::new());
+        // Good Friday 2026 is April 3, so Easter Sunday rolls back to Thursday
+        assert_eq!(calendar.on_or_before(day(2026, 4, 5)), day(2026, 4, 2));
+        // British Columbia Day 2026 is Monday August 3
+        assert_eq!(calendar.on_or_before(day(2026, 8, 3)), day(2026, 7, 31));
+
+        // A company closure on the Friday stretches the weekend further
+        let closure = CompanyClosure {
+            id: String::new(),
+            name: "Summer closure".to_string(),
+            date: "2026-07-31".to_string(),
+            recurring: false,
+        };
+        let calendar = BusinessCalendar::new(Some("BC"), vec![closure]);
+        assert_eq!(calendar.on_or_before(day(2026, 8, 3)), day(2026, 7, 30));
+        assert_eq!(calendar.on_or_before(day(2026, 7, 30)), day(2026, 7, 30));
+    }
+}
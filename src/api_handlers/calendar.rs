@@ -0,0 +1,410 @@
+use axum::extract::{Path, State};
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use crate::api_handlers::clock;
+use crate::api_handlers::logistics::{appointment_from_row, load_appointment, APPOINTMENT_COLUMNS};
+use crate::api_handlers::session::CurrentUser;
+use crate::api_handlers::slots::{DEFAULT_DURATION_HOURS, DRAFT_MEETING_MINUTES};
+use crate::db::AppState;
+use crate::models::{Appointment, AppointmentStatus};
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use std::sync::Arc;
+
+const PRODID: &str = "-//Strata Reserve Planning//SRP Portal//EN";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarToken {
+    token: String,
+    feed_url: String,
+}
+
+pub fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/token", get(get_token))
+        .route("/token/rotate", post(rotate_token))
+        .route("/appointments/:id/invite.ics", get(get_invite))
+        .route("/:feed", get(get_feed))
+        .with_state(state)
+}
+
+fn token_response(token: String) -> Json<CalendarToken> {
+    Json(CalendarToken {
+        feed_url: format!("/api/calendar/{}.ics", token),
+        token,
+    })
+}
+
+fn issue_token(conn: &Connection, user_id: &str, created_at: &str) -> rusqlite::Result<String> {
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    conn.execute(
+        "INSERT OR REPLACE INTO calendar_tokens (user_id, token, created_at) VALUES (?1, ?2, ?3)",
+        (user_id, &token, created_at),
+    )?;
+    Ok(token)
+}
+
+// The caller's feed token, created on first use
+async fn get_token(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+) -> Result<Json<CalendarToken>, StatusCode> {
+    let conn = state.conn.lock().unwrap();
+    let existing: Option<String> = conn
+        .query_row("SELECT token FROM calendar_tokens WHERE user_id = ?1", [&user.0.id], |row| row.get(0))
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let token = match existing {
+        Some(token) => token,
+        None => issue_token(&conn, &user.0.id, &state.clock.now().to_rfc3339()).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+    };
+    Ok(token_response(token))
+}
+
+// Invalidates the old feed URL, e.g. after it was shared by mistake
+async fn rotate_token(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+) -> Result<Json<CalendarToken>, StatusCode> {
+    let conn = state.conn.lock().unwrap();
+    issue_token(&conn, &user.0.id, &state.clock.now().to_rfc3339())
+        .map(token_response)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+// Subscription feed: confirmed appointments visible to the token's owner.
+// Inspectors get their own visits, clients their strata's, admins everything.
+async fn get_feed(
+    State(state): State<Arc<AppState>>,
+    Path(feed): Path<String>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let token = feed.strip_suffix(".ics").ok_or(StatusCode::NOT_FOUND)?;
+    let conn = state.conn.lock().unwrap();
+    let owner: Option<(String, String, Option<String>)> = conn
+        .query_row(
+            "SELECT u.id, u.role, u.strata_id FROM calendar_tokens t JOIN users u ON u.id = t.user_id
+             WHERE t.token = ?1",
+            [token],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let (user_id, role, strata_id) = owner.ok_or(StatusCode::NOT_FOUND)?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {APPOINTMENT_COLUMNS} FROM appointments
+             WHERE status = 'confirmed'
+               AND CASE ?1
+                     WHEN 'admin' THEN 1
+                     WHEN 'client' THEN service_request_id IN (SELECT id FROM service_requests WHERE strata_id = ?3)
+                     ELSE inspector_id = ?2
+                   END
+             ORDER BY confirmed_date, confirmed_time"
+        ))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let appointments: Vec<Appointment> = stmt
+        .query_map((&role, &user_id, &strata_id), appointment_from_row)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let now = state.clock.now();
+    let mut body = calendar_header("PUBLISH");
+    body.push_str("X-WR-CALNAME:Strata Reserve Planning\r\n");
+    for appointment in &appointments {
+        if let Some(event) = vevent(&conn, appointment, now).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? {
+            body.push_str(&event);
+        }
+    }
+    body.push_str("END:VCALENDAR\r\n");
+
+    Ok(([(header::CONTENT_TYPE, "text/calendar; charset=utf-8")], body))
+}
+
+// Single-event invite. Cancelled appointments produce a CANCEL for the same UID
+// so calendar apps remove the event they imported earlier.
+async fn get_invite(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = state.conn.lock().unwrap();
+    let appointment = load_appointment(&conn, &id)?;
+    if !can_view(&conn, &user, &appointment)? {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let (method, body) = invite(&conn, &appointment, state.clock.now())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::CONFLICT)?; // nothing scheduled yet
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, format!("text/calendar; charset=utf-8; method={}", method)),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"appointment-{}.ics\"", appointment.id)),
+        ],
+        body,
+    ))
+}
+
+fn can_view(conn: &Connection, user: &CurrentUser, appointment: &Appointment) -> Result<bool, StatusCode> {
+    if user.is_admin() || appointment.inspector_id.as_ref() == Some(&user.0.id) {
+        return Ok(true);
+    }
+    let Some(scope) = user.strata_scope() else {
+        return Ok(false);
+    };
+    let strata_id: Option<String> = conn
+        .query_row(
+            "SELECT strata_id FROM service_requests WHERE id = ?1",
+            [&appointment.service_request_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(strata_id == Some(scope))
+}
+
+// iTIP method and calendar body for the appointment's invite
+fn invite(
+    conn: &Connection,
+    appointment: &Appointment,
+    now: DateTime<Utc>,
+) -> rusqlite::Result<Option<(&'static str, String)>> {
+    let method = if appointment.status == AppointmentStatus::Cancelled { "CANCEL" } else { "REQUEST" };
+    let event = vevent(conn, appointment, now)?;
+    Ok(event.map(|event| (method, format!("{}{}END:VCALENDAR\r\n", calendar_header(method), event))))
+}
+
+fn calendar_header(method: &str) -> String {
+    format!(
+        "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:{}\r\nCALSCALE:GREGORIAN\r\nMETHOD:{}\r\n",
+        PRODID, method
+    )
+}
+
+// VEVENT for an appointment with a confirmed (or offered) slot; None otherwise
+fn vevent(conn: &Connection, appointment: &Appointment, now: DateTime<Utc>) -> rusqlite::Result<Option<String>> {
+    let (Some(date), Some(time)) = (&appointment.confirmed_date, &appointment.confirmed_time) else {
+        return Ok(None);
+    };
+    let (Ok(date), Some(time)) = (NaiveDate::parse_from_str(date, "%Y-%m-%d"), clock::parse_local_time(time)) else {
+        return Ok(None);
+    };
+    // Times are local to the strata; events carry the UTC instant so every
+    // client places them correctly whatever zone the site is in
+    let start = clock::to_utc(date, time, clock::request_time_zone(conn, &appointment.service_request_id)?);
+
+    let (location, duration_hours): (Option<String>, Option<f64>) = conn
+        .query_row(
+            "SELECT NULLIF(concat_ws(', ', NULLIF(s.address, ''), NULLIF(s.city, ''),
+                                     NULLIF(trim(concat_ws(' ', s.province, s.postal_code)), '')), ''),
+                    r.estimated_duration_hours
+             FROM service_requests r LEFT JOIN stratas s ON s.id = r.strata_id
+             WHERE r.id = ?1",
+            [&appointment.service_request_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?
+        .unwrap_or((None, None));
+
+    let is_meeting = appointment.appointment_type == "draft_meeting";
+    let minutes = if is_meeting {
+        DRAFT_MEETING_MINUTES
+    } else {
+        (duration_hours.unwrap_or(DEFAULT_DURATION_HOURS) * 60.0) as i64
+    };
+    let end = start + Duration::minutes(minutes);
+
+    // Every offer, reschedule or status change after the request supersedes
+    // what calendars already hold
+    let sequence: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM appointment_events WHERE appointment_id = ?1 AND action <> 'requested'",
+        [&appointment.id],
+        |row| row.get(0),
+    )?;
+
+    // RFC 5546 invites name an organizer: the admin who last acted on the
+    // appointment, else the first admin account
+    let organizer: Option<(String, String)> = conn
+        .query_row(
+            "SELECT u.name, u.email FROM users u
+             LEFT JOIN appointment_events e ON e.actor_id = u.id AND e.appointment_id = ?1
+             WHERE u.role = 'admin'
+             ORDER BY e.created_at IS NULL, e.created_at DESC, e.rowid DESC, u.created_at
+             LIMIT 1",
+            [&appointment.id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    let summary = if is_meeting {
+        format!("Draft report review meeting - {}", appointment.strata_plan)
+    } else {
+        format!("Site inspection - {}", appointment.strata_plan)
+    };
+    let location = match (is_meeting, appointment.meeting_type.as_deref()) {
+        (true, Some("zoom")) => Some("Zoom".to_string()),
+        _ => location,
+    };
+    let status = match appointment.status {
+        AppointmentStatus::Cancelled => "CANCELLED",
+        AppointmentStatus::Offered | AppointmentStatus::Pending => "TENTATIVE",
+        _ => "CONFIRMED",
+    };
+    let stamp = DateTime::parse_from_rfc3339(&appointment.updated_at)
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or(now);
+
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}@srp-portal", appointment.id),
+        format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")),
+        format!("DTSTART:{}", start.format("%Y%m%dT%H%M%SZ")),
+        format!("DTEND:{}", end.format("%Y%m%dT%H%M%SZ")),
+        format!("SEQUENCE:{}", sequence),
+        format!("STATUS:{}", status),
+        format!("SUMMARY:{}", escape_text(&summary)),
+    ];
+    if let Some((name, email)) = organizer {
+        lines.push(format!("ORGANIZER;CN=\"{}\":mailto:{}", name.replace('"', "'"), email));
+    }
+    if let Some(location) = location {
+        lines.push(format!("LOCATION:{}", escape_text(&location)));
+    }
+    if let Some(notes) = appointment.notes.as_deref().filter(|n| !n.is_empty()) {
+        lines.push(format!("DESCRIPTION:{}", escape_text(notes)));
+    }
+    let attendees: Vec<(String, String, bool)> = conn
+        .prepare("SELECT name, email, required FROM appointment_attendees WHERE appointment_id = ?1 ORDER BY name")?
+        .query_map([&appointment.id], |row| Ok((row.get(0)?, row.get(1)?, row.get::<_, i32>(2)? != 0)))?
+        .collect::<rusqlite::Result<_>>()?;
+    for (name, email, required) in attendees {
+        let role = if required { "REQ-PARTICIPANT" } else { "OPT-PARTICIPANT" };
+        lines.push(format!(
+            "ATTENDEE;ROLE={};CN=\"{}\":mailto:{}",
+            role,
+            name.replace('"', "'"),
+            email
+        ));
+    }
+    lines.push("END:VEVENT".to_string());
+
+    Ok(Some(lines.iter().map(|line| fold_line(line)).collect()))
+}
+
+// RFC 5545 TEXT escaping
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+// Lines longer than 75 octets continue on the next line after a space
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for ch in line.chars() {
+        let len = ch.len_utf8();
+        if width + len > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(ch);
+        width += len;
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_values_are_escaped() {
+        assert_eq!(escape_text("Roof; gutters, drains"), r"Roof\; gutters\, drains");
+        assert_eq!(escape_text("C:\\plans\r\nline two\nthree"), r"C:\\plans\nline two\nthree");
+    }
+
+    #[test]
+    fn long_lines_fold_at_75_octets_without_splitting_characters() {
+        assert_eq!(fold_line("SUMMARY:short"), "SUMMARY:short\r\n");
+
+        let line = format!("DESCRIPTION:{}", "é".repeat(80));
+        let folded = fold_line(&line);
+        let parts: Vec<&str> = folded.trim_end_matches("\r\n").split("\r\n").collect();
+        assert!(parts.len() > 1);
+        for part in &parts {
+            assert!(part.len() <= 75, "{} octets", part.len());
+        }
+        assert!(parts[1..].iter().all(|part| part.starts_with(' ')));
+        assert_eq!(folded.replace("\r\n ", "").trim_end_matches("\r\n"), line);
+    }
+
+    #[test]
+    fn cancelled_appointments_produce_a_cancel_for_the_same_event() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id TEXT PRIMARY KEY, name TEXT, email TEXT, role TEXT, created_at TEXT);
+             CREATE TABLE stratas (id TEXT PRIMARY KEY, address TEXT, city TEXT, province TEXT, postal_code TEXT,
+                                   time_zone TEXT);
+             CREATE TABLE service_requests (id TEXT PRIMARY KEY, strata_id TEXT, estimated_duration_hours REAL);
+             CREATE TABLE appointment_events (id TEXT PRIMARY KEY, appointment_id TEXT, action TEXT, actor_id TEXT,
+                                              created_at TEXT);
+             CREATE TABLE appointment_attendees (appointment_id TEXT, user_id TEXT, name TEXT, email TEXT,
+                                                 required INTEGER);
+             INSERT INTO users VALUES ('admin', 'Dana Admin', 'dana@srp.com', 'admin', '2026-01-01');
+             INSERT INTO stratas VALUES ('strata-1', '1 Main St', 'Vancouver', 'BC', 'V6B 1A1', 'America/Vancouver');
+             INSERT INTO service_requests VALUES ('req-1', 'strata-1', 3.0);
+             INSERT INTO appointment_events VALUES
+                 ('e1', 'appt-1', 'requested', 'client', '2026-10-01T00:00:00Z'),
+                 ('e2', 'appt-1', 'offered', 'admin', '2026-10-02T00:00:00Z'),
+                 ('e3', 'appt-1', 'accepted', 'client', '2026-10-03T00:00:00Z'),
+                 ('e4', 'appt-1', 'cancelled', 'admin', '2026-10-04T00:00:00Z');",
+        )
+        .unwrap();
+        let appointment = Appointment {
+            id: "appt-1".to_string(),
+            service_request_id: "req-1".to_string(),
+            strata_plan: "VR 1234".to_string(),
+            appointment_type: "inspection".to_string(),
+            requested_date_1: "2026-11-02".to_string(),
+            requested_time_1: "09:00".to_string(),
+            requested_date_2: None,
+            requested_time_2: None,
+            confirmed_date: Some("2026-11-03".to_string()),
+            confirmed_time: Some("10:00".to_string()),
+            inspector_id: None,
+            status: AppointmentStatus::Cancelled,
+            meeting_type: None,
+            notes: None,
+            cancellation_reason: Some("Site closed".to_string()),
+            created_at: "2026-10-01T00:00:00Z".to_string(),
+            updated_at: "2026-10-04T00:00:00Z".to_string(),
+            starts_at: None,
+        };
+
+        let (method, body) = invite(&conn, &appointment, Utc::now()).unwrap().unwrap();
+        assert_eq!(method, "CANCEL");
+        for line in [
+            "METHOD:CANCEL",
+            "UID:appt-1@srp-portal",
+            "DTSTART:20261103T180000Z",
+            "SEQUENCE:3",
+            "STATUS:CANCELLED",
+            "ORGANIZER;CN=\"Dana Admin\":mailto:dana@srp.com",
+        ] {
+            assert!(body.contains(&format!("{}\r\n", line)), "missing {}", line);
+        }
+        assert!(body.starts_with("BEGIN:VCALENDAR\r\n") && body.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+    }
+}
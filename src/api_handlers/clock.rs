@@ -0,0 +1,133 @@
+use chrono::{DateTime, LocalResult, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use rusqlite::{Connection, OptionalExtension};
+use std::sync::Arc;
+
+// Stratas without a time zone on file are in the Lower Mainland
+pub const DEFAULT_TIME_ZONE: Tz = chrono_tz::America::Vancouver;
+
+// Source of "now" for anything date-dependent. The server runs on the system
+// clock; FIXED_NOW pins it for reproducible runs.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    // The calendar date in `tz` right now, independent of the server's own zone
+    fn today_in(&self, tz: Tz) -> NaiveDate {
+        self.now().with_timezone(&tz).date_naive()
+    }
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+// FIXED_NOW=2026-03-08T09:30:00Z (any RFC 3339 instant) freezes the clock
+pub fn from_env() -> Arc<dyn Clock> {
+    match std::env::var("FIXED_NOW").ok().map(|value| DateTime::parse_from_rfc3339(&value)) {
+        Some(Ok(instant)) => Arc::new(FixedClock(instant.with_timezone(&Utc))),
+        Some(Err(_)) => {
+            println!("Clock: FIXED_NOW is not an RFC 3339 instant, using the system clock");
+            Arc::new(SystemClock)
+        }
+        None => Arc::new(SystemClock),
+    }
+}
+
+pub fn parse_time_zone(name: &str) -> Option<Tz> {
+    name.trim().parse().ok()
+}
+
+// The zone a strata's local dates and times are in
+pub fn strata_time_zone(conn: &Connection, strata_id: &str) -> rusqlite::Result<Tz> {
+    let name: Option<String> = conn
+        .query_row("SELECT time_zone FROM stratas WHERE id = ?1", [strata_id], |row| row.get(0))
+        .optional()?
+        .flatten();
+    Ok(name.as_deref().and_then(parse_time_zone).unwrap_or(DEFAULT_TIME_ZONE))
+}
+
+pub fn request_time_zone(conn: &Connection, service_request_id: &str) -> rusqlite::Result<Tz> {
+    let strata_id: Option<String> = conn
+        .query_row("SELECT strata_id FROM service_requests WHERE id = ?1", [service_request_id], |row| row.get(0))
+        .optional()?;
+    match strata_id {
+        Some(strata_id) => strata_time_zone(conn, &strata_id),
+        None => Ok(DEFAULT_TIME_ZONE),
+    }
+}
+
+// Wall-clock times arrive as "14:30" or "2:30 PM"
+pub fn parse_local_time(value: &str) -> Option<NaiveTime> {
+    let value = value.trim();
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(&value.to_uppercase(), "%I:%M %p"))
+        .ok()
+}
+
+// Stored times are always 24-hour "HH:MM"; anything unparseable is kept as is
+pub fn normalize_time(value: &str) -> String {
+    parse_local_time(value)
+        .map(|time| time.format("%H:%M").to_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+// The instant a local date and time in `tz` refers to. Across a DST change an
+// ambiguous time takes the first occurrence and a skipped one the hour after.
+pub fn to_utc(date: NaiveDate, time: NaiveTime, tz: Tz) -> DateTime<Utc> {
+    let local = date.and_time(time);
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(instant) | LocalResult::Ambiguous(instant, _) => instant.with_timezone(&Utc),
+        LocalResult::None => tz
+            .from_local_datetime(&(local + chrono::Duration::hours(1)))
+            .earliest()
+            .map(|instant| instant.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&local)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixed(instant: &str) -> FixedClock {
+        FixedClock(DateTime::parse_from_rfc3339(instant).unwrap().with_timezone(&Utc))
+    }
+
+    #[test]
+    fn today_follows_the_zone_not_utc() {
+        // Still Saturday evening on the coast when it is already Sunday in UTC
+        let clock = fixed("2026-03-08T06:30:00Z");
+        assert_eq!(clock.today_in(DEFAULT_TIME_ZONE), NaiveDate::from_ymd_opt(2026, 3, 7).unwrap());
+        assert_eq!(clock.today_in(chrono_tz::America::Toronto), NaiveDate::from_ymd_opt(2026, 3, 8).unwrap());
+        assert_eq!(clock.today_in(chrono_tz::UTC), NaiveDate::from_ymd_opt(2026, 3, 8).unwrap());
+    }
+
+    #[test]
+    fn fixed_clock_does_not_move() {
+        let clock = fixed("2026-03-08T09:30:00Z");
+        assert_eq!(clock.now(), clock.now());
+        assert_eq!(clock.now().to_rfc3339(), "2026-03-08T09:30:00+00:00");
+    }
+
+    #[test]
+    fn local_times_across_the_spring_change() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 8).unwrap();
+        let time = |value| parse_local_time(value).unwrap();
+        assert_eq!(to_utc(date, time("01:30"), DEFAULT_TIME_ZONE).to_rfc3339(), "2026-03-08T09:30:00+00:00");
+        // 02:30 does not exist that night and becomes 03:30 PDT
+        assert_eq!(to_utc(date, time("2:30 PM"), DEFAULT_TIME_ZONE).to_rfc3339(), "2026-03-08T21:30:00+00:00");
+        assert_eq!(to_utc(date, time("02:30"), DEFAULT_TIME_ZONE).to_rfc3339(), "2026-03-08T10:30:00+00:00");
+    }
+}
@@ -0,0 +1,513 @@
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use chrono::Datelike;
+use crate::api_handlers::clock;
+use crate::api_handlers::session::CurrentUser;
+use crate::db::AppState;
+use crate::models::{BuildingComponent, PropertyType};
+use crate::models::PropertyType::{Apartment, BareLand, Industrial, MixedUse, Townhouse};
+use rusqlite::{Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+const COMPONENT_COLUMNS: &str = "id, strata_id, catalogue_code, component_type, category, quantity, unit, install_year,
+    expected_useful_life, replacement_unit_cost, location, created_at, updated_at";
+
+const DEFAULT_FORECAST_YEARS: i32 = 30;
+const MAX_FORECAST_YEARS: i32 = 60;
+
+// A standard reserve-study component. Costs are typical Lower Mainland
+// replacement costs per unit, in today's dollars.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogueEntry {
+    code: &'static str,
+    component_type: &'static str,
+    category: &'static str,
+    unit: &'static str,
+    expected_useful_life: u32,
+    replacement_unit_cost: f64,
+    property_types: &'static [PropertyType],
+}
+
+const fn entry(
+    code: &'static str,
+    component_type: &'static str,
+    category: &'static str,
+    unit: &'static str,
+    expected_useful_life: u32,
+    replacement_unit_cost: f64,
+    property_types: &'static [PropertyType],
+) -> CatalogueEntry {
+    CatalogueEntry { code, component_type, category, unit, expected_useful_life, replacement_unit_cost, property_types }
+}
+
+// On a bare land strata the owners' houses, roofs included, are not common
+// property; the corporation only maintains the site
+const GROUND_LEVEL: &[PropertyType] = &[BareLand, Townhouse, Industrial];
+const PITCHED_ROOF: &[PropertyType] = &[Townhouse];
+const FLAT_ROOF: &[PropertyType] = &[Apartment, MixedUse, Industrial];
+const BUILDINGS: &[PropertyType] = &[Townhouse, Apartment, MixedUse, Industrial];
+const RESIDENTIAL: &[PropertyType] = &[Townhouse, Apartment, MixedUse];
+const MID_RISE: &[PropertyType] = &[Apartment, MixedUse];
+const COMMERCIAL: &[PropertyType] = &[MixedUse, Industrial];
+
+const CATALOGUE: &[CatalogueEntry] = &[
+    // Envelope
+    entry("ROOF-SBS", "SBS membrane roofing", "Envelope", "m2", 25, 220.0, FLAT_ROOF),
+    entry("ROOF-ASPHALT", "Asphalt shingle roofing", "Envelope", "m2", 25, 95.0, PITCHED_ROOF),
+    entry("GUTTERS", "Gutters and downspouts", "Envelope", "lm", 25, 40.0, PITCHED_ROOF),
+    entry("WINDOWS", "Vinyl windows and sliding doors", "Envelope", "ea", 30, 1200.0, RESIDENTIAL),
+    entry("STOREFRONT", "Commercial storefront glazing", "Envelope", "m2", 35, 900.0, &[MixedUse]),
+    entry("SIDING", "Fibre-cement siding", "Envelope", "m2", 40, 140.0, RESIDENTIAL),
+    entry("METAL-CLADDING", "Metal wall cladding", "Envelope", "m2", 40, 180.0, &[Industrial]),
+    entry("OVERHEAD-DOORS", "Overhead loading doors", "Envelope", "ea", 25, 9000.0, &[Industrial]),
+    entry("SEALANTS", "Exterior sealants", "Envelope", "lm", 12, 25.0, BUILDINGS),
+    entry("PAINT-EXT", "Exterior painting", "Envelope", "m2", 10, 30.0, BUILDINGS),
+    entry("BALCONY-MEMBRANE", "Balcony vinyl membranes", "Envelope", "m2", 15, 160.0, RESIDENTIAL),
+    // Structure
+    entry("PARKADE-MEMBRANE", "Parkade traffic membrane", "Structure", "m2", 20, 120.0, MID_RISE),
+    // Mechanical and electrical
+    entry("ELEVATOR", "Elevator modernization", "Mechanical", "ea", 25, 250000.0, MID_RISE),
+    entry("DHW-BOILER", "Domestic hot water boiler", "Mechanical", "ea", 20, 45000.0, MID_RISE),
+    entry("HVAC-RTU", "Rooftop HVAC units", "Mechanical", "ea", 20, 35000.0, COMMERCIAL),
+    entry("FIRE-ALARM", "Fire alarm panel and devices", "Life Safety", "ls", 25, 60000.0, FLAT_ROOF),
+    entry("INTERCOM", "Enterphone and access control", "Electrical", "ea", 15, 12000.0, MID_RISE),
+    entry("SITE-LIGHTING", "Site and street lighting", "Electrical", "ea", 25, 3500.0, GROUND_LEVEL),
+    // Interiors
+    entry("LOBBY", "Lobby interior finishes", "Interior", "ls", 15, 40000.0, MID_RISE),
+    entry("CORRIDOR-CARPET", "Corridor carpet", "Interior", "m2", 12, 90.0, MID_RISE),
+    // Site
+    entry("ASPHALT-PAVING", "Asphalt paving", "Site", "m2", 25, 60.0, GROUND_LEVEL),
+    entry("FENCING", "Wood fencing", "Site", "lm", 20, 120.0, GROUND_LEVEL),
+];
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentQuery {
+    strata_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForecastQuery {
+    strata_id: String,
+    years: Option<i32>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForecastItem {
+    component_id: String,
+    component_type: String,
+    cost: f64,
+    basis: &'static str, // install_year, or finding when an inspection revised it
+}
+
+#[derive(Serialize)]
+pub struct ForecastYear {
+    year: i32,
+    total: f64,
+    items: Vec<ForecastItem>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Forecast {
+    strata_id: String,
+    from_year: i32,
+    to_year: i32,
+    years: Vec<ForecastYear>,
+    incomplete: Vec<String>, // components missing a quantity or install year
+}
+
+pub fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(list_components).post(create_component))
+        .route("/:id", put(update_component).delete(delete_component))
+        .route("/catalogue", get(list_catalogue))
+        .route("/seed/:strata_id", post(seed_components))
+        .route("/forecast", get(forecast))
+        .with_state(state)
+}
+
+fn require_admin(user: &CurrentUser) -> Result<(), StatusCode> {
+    if user.is_admin() {
+        Ok(())
+    } else {
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+// Clients only see their own strata's inventory
+fn ensure_can_view(user: &CurrentUser, strata_id: &str) -> Result<(), StatusCode> {
+    match user.strata_scope() {
+        Some(scope) if scope != strata_id => Err(StatusCode::FORBIDDEN),
+        _ => Ok(()),
+    }
+}
+
+fn component_from_row(row: &Row) -> rusqlite::Result<BuildingComponent> {
+    Ok(BuildingComponent {
+        id: row.get(0)?,
+        strata_id: row.get(1)?,
+        catalogue_code: row.get(2)?,
+        component_type: row.get(3)?,
+        category: row.get(4)?,
+        quantity: row.get(5)?,
+        unit: row.get(6)?,
+        install_year: row.get(7)?,
+        expected_useful_life: row.get(8)?,
+        replacement_unit_cost: row.get(9)?,
+        location: row.get(10)?,
+        created_at: row.get(11)?,
+        updated_at: row.get(12)?,
+    })
+}
+
+pub fn load_component(conn: &Connection, id: &str) -> rusqlite::Result<Option<BuildingComponent>> {
+    conn.query_row(
+        &format!("SELECT {COMPONENT_COLUMNS} FROM component_inventory WHERE id = ?1"),
+        [id],
+        component_from_row,
+    )
+    .optional()
+}
+
+fn load_inventory(conn: &Connection, strata_id: &str) -> rusqlite::Result<Vec<BuildingComponent>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {COMPONENT_COLUMNS} FROM component_inventory WHERE strata_id = ?1 ORDER BY category, component_type"
+    ))?;
+    let rows = stmt.query_map([strata_id], component_from_row)?;
+    rows.collect()
+}
+
+fn validate(component: &BuildingComponent) -> Result<(), StatusCode> {
+    let valid = !component.component_type.trim().is_empty()
+        && !component.unit.trim().is_empty()
+        && component.expected_useful_life > 0
+        && component.replacement_unit_cost >= 0.0
+        && component.quantity.is_none_or(|quantity| quantity >= 0.0)
+        && component.install_year.is_none_or(|year| (1800..=2200).contains(&year));
+    if valid {
+        Ok(())
+    } else {
+        Err(StatusCode::BAD_REQUEST)
+    }
+}
+
+fn insert_component(conn: &Connection, component: &BuildingComponent) -> rusqlite::Result<()> {
+    conn.execute(
+        &format!(
+            "INSERT INTO component_inventory ({COMPONENT_COLUMNS})
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)"
+        ),
+        rusqlite::params![
+            &component.id,
+            &component.strata_id,
+            &component.catalogue_code,
+            &component.component_type,
+            &component.category,
+            component.quantity,
+            &component.unit,
+            component.install_year,
+            component.expected_useful_life,
+            component.replacement_unit_cost,
+            &component.location,
+            &component.created_at,
+            &component.updated_at,
+        ],
+    )?;
+    Ok(())
+}
+
+// Add every catalogue component that suits the property type and is not in
+// the inventory yet. Quantities and install years are left for the site visit.
+pub fn seed_inventory(
+    conn: &Connection,
+    strata_id: &str,
+    property_type: PropertyType,
+    now: &str,
+) -> rusqlite::Result<Vec<BuildingComponent>> {
+    let existing: Vec<String> = conn
+        .prepare("SELECT catalogue_code FROM component_inventory WHERE strata_id = ?1 AND catalogue_code IS NOT NULL")?
+        .query_map([strata_id], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let mut added = Vec::new();
+    let suitable = CATALOGUE.iter().filter(|entry| entry.property_types.contains(&property_type));
+    for entry in suitable.filter(|entry| !existing.iter().any(|code| code == entry.code)) {
+        let component = BuildingComponent {
+            id: uuid::Uuid::new_v4().to_string(),
+            strata_id: strata_id.to_string(),
+            catalogue_code: Some(entry.code.to_string()),
+            component_type: entry.component_type.to_string(),
+            category: entry.category.to_string(),
+            quantity: None,
+            unit: entry.unit.to_string(),
+            install_year: None,
+            expected_useful_life: entry.expected_useful_life,
+            replacement_unit_cost: entry.replacement_unit_cost,
+            location: None,
+            created_at: now.to_string(),
+            updated_at: now.to_string(),
+        };
+        insert_component(conn, &component)?;
+        added.push(component);
+    }
+    Ok(added)
+}
+
+async fn list_catalogue() -> Json<&'static [CatalogueEntry]> {
+    Json(CATALOGUE)
+}
+
+async fn list_components(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Query(params): Query<ComponentQuery>,
+) -> Result<Json<Vec<BuildingComponent>>, StatusCode> {
+    let strata_id = user.strata_scope().or(params.strata_id).ok_or(StatusCode::BAD_REQUEST)?;
+    ensure_can_view(&user, &strata_id)?;
+    let conn = state.conn.lock().unwrap();
+    load_inventory(&conn, &strata_id)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn create_component(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Json(payload): Json<BuildingComponent>,
+) -> Result<(StatusCode, Json<BuildingComponent>), StatusCode> {
+    require_admin(&user)?;
+    validate(&payload)?;
+    let conn = state.conn.lock().unwrap();
+    let strata_exists: Option<i64> = conn
+        .query_row("SELECT 1 FROM stratas WHERE id = ?1", [&payload.strata_id], |row| row.get(0))
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if strata_exists.is_none() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let now = state.clock.now().to_rfc3339();
+    let component = BuildingComponent {
+        id: uuid::Uuid::new_v4().to_string(),
+        created_at: now.clone(),
+        updated_at: now,
+        ..payload
+    };
+    insert_component(&conn, &component).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok((StatusCode::CREATED, Json(component)))
+}
+
+// The strata a component belongs to never changes, and an omitted catalogue
+// code keeps the existing one so re-seeding does not duplicate it
+async fn update_component(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+    Json(payload): Json<BuildingComponent>,
+) -> Result<Json<BuildingComponent>, StatusCode> {
+    require_admin(&user)?;
+    validate(&payload)?;
+    let conn = state.conn.lock().unwrap();
+    let existing = load_component(&conn, &id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let component = BuildingComponent {
+        id: existing.id,
+        strata_id: existing.strata_id,
+        catalogue_code: payload.catalogue_code.or(existing.catalogue_code),
+        created_at: existing.created_at,
+        updated_at: state.clock.now().to_rfc3339(),
+        ..payload
+    };
+    conn.execute(
+        "UPDATE component_inventory
+         SET catalogue_code = ?1, component_type = ?2, category = ?3, quantity = ?4, unit = ?5, install_year = ?6,
+             expected_useful_life = ?7, replacement_unit_cost = ?8, location = ?9, updated_at = ?10
+         WHERE id = ?11",
+        rusqlite::params![
+            &component.catalogue_code,
+            &component.component_type,
+            &component.category,
+            component.quantity,
+            &component.unit,
+            component.install_year,
+            component.expected_useful_life,
+            component.replacement_unit_cost,
+            &component.location,
+            &component.updated_at,
+            &component.id,
+        ],
+    )
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(component))
+}
+
+// Components with recorded findings are kept; 409
+async fn delete_component(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    require_admin(&user)?;
+    let conn = state.conn.lock().unwrap();
+    let referenced: Option<i64> = conn
+        .query_row("SELECT 1 FROM inspection_findings WHERE component_id = ?1 LIMIT 1", [&id], |row| row.get(0))
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if referenced.is_some() {
+        return Err(StatusCode::CONFLICT);
+    }
+    let changed = conn
+        .execute("DELETE FROM component_inventory WHERE id = ?1", [&id])
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if changed == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ADMIN: fill a strata's inventory from the catalogue for its property type;
+// 422 when the stored type is not one the catalogue knows
+async fn seed_components(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(strata_id): Path<String>,
+) -> Result<(StatusCode, Json<Vec<BuildingComponent>>), StatusCode> {
+    require_admin(&user)?;
+    let conn = state.conn.lock().unwrap();
+    let property_type: String = conn
+        .query_row("SELECT property_type FROM stratas WHERE id = ?1", [&strata_id], |row| row.get(0))
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let property_type = PropertyType::parse(&property_type).ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+    let added = seed_inventory(&conn, &strata_id, property_type, &state.clock.now().to_rfc3339())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok((StatusCode::CREATED, Json(added)))
+}
+
+// Replacement costs by year in today's dollars. Each component is replaced at
+// the end of its useful life and every life after that; the latest inspection
+// finding, when there is one, resets the remaining life in proportion to its
+// condition score and replaces the cost with the inspector's estimate.
+async fn forecast(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Query(params): Query<ForecastQuery>,
+) -> Result<Json<Forecast>, StatusCode> {
+    ensure_can_view(&user, &params.strata_id)?;
+    let years = params.years.unwrap_or(DEFAULT_FORECAST_YEARS);
+    if !(1..=MAX_FORECAST_YEARS).contains(&years) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let conn = state.conn.lock().unwrap();
+    let tz = clock::strata_time_zone(&conn, &params.strata_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let from_year = state.clock.today_in(tz).year();
+    let to_year = from_year + years - 1;
+    let inventory = load_inventory(&conn, &params.strata_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let mut by_year: BTreeMap<i32, Vec<ForecastItem>> = BTreeMap::new();
+    let mut incomplete = Vec::new();
+    for component in inventory {
+        let (Some(quantity), Some(install_year)) = (component.quantity, component.install_year) else {
+            incomplete.push(component.id);
+            continue;
+        };
+        let life = component.expected_useful_life as i32;
+        let full_cost = quantity * component.replacement_unit_cost;
+
+        let latest: Option<(u8, Option<u32>, String)> = conn
+            .query_row(
+                "SELECT condition_score, estimated_cost, recorded_at FROM inspection_findings
+                 WHERE component_id = ?1 ORDER BY recorded_at DESC LIMIT 1",
+                [&component.id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let finding = latest.and_then(|(score, cost, recorded_at)| {
+            let year = recorded_at.get(..4)?.parse::<i32>().ok()?;
+            let remaining = (life as f64 * score as f64 / 100.0).round() as i32;
+            Some((year + remaining, cost.map(f64::from)))
+        });
+        let (first_year, first_cost, basis) = match finding {
+            Some((year, cost)) => (year, cost.unwrap_or(full_cost), "finding"),
+            None => (install_year + life, full_cost, "install_year"),
+        };
+
+        // Anything already past due is planned for this year
+        let mut year = first_year.max(from_year);
+        let mut cost = first_cost;
+        while year <= to_year {
+            by_year.entry(year).or_default().push(ForecastItem {
+                component_id: component.id.clone(),
+                component_type: component.component_type.clone(),
+                cost,
+                basis,
+            });
+            year += life;
+            cost = full_cost;
+        }
+    }
+
+    let years = by_year
+        .into_iter()
+        .map(|(year, items)| ForecastYear { year, total: items.iter().map(|item| item.cost).sum(), items })
+        .collect();
+    Ok(Json(Forecast { strata_id: params.strata_id, from_year, to_year, years, incomplete }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes_for(property_type: PropertyType) -> Vec<&'static str> {
+        CATALOGUE
+            .iter()
+            .filter(|entry| entry.property_types.contains(&property_type))
+            .map(|entry| entry.code)
+            .collect()
+    }
+
+    #[test]
+    fn every_property_type_has_components() {
+        for property_type in [BareLand, Townhouse, Apartment, MixedUse, Industrial] {
+            assert!(!codes_for(property_type).is_empty(), "{}", property_type.as_str());
+        }
+    }
+
+    #[test]
+    fn bare_land_seeds_only_common_property() {
+        let codes = codes_for(BareLand);
+        for code in ["ROOF-SBS", "ROOF-ASPHALT", "GUTTERS", "WINDOWS", "SIDING"] {
+            assert!(!codes.contains(&code), "{}", code);
+        }
+        assert!(codes.contains(&"ASPHALT-PAVING"));
+    }
+
+    #[test]
+    fn mixed_use_and_industrial_get_their_own_components() {
+        assert!(codes_for(MixedUse).contains(&"STOREFRONT"));
+        assert!(codes_for(MixedUse).contains(&"ELEVATOR"));
+        assert!(codes_for(Industrial).contains(&"OVERHEAD-DOORS"));
+        assert!(!codes_for(Industrial).contains(&"BALCONY-MEMBRANE"));
+    }
+
+    #[test]
+    fn catalogue_codes_are_unique() {
+        let mut codes: Vec<_> = CATALOGUE.iter().map(|entry| entry.code).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), CATALOGUE.len());
+    }
+}
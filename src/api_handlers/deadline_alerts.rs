@@ -0,0 +1,531 @@
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use crate::api_handlers::clock::Clock;
+use crate::api_handlers::milestones::{self, Milestone};
+use crate::api_handlers::notifications;
+use crate::api_handlers::session::CurrentUser;
+use crate::db::AppState;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::str::FromStr;
+use std::sync::{Arc, PoisonError};
+use std::time::Duration;
+
+// When the monitor raises alerts and how often it looks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlertThresholds {
+    // How often the monitor re-checks every open request
+    pub scan_interval: Duration,
+    // Documents still missing this close to the inspection put it at risk
+    pub documents_warning_days: i64,
+    pub documents_critical_days: i64,
+    // Draft not yet sent this close to its deadline
+    pub draft_warning_days: i64,
+    pub draft_critical_days: i64,
+    // Below this progress a draft that close to its deadline is unlikely to make it
+    pub draft_on_track_progress: u8,
+}
+
+impl Default for AlertThresholds {
+    fn default() -> Self {
+        AlertThresholds {
+            scan_interval: Duration::from_secs(60 * 60),
+            documents_warning_days: 14,
+            documents_critical_days: 3,
+            draft_warning_days: 21,
+            draft_critical_days: 7,
+            draft_on_track_progress: 75,
+        }
+    }
+}
+
+impl AlertThresholds {
+    // ALERT_SCAN_MINUTES, ALERT_DOCUMENTS_WARNING_DAYS, ALERT_DOCUMENTS_CRITICAL_DAYS,
+    // ALERT_DRAFT_WARNING_DAYS, ALERT_DRAFT_CRITICAL_DAYS and ALERT_DRAFT_ON_TRACK_PROGRESS
+    // override the defaults
+    pub fn from_env() -> Self {
+        let defaults = AlertThresholds::default();
+        let scan_minutes: u64 = env_or("ALERT_SCAN_MINUTES", defaults.scan_interval.as_secs() / 60);
+        let thresholds = AlertThresholds {
+            scan_interval: Duration::from_secs(scan_minutes.max(1) * 60),
+            documents_warning_days: env_or("ALERT_DOCUMENTS_WARNING_DAYS", defaults.documents_warning_days),
+            documents_critical_days: env_or("ALERT_DOCUMENTS_CRITICAL_DAYS", defaults.documents_critical_days),
+            draft_warning_days: env_or("ALERT_DRAFT_WARNING_DAYS", defaults.draft_warning_days),
+            draft_critical_days: env_or("ALERT_DRAFT_CRITICAL_DAYS", defaults.draft_critical_days),
+            draft_on_track_progress: env_or("ALERT_DRAFT_ON_TRACK_PROGRESS", defaults.draft_on_track_progress)
+                .min(100),
+        };
+        if thresholds.documents_critical_days > thresholds.documents_warning_days
+            || thresholds.draft_critical_days > thresholds.draft_warning_days
+        {
+            println!("Deadline monitor: critical thresholds exceed the warning ones, using the defaults");
+            return defaults;
+        }
+        thresholds
+    }
+}
+
+fn env_or<T: FromStr>(name: &str, default: T) -> T {
+    match std::env::var(name).map(|value| value.trim().parse()) {
+        Ok(Ok(value)) => value,
+        Ok(Err(_)) => {
+            println!("Deadline monitor: {} is not a valid number, using the default", name);
+            default
+        }
+        Err(_) => default,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "info" => Some(Severity::Info),
+            "warning" => Some(Severity::Warning),
+            "critical" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+}
+
+// One rule firing for one request
+#[derive(Debug, Clone)]
+struct Risk {
+    service_request_id: String,
+    kind: &'static str, // documents_missing or draft_at_risk
+    severity: Severity,
+    due_date: String,
+    days_remaining: i64,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadlineAlert {
+    id: String,
+    service_request_id: String,
+    strata_plan: String,
+    kind: String,
+    severity: Severity,
+    message: String,
+    due_date: String,
+    days_remaining: i64,
+    raised_at: String,
+    updated_at: String,
+    resolved_at: Option<String>,
+    acknowledged_by: Option<String>,
+    acknowledged_at: Option<String>,
+}
+
+#[derive(Serialize, Default)]
+pub struct SeverityCounts {
+    critical: usize,
+    warning: usize,
+    info: usize,
+}
+
+#[derive(Serialize)]
+pub struct AlertDashboard {
+    counts: SeverityCounts,
+    alerts: Vec<DeadlineAlert>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanSummary {
+    raised: usize,
+    escalated: usize,
+    resolved: usize,
+    open: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertQuery {
+    severity: Option<String>,
+    include_resolved: Option<bool>,
+}
+
+pub fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(dashboard))
+        .route("/scan", post(scan_now))
+        .route("/:id/acknowledge", put(acknowledge))
+        .with_state(state)
+}
+
+// Periodically re-scan open requests for deadline risk on a thread of its own.
+// A failed or panicking pass is logged and the next one runs as usual.
+pub fn start_monitor(state: Arc<AppState>) {
+    std::thread::Builder::new()
+        .name("deadline-monitor".into())
+        .spawn(move || loop {
+            let thresholds = &state.alert_thresholds;
+            let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+                // A handler that panicked mid-request must not stop the monitor for good
+                let mut conn = state.conn.lock().unwrap_or_else(PoisonError::into_inner);
+                scan(&mut conn, state.clock.as_ref(), thresholds)
+            }));
+            match outcome {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => println!("Deadline monitor: scan failed: {}", e),
+                Err(_) => println!("Deadline monitor: scan panicked"),
+            }
+            std::thread::sleep(thresholds.scan_interval);
+        })
+        .expect("Failed to spawn deadline monitor");
+}
+
+// Apply the rules to one request's milestones
+fn assess(milestones: &[&Milestone], progress: u8, thresholds: &AlertThresholds) -> Vec<Risk> {
+    let find = |kind: &str| milestones.iter().find(|m| m.kind == kind);
+    let mut risks = Vec::new();
+
+    if let (Some(documents), Some(inspection)) = (find("documents_due"), find("inspection")) {
+        let days = inspection.days_remaining;
+        let pending = documents.completed_date.is_none() && inspection.completed_date.is_none();
+        if pending && days <= thresholds.documents_warning_days {
+            let severity = if days <= thresholds.documents_critical_days { Severity::Critical } else { Severity::Warning };
+            let message = if days < 0 {
+                format!("Mandatory documents are still missing and the inspection was due {} days ago", -days)
+            } else {
+                format!("Mandatory documents are still missing {} days before the inspection", days)
+            };
+            risks.push(Risk {
+                service_request_id: inspection.service_request_id.clone(),
+                kind: "documents_missing",
+                severity,
+                due_date: inspection.due_date.clone(),
+                days_remaining: days,
+                message,
+            });
+        }
+    }
+
+    if let Some(deadline) = find("draft_deadline").filter(|deadline| deadline.completed_date.is_none()) {
+        let days = deadline.days_remaining;
+        let on_track = progress >= thresholds.draft_on_track_progress;
+        let (warning, critical) = (thresholds.draft_warning_days, thresholds.draft_critical_days);
+        let severity = if days < 0 || (days <= critical && !on_track) {
+            Some(Severity::Critical)
+        } else if days <= critical || (days <= warning && !on_track) {
+            Some(Severity::Warning)
+        } else if days <= warning {
+            Some(Severity::Info)
+        } else {
+            None
+        };
+        if let Some(severity) = severity {
+            let message = if days < 0 {
+                format!("Draft deadline passed {} days ago without the draft being sent", -days)
+            } else {
+                format!("Draft not sent {} days before its deadline, {}% complete", days, progress)
+            };
+            risks.push(Risk {
+                service_request_id: deadline.service_request_id.clone(),
+                kind: "draft_at_risk",
+                severity,
+                due_date: deadline.due_date.clone(),
+                days_remaining: days,
+                message,
+            });
+        }
+    }
+
+    risks
+}
+
+// Refresh milestones, then raise, update or resolve alerts to match. Admins
+// are notified when an alert is first raised or gets more severe.
+pub fn scan(conn: &mut Connection, clock: &dyn Clock, thresholds: &AlertThresholds) -> rusqlite::Result<ScanSummary> {
+    let tx = conn.transaction()?;
+    let (milestones, _) = milestones::refresh(&tx, None, clock)?;
+    tx.commit()?;
+
+    let progress: HashMap<String, u8> = {
+        let mut stmt = conn.prepare("SELECT id, progress FROM service_requests")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get::<_, Option<u8>>(1)?.unwrap_or(0))))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    let mut by_request: HashMap<&str, Vec<&Milestone>> = HashMap::new();
+    for milestone in &milestones {
+        by_request.entry(milestone.service_request_id.as_str()).or_default().push(milestone);
+    }
+    let plans: HashMap<&str, &str> =
+        milestones.iter().map(|m| (m.service_request_id.as_str(), m.strata_plan.as_str())).collect();
+
+    let mut risks: Vec<Risk> = by_request
+        .iter()
+        .flat_map(|(id, found)| assess(found, progress.get(*id).copied().unwrap_or(0), thresholds))
+        .collect();
+    risks.sort_by(|a, b| (&a.service_request_id, a.kind).cmp(&(&b.service_request_id, b.kind)));
+
+    let now = clock.now().to_rfc3339();
+    let admins: Vec<String> = {
+        let mut stmt = conn.prepare("SELECT id FROM users WHERE role = 'admin'")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+
+    let tx = conn.transaction()?;
+    let mut summary = ScanSummary { raised: 0, escalated: 0, resolved: 0, open: risks.len() };
+    for risk in &risks {
+        let existing: Option<(String, String, Option<String>)> = tx
+            .query_row(
+                "SELECT id, severity, resolved_at FROM deadline_alerts WHERE service_request_id = ?1 AND kind = ?2",
+                (&risk.service_request_id, risk.kind),
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+
+        let notify = match existing {
+            Some((id, severity, resolved_at)) => {
+                let previous = Severity::parse(&severity).unwrap_or(Severity::Info);
+                let escalated = resolved_at.is_none() && risk.severity > previous;
+                // A returning alert starts over; an escalated one needs acknowledging again
+                tx.execute(
+                    "UPDATE deadline_alerts
+                     SET severity = ?1, message = ?2, due_date = ?3, days_remaining = ?4, updated_at = ?5,
+                         raised_at = CASE WHEN resolved_at IS NULL THEN raised_at ELSE ?5 END,
+                         acknowledged_by = CASE WHEN resolved_at IS NULL AND ?6 = 0 THEN acknowledged_by END,
+                         acknowledged_at = CASE WHEN resolved_at IS NULL AND ?6 = 0 THEN acknowledged_at END,
+                         resolved_at = NULL
+                     WHERE id = ?7",
+                    (
+                        risk.severity.as_str(),
+                        &risk.message,
+                        &risk.due_date,
+                        risk.days_remaining,
+                        &now,
+                        escalated,
+                        &id,
+                    ),
+                )?;
+                if resolved_at.is_some() {
+                    summary.raised += 1;
+                } else if escalated {
+                    summary.escalated += 1;
+                }
+                resolved_at.is_some() || escalated
+            }
+            None => {
+                tx.execute(
+                    "INSERT INTO deadline_alerts
+                        (id, service_request_id, kind, severity, message, due_date, days_remaining, raised_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)",
+                    (
+                        uuid::Uuid::new_v4().to_string(),
+                        &risk.service_request_id,
+                        risk.kind,
+                        risk.severity.as_str(),
+                        &risk.message,
+                        &risk.due_date,
+                        risk.days_remaining,
+                        &now,
+                    ),
+                )?;
+                summary.raised += 1;
+                true
+            }
+        };
+
+        if notify && risk.severity >= Severity::Warning {
+            let plan = plans.get(risk.service_request_id.as_str()).copied().unwrap_or_default();
+            let title = format!("Deadline at risk: {}", plan);
+            let kind = if risk.severity == Severity::Critical { "error" } else { "warning" };
+            for admin in &admins {
+                notifications::notify(&tx, admin, &title, &risk.message, kind, Some("/admin/alerts"), &now)?;
+            }
+        }
+    }
+
+    // Anything still open that no rule fired for this time has cleared
+    let open: Vec<(String, String, String)> = {
+        let mut stmt = tx.prepare("SELECT id, service_request_id, kind FROM deadline_alerts WHERE resolved_at IS NULL")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    for (id, request_id, kind) in open {
+        if !risks.iter().any(|r| r.service_request_id == request_id && r.kind == kind) {
+            tx.execute("UPDATE deadline_alerts SET resolved_at = ?1, updated_at = ?1 WHERE id = ?2", (&now, &id))?;
+            summary.resolved += 1;
+        }
+    }
+    tx.commit()?;
+
+    Ok(summary)
+}
+
+// ADMIN: open alerts, most severe and most urgent first
+async fn dashboard(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Query(params): Query<AlertQuery>,
+) -> Result<Json<AlertDashboard>, StatusCode> {
+    if !user.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let severity = params
+        .severity
+        .as_deref()
+        .map(|value| Severity::parse(value).ok_or(StatusCode::BAD_REQUEST))
+        .transpose()?;
+
+    let conn = state.conn.lock().unwrap();
+    let mut stmt = conn
+        .prepare(
+            "SELECT a.id, a.service_request_id, s.strata_plan, a.kind, a.severity, a.message, a.due_date,
+                    a.days_remaining, a.raised_at, a.updated_at, a.resolved_at, a.acknowledged_by, a.acknowledged_at
+             FROM deadline_alerts a
+             JOIN service_requests r ON r.id = a.service_request_id
+             JOIN stratas s ON s.id = r.strata_id
+             WHERE (?1 = 1 OR a.resolved_at IS NULL)",
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let rows = stmt
+        .query_map([params.include_resolved.unwrap_or(false)], |row| {
+            let severity: String = row.get(4)?;
+            Ok(DeadlineAlert {
+                id: row.get(0)?,
+                service_request_id: row.get(1)?,
+                strata_plan: row.get(2)?,
+                kind: row.get(3)?,
+                severity: Severity::parse(&severity).unwrap_or(Severity::Info),
+                message: row.get(5)?,
+                due_date: row.get(6)?,
+                days_remaining: row.get(7)?,
+                raised_at: row.get(8)?,
+                updated_at: row.get(9)?,
+                resolved_at: row.get(10)?,
+                acknowledged_by: row.get(11)?,
+                acknowledged_at: row.get(12)?,
+            })
+        })
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let mut alerts = rows
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let mut counts = SeverityCounts::default();
+    for alert in alerts.iter().filter(|a| a.resolved_at.is_none()) {
+        match alert.severity {
+            Severity::Critical => counts.critical += 1,
+            Severity::Warning => counts.warning += 1,
+            Severity::Info => counts.info += 1,
+        }
+    }
+
+    alerts.retain(|a| severity.is_none_or(|severity| a.severity == severity));
+    alerts.sort_by(|a, b| {
+        a.resolved_at
+            .is_some()
+            .cmp(&b.resolved_at.is_some())
+            .then_with(|| b.severity.cmp(&a.severity))
+            .then_with(|| a.days_remaining.cmp(&b.days_remaining))
+    });
+
+    Ok(Json(AlertDashboard { counts, alerts }))
+}
+
+// ADMIN: run the monitor now rather than waiting for the next pass
+async fn scan_now(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+) -> Result<Json<ScanSummary>, StatusCode> {
+    if !user.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let mut conn = state.conn.lock().unwrap();
+    let summary = scan(&mut conn, state.clock.as_ref(), &state.alert_thresholds)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(summary))
+}
+
+async fn acknowledge(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    if !user.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let conn = state.conn.lock().unwrap();
+    let changed = conn
+        .execute(
+            "UPDATE deadline_alerts SET acknowledged_by = ?1, acknowledged_at = ?2 WHERE id = ?3",
+            (&user.0.id, state.clock.now().to_rfc3339(), &id),
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if changed == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(StatusCode::OK)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft_deadline(days_remaining: i64) -> Milestone {
+        Milestone {
+            service_request_id: "req-1".to_string(),
+            strata_id: "strata-1".to_string(),
+            strata_plan: "VIS 1".to_string(),
+            kind: "draft_deadline",
+            label: "Draft deadline",
+            due_date: "2026-11-01".to_string(),
+            completed_date: None,
+            overdue: days_remaining < 0,
+            days_remaining,
+            projected: false,
+        }
+    }
+
+    fn draft_severity(days_remaining: i64, progress: u8, thresholds: &AlertThresholds) -> Option<Severity> {
+        let deadline = draft_deadline(days_remaining);
+        assess(&[&deadline], progress, thresholds).first().map(|risk| risk.severity)
+    }
+
+    #[test]
+    fn default_thresholds_grade_the_draft_deadline() {
+        let thresholds = AlertThresholds::default();
+        assert_eq!(draft_severity(30, 0, &thresholds), None);
+        assert_eq!(draft_severity(20, 90, &thresholds), Some(Severity::Info));
+        assert_eq!(draft_severity(20, 10, &thresholds), Some(Severity::Warning));
+        assert_eq!(draft_severity(5, 90, &thresholds), Some(Severity::Warning));
+        assert_eq!(draft_severity(5, 10, &thresholds), Some(Severity::Critical));
+        assert_eq!(draft_severity(-1, 100, &thresholds), Some(Severity::Critical));
+    }
+
+    #[test]
+    fn configured_thresholds_replace_the_defaults() {
+        let thresholds = AlertThresholds {
+            draft_warning_days: 40,
+            draft_critical_days: 10,
+            draft_on_track_progress: 50,
+            ..AlertThresholds::default()
+        };
+        assert_eq!(draft_severity(30, 60, &thresholds), Some(Severity::Info));
+        assert_eq!(draft_severity(30, 40, &thresholds), Some(Severity::Warning));
+        assert_eq!(draft_severity(8, 40, &thresholds), Some(Severity::Critical));
+    }
+}
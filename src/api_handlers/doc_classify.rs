@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+
+// Required-document categories an uploaded file can be filed under
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentType {
+    Budget,
+    AgmMinutes,
+    FinancialStatements,
+    StrataPlan,
+    EngineeringReport,
+    InsuranceAppraisal,
+    Unknown,
+}
+
+impl DocumentType {
+    // Matching category id in content/documents/requirements.md
+    pub fn checklist_id(&self) -> Option<&'static str> {
+        match self {
+            DocumentType::Budget | DocumentType::FinancialStatements => Some("finance"),
+            DocumentType::AgmMinutes => Some("agm"),
+            DocumentType::StrataPlan => Some("plan"),
+            DocumentType::EngineeringReport => Some("depr"),
+            DocumentType::InsuranceAppraisal => Some("insurance"),
+            DocumentType::Unknown => None,
+        }
+    }
+}
+
+struct ClassificationRule {
+    document_type: DocumentType,
+    filename_keywords: &'static [&'static str],
+    text_keywords: &'static [&'static str],
+}
+
+const RULES: &[ClassificationRule] = &[
+    ClassificationRule {
+        document_type: DocumentType::Budget,
+        filename_keywords: &["budget", "operating"],
+        text_keywords: &["budget", "proposed budget", "operating fund", "strata fees", "monthly contribution", "fiscal year"],
+    },
+    ClassificationRule {
+        document_type: DocumentType::AgmMinutes,
+        filename_keywords: &["agm", "sgm", "minutes", "meeting"],
+        text_keywords: &["minutes", "annual general meeting", "special general meeting", "quorum", "moved and seconded", "carried", "resolution"],
+    },
+    ClassificationRule {
+        document_type: DocumentType::FinancialStatements,
+        filename_keywords: &["financial", "financials", "statements", "audit", "fs"],
+        text_keywords: &["balance sheet", "statement of operations", "statement of financial position", "auditor", "net assets", "total liabilities"],
+    },
+    ClassificationRule {
+        document_type: DocumentType::StrataPlan,
+        filename_keywords: &["plan", "strataplan", "survey", "entitlement"],
+        text_keywords: &["strata plan", "unit entitlement", "schedule of unit entitlement", "common property", "land title", "strata lot"],
+    },
+    ClassificationRule {
+        document_type: DocumentType::EngineeringReport,
+        filename_keywords: &["engineering", "depreciation", "report", "condition", "assessment"],
+        text_keywords: &["depreciation report", "condition assessment", "remaining useful life", "structural", "engineer", "building envelope"],
+    },
+    ClassificationRule {
+        document_type: DocumentType::InsuranceAppraisal,
+        filename_keywords: &["insurance", "appraisal", "valuation", "certificate"],
+        text_keywords: &["insurance appraisal", "replacement cost", "insured value", "policy", "insurer", "certificate of insurance"],
+    },
+];
+
+// Only the opening of a long document is needed to tell what it is
+const TEXT_SAMPLE_BYTES: usize = 20_000;
+
+// A filename hit is a deliberate label from the uploader, so it outweighs body text
+const FILENAME_WEIGHT: f32 = 2.0;
+const TEXT_WEIGHT: f32 = 1.0;
+
+// Classify from filename tokens and body keywords. Confidence is the winner's
+// share of all matched evidence, scaled down while the evidence is still thin.
+pub fn classify(filename: &str, text: &str) -> (DocumentType, f32, Vec<String>) {
+    let lower_name = filename.to_lowercase();
+    let name_tokens: Vec<&str> = lower_name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    let mut sample_end = text.len().min(TEXT_SAMPLE_BYTES);
+    while !text.is_char_boundary(sample_end) {
+        sample_end -= 1;
+    }
+    let sample = text[..sample_end].to_lowercase();
+
+    let mut scored: Vec<(DocumentType, f32, Vec<String>)> = RULES
+        .iter()
+        .map(|rule| {
+            let mut score = 0.0;
+            let mut signals = Vec::new();
+            for keyword in rule.filename_keywords {
+                if name_tokens.contains(keyword) {
+                    score += FILENAME_WEIGHT;
+                    signals.push(format!("filename:{}", keyword));
+                }
+            }
+            for keyword in rule.text_keywords {
+                if sample.contains(keyword) {
+                    score += TEXT_WEIGHT;
+                    signals.push(format!("text:{}", keyword));
+                }
+            }
+            (rule.document_type, score, signals)
+        })
+        .collect();
+
+    let total: f32 = scored.iter().map(|(_, score, _)| score).sum();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let (document_type, best, signals) = scored.swap_remove(0);
+
+    if best < TEXT_WEIGHT {
+        return (DocumentType::Unknown, 0.0, Vec::new());
+    }
+
+    let confidence = (best / total) * (best / 4.0).min(1.0);
+    (document_type, confidence, signals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filename_alone_is_enough_to_classify() {
+        let (document_type, confidence, signals) = classify("2024_Budget.pdf", "");
+        assert_eq!(document_type, DocumentType::Budget);
+        assert!(confidence > 0.0);
+        assert_eq!(signals, vec!["filename:budget"]);
+    }
+
+    #[test]
+    fn body_text_classifies_a_generically_named_file() {
+        let text = "Minutes of the Annual General Meeting. Quorum was established. Moved and seconded, carried.";
+        let (document_type, _, signals) = classify("scan0001.pdf", text);
+        assert_eq!(document_type, DocumentType::AgmMinutes);
+        assert!(signals.contains(&"text:quorum".to_string()));
+    }
+
+    #[test]
+    fn filename_outweighs_a_single_text_hit() {
+        let (document_type, _, _) = classify("insurance_appraisal.pdf", "the proposed budget was discussed");
+        assert_eq!(document_type, DocumentType::InsuranceAppraisal);
+    }
+
+    #[test]
+    fn no_evidence_is_unknown() {
+        assert_eq!(classify("IMG_0042.jpg", "hello world"), (DocumentType::Unknown, 0.0, Vec::new()));
+        assert_eq!(DocumentType::Unknown.checklist_id(), None);
+    }
+
+    #[test]
+    fn strong_unambiguous_evidence_scores_higher_than_thin_evidence() {
+        let (_, thin, _) = classify("notes.txt", "balance sheet");
+        let (_, strong, _) = classify(
+            "financial_statements.pdf",
+            "Balance sheet, statement of operations, auditor's report, net assets",
+        );
+        assert!(strong > thin);
+        assert!(strong <= 1.0);
+    }
+
+    #[test]
+    fn types_map_to_checklist_categories() {
+        assert_eq!(DocumentType::FinancialStatements.checklist_id(), Some("finance"));
+        assert_eq!(DocumentType::EngineeringReport.checklist_id(), Some("depr"));
+    }
+}
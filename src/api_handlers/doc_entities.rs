@@ -0,0 +1,357 @@
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+// Kinds of facts a reserve planner cares about in strata paperwork
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    StrataPlan,
+    FiscalYearEnd,
+    ContingencyReserveBalance,
+    AgmDate,
+    DollarAmount,
+    BuildingComponent,
+    Contractor,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyEntity {
+    pub kind: EntityKind,
+    pub text: String,  // exactly as it appears in the document
+    pub value: String, // normalized: "VIS 2345", "2024-12-31", "125000.00", ...
+    pub start: usize,  // byte offsets into the extracted text
+    pub end: usize,
+    pub confidence: f32,
+}
+
+// BC land title prefixes seen on strata plans (VIS 2345, LMS 1234, EPS 987, ...)
+static STRATA_PLAN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(VIS|VAS|VR|LMS|LMP|BCS|EPS|EPP|KAS|KAP|NWS|NW|NES|PGS)\s?-?\s?(\d{1,6})\b").unwrap()
+});
+
+const MONTHS: &str = r"(Jan(?:uary)?|Feb(?:ruary)?|Mar(?:ch)?|Apr(?:il)?|May|June?|July?|Aug(?:ust)?|Sept?(?:ember)?|Oct(?:ober)?|Nov(?:ember)?|Dec(?:ember)?)";
+
+static ISO_DATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(\d{4})-(\d{1,2})-(\d{1,2})\b").unwrap());
+static MONTH_DAY_YEAR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(r"(?i)\b{}\.?\s+(\d{{1,2}})(?:st|nd|rd|th)?,?\s+(\d{{4}})\b", MONTHS)).unwrap()
+});
+static DAY_MONTH_YEAR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(r"(?i)\b(\d{{1,2}})(?:st|nd|rd|th)?\s+{}\.?,?\s+(\d{{4}})\b", MONTHS)).unwrap()
+});
+
+static DOLLAR_AMOUNT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\$\s?(\d{1,3}(?:,\d{3})+|\d+)(\.\d{1,2})?(\s?(?:million|m)\b)?").unwrap()
+});
+
+static BUILDING_COMPONENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b(roof membrane|parkade membrane|roof(?:ing)?|membrane|boiler|elevator|window|siding|cladding|balcon(?:y|ie)|deck|railing|gutter|downspout|parkade|fire alarm|sprinkler|hvac|make-up air unit|hot water tank|plumbing|electrical|intercom|garage door|paving|asphalt|fence|fencing|landscaping|irrigation|lobby|hallway|carpet|clubhouse|pool|sump pump|generator)(?:s|es)?\b",
+    )
+    .unwrap()
+});
+
+static CONTRACTOR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\b((?:[A-Z][A-Za-z&'-]*\s+){0,4}?[A-Z][A-Za-z&'-]*\s+(?:Ltd|Limited|Inc|Corp|Corporation|Contracting|Contractors|Construction|Roofing|Plumbing|Mechanical|Electrical|Engineering|Consulting|Restoration|Services)(?:\s+(?:Ltd|Limited|Inc|Corp|Corporation))?\b\.?)",
+    )
+    .unwrap()
+});
+
+// Context cues looked for in the text just before a candidate value
+static FISCAL_CUE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(fiscal year[- ]end(?:ing|ed)?|fiscal year|year[- ]end(?:ing|ed)?|year ending|\bfye\b)").unwrap()
+});
+static AGM_CUE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)(annual general meeting|\bagm\b)").unwrap());
+static CRF_CUE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)(contingency reserve fund|\bcrf\b)").unwrap());
+static CONTRACTOR_CUE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(contractor|quote|quotation|invoice|proposal|awarded|completed by|performed by)").unwrap()
+});
+
+const CONTEXT_WINDOW: usize = 80;
+
+// Leading words a capitalized-name match tends to drag in at the start of a sentence
+const CONTRACTOR_NOISE: &[&str] = &["The", "A", "An", "By", "From", "Per", "Quote", "Invoice", "Contractor"];
+
+pub fn extract_entities(text: &str) -> Vec<KeyEntity> {
+    let mut entities = Vec::new();
+
+    for caps in STRATA_PLAN.captures_iter(text) {
+        let m = caps.get(0).unwrap();
+        let context = preceding(text, m.start());
+        let confidence = if context.to_lowercase().contains("strata plan") { 0.95 } else { 0.8 };
+        entities.push(entity(EntityKind::StrataPlan, m, format!("{} {}", &caps[1], &caps[2]), confidence));
+    }
+
+    for (m, date) in find_dates(text) {
+        let context = preceding(text, m.start());
+        let fiscal = last_cue(&FISCAL_CUE, context);
+        let agm = last_cue(&AGM_CUE, context);
+        // The cue closest to the date wins, so "AGM ... for the year ended <date>" is a fiscal year end
+        let kind = match (fiscal, agm) {
+            (Some(f), Some(a)) if f > a => EntityKind::FiscalYearEnd,
+            (Some(_), None) => EntityKind::FiscalYearEnd,
+            (_, Some(_)) => EntityKind::AgmDate,
+            (None, None) => continue,
+        };
+        let value = date.format("%Y-%m-%d").to_string();
+        entities.push(entity(kind, m, value, 0.85));
+    }
+
+    for caps in DOLLAR_AMOUNT.captures_iter(text) {
+        let m = caps.get(0).unwrap();
+        let Some(amount) = parse_amount(&caps) else { continue };
+        let context = preceding(text, m.start());
+        let (kind, confidence) = if CRF_CUE.is_match(context) {
+            let has_balance = context.to_lowercase().contains("balance");
+            (EntityKind::ContingencyReserveBalance, if has_balance { 0.9 } else { 0.65 })
+        } else {
+            (EntityKind::DollarAmount, 0.95)
+        };
+        entities.push(entity(kind, m, format!("{:.2}", amount), confidence));
+    }
+
+    for caps in BUILDING_COMPONENT.captures_iter(text) {
+        let m = caps.get(0).unwrap();
+        entities.push(entity(EntityKind::BuildingComponent, m, caps[1].to_lowercase(), 0.7));
+    }
+
+    for caps in CONTRACTOR.captures_iter(text) {
+        let m = caps.get(1).unwrap();
+        let Some((offset, name)) = trim_contractor_noise(m.as_str()) else { continue };
+        if name.starts_with("Strata") || name.starts_with("Owners") {
+            continue;
+        }
+        let start = m.start() + offset;
+        let context = preceding(text, start);
+        let confidence = if CONTRACTOR_CUE.is_match(context) { 0.85 } else { 0.7 };
+        entities.push(KeyEntity {
+            kind: EntityKind::Contractor,
+            text: name.to_string(),
+            value: name.trim_end_matches('.').to_string(),
+            start,
+            end: m.end(),
+            confidence,
+        });
+    }
+
+    // "Roofing" inside "Coastal Roofing Ltd." names a trade, not a component
+    let contractor_spans: Vec<(usize, usize)> = entities
+        .iter()
+        .filter(|e| e.kind == EntityKind::Contractor)
+        .map(|e| (e.start, e.end))
+        .collect();
+    entities.retain(|e| {
+        e.kind != EntityKind::BuildingComponent
+            || !contractor_spans.iter().any(|&(start, end)| e.start < end && start < e.end)
+    });
+
+    // Keep the first mention of each distinct fact, in document order
+    entities.sort_by_key(|e| e.start);
+    let mut seen = HashSet::new();
+    entities.retain(|e| seen.insert((e.kind, e.value.to_lowercase())));
+    entities
+}
+
+// One-paragraph digest of what the extractor found, for the review screen
+pub fn summarize(entities: &[KeyEntity]) -> String {
+    let first = |kind: EntityKind| entities.iter().find(|e| e.kind == kind).map(|e| e.value.as_str());
+    let all = |kind: EntityKind| -> Vec<&str> {
+        entities.iter().filter(|e| e.kind == kind).map(|e| e.value.as_str()).collect()
+    };
+
+    let mut sentences = Vec::new();
+    if let Some(plan) = first(EntityKind::StrataPlan) {
+        sentences.push(format!("Strata plan {}.", plan));
+    }
+    if let Some(fye) = first(EntityKind::FiscalYearEnd) {
+        sentences.push(format!("Fiscal year ends {}.", fye));
+    }
+    if let Some(agm) = first(EntityKind::AgmDate) {
+        sentences.push(format!("AGM date {}.", agm));
+    }
+    if let Some(crf) = first(EntityKind::ContingencyReserveBalance) {
+        sentences.push(format!("Contingency reserve fund balance ${}.", crf));
+    }
+    let components = all(EntityKind::BuildingComponent);
+    if !components.is_empty() {
+        sentences.push(format!("Building components mentioned: {}.", components.join(", ")));
+    }
+    let contractors = all(EntityKind::Contractor);
+    if !contractors.is_empty() {
+        sentences.push(format!("Contractors: {}.", contractors.join(", ")));
+    }
+    let amounts = all(EntityKind::DollarAmount).len();
+    if amounts > 0 {
+        sentences.push(format!("{} other dollar amount(s) referenced.", amounts));
+    }
+
+    if sentences.is_empty() {
+        "No reserve planning details were recognized in this document.".to_string()
+    } else {
+        sentences.join(" ")
+    }
+}
+
+fn entity(kind: EntityKind, m: regex::Match, value: String, confidence: f32) -> KeyEntity {
+    KeyEntity {
+        kind,
+        text: m.as_str().to_string(),
+        value,
+        start: m.start(),
+        end: m.end(),
+        confidence,
+    }
+}
+
+// Up to CONTEXT_WINDOW bytes before `pos`, cut on a char boundary
+fn preceding(text: &str, pos: usize) -> &str {
+    let mut from = pos.saturating_sub(CONTEXT_WINDOW);
+    while !text.is_char_boundary(from) {
+        from += 1;
+    }
+    &text[from..pos]
+}
+
+fn last_cue(cue: &Regex, context: &str) -> Option<usize> {
+    cue.find_iter(context).last().map(|m| m.end())
+}
+
+fn find_dates(text: &str) -> Vec<(regex::Match<'_>, NaiveDate)> {
+    let mut dates = Vec::new();
+
+    for caps in ISO_DATE.captures_iter(text) {
+        let (Ok(y), Ok(mo), Ok(d)) = (caps[1].parse(), caps[2].parse(), caps[3].parse()) else { continue };
+        if let Some(date) = NaiveDate::from_ymd_opt(y, mo, d) {
+            dates.push((caps.get(0).unwrap(), date));
+        }
+    }
+    for caps in MONTH_DAY_YEAR.captures_iter(text) {
+        let (Some(mo), Ok(d), Ok(y)) = (month_number(&caps[1]), caps[2].parse(), caps[3].parse()) else { continue };
+        if let Some(date) = NaiveDate::from_ymd_opt(y, mo, d) {
+            dates.push((caps.get(0).unwrap(), date));
+        }
+    }
+    for caps in DAY_MONTH_YEAR.captures_iter(text) {
+        let (Ok(d), Some(mo), Ok(y)) = (caps[1].parse(), month_number(&caps[2]), caps[3].parse()) else { continue };
+        if let Some(date) = NaiveDate::from_ymd_opt(y, mo, d) {
+            dates.push((caps.get(0).unwrap(), date));
+        }
+    }
+
+    dates
+}
+
+fn month_number(name: &str) -> Option<u32> {
+    let month = match name.to_lowercase().get(..3)? {
+        "jan" => 1,
+        "feb" => 2,
+        "mar" => 3,
+        "apr" => 4,
+        "may" => 5,
+        "jun" => 6,
+        "jul" => 7,
+        "aug" => 8,
+        "sep" => 9,
+        "oct" => 10,
+        "nov" => 11,
+        "dec" => 12,
+        _ => return None,
+    };
+    Some(month)
+}
+
+fn parse_amount(caps: &regex::Captures) -> Option<f64> {
+    let whole = caps[1].replace(',', "");
+    let cents = caps.get(2).map(|m| m.as_str()).unwrap_or("");
+    let amount: f64 = format!("{}{}", whole, cents).parse().ok()?;
+    Some(if caps.get(3).is_some() { amount * 1_000_000.0 } else { amount })
+}
+
+// Strip sentence-start words like "Quote" or "The" off a contractor match,
+// returning the byte offset of what remains.
+fn trim_contractor_noise(name: &str) -> Option<(usize, &str)> {
+    let mut offset = 0;
+    let mut rest = name;
+    while let Some((word, tail)) = rest.split_once(char::is_whitespace) {
+        if !CONTRACTOR_NOISE.contains(&word) {
+            break;
+        }
+        let trimmed = tail.trim_start();
+        offset += rest.len() - trimmed.len();
+        rest = trimmed;
+    }
+    // A bare suffix ("Services") left over is not a name
+    if rest.split_whitespace().count() < 2 {
+        return None;
+    }
+    Some((offset, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(entities: &[KeyEntity], kind: EntityKind) -> Vec<&str> {
+        entities.iter().filter(|e| e.kind == kind).map(|e| e.value.as_str()).collect()
+    }
+
+    #[test]
+    fn strata_plan_numbers_are_normalized() {
+        let entities = extract_entities("Owners, Strata Plan VIS-2345 and LMS1234.");
+        assert_eq!(values(&entities, EntityKind::StrataPlan), vec!["VIS 2345", "LMS 1234"]);
+    }
+
+    #[test]
+    fn nearest_cue_decides_between_fiscal_year_end_and_agm() {
+        let text = "The AGM was held on March 3, 2025. Financial statements for the year ended 31 December 2024.";
+        let entities = extract_entities(text);
+        assert_eq!(values(&entities, EntityKind::AgmDate), vec!["2025-03-03"]);
+        assert_eq!(values(&entities, EntityKind::FiscalYearEnd), vec!["2024-12-31"]);
+    }
+
+    #[test]
+    fn dates_without_a_cue_are_ignored() {
+        assert!(extract_entities("Printed 2024-05-01.").is_empty());
+    }
+
+    #[test]
+    fn crf_balance_is_told_apart_from_other_amounts() {
+        let text = "Contingency reserve fund balance: $125,000.50.\n\n\
+                    Council reviewed three painting proposals for the exterior trim. Painting quote $8,400.";
+        let entities = extract_entities(text);
+        assert_eq!(values(&entities, EntityKind::ContingencyReserveBalance), vec!["125000.50"]);
+        assert_eq!(values(&entities, EntityKind::DollarAmount), vec!["8400.00"]);
+    }
+
+    #[test]
+    fn million_suffix_scales_the_amount() {
+        let entities = extract_entities("Replacement cost of $2.5 million");
+        assert_eq!(values(&entities, EntityKind::DollarAmount), vec!["2500000.00"]);
+    }
+
+    #[test]
+    fn contractor_names_drop_leading_noise_and_do_not_count_as_components() {
+        let entities = extract_entities("Quote from Coastal Roofing Ltd. for the boiler.");
+        assert_eq!(values(&entities, EntityKind::Contractor), vec!["Coastal Roofing Ltd"]);
+        assert_eq!(values(&entities, EntityKind::BuildingComponent), vec!["boiler"]);
+    }
+
+    #[test]
+    fn repeated_facts_are_reported_once() {
+        let entities = extract_entities("Elevator service. The elevator was modernized.");
+        assert_eq!(values(&entities, EntityKind::BuildingComponent), vec!["elevator"]);
+    }
+
+    #[test]
+    fn summary_mentions_what_was_found() {
+        let entities = extract_entities("Strata Plan EPS 987, fiscal year end 2024-12-31");
+        assert_eq!(summarize(&entities), "Strata plan EPS 987. Fiscal year ends 2024-12-31.");
+        assert_eq!(summarize(&[]), "No reserve planning details were recognized in this document.");
+    }
+}
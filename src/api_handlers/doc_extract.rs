@@ -0,0 +1,153 @@
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::Serialize;
+use std::io::{Cursor, Read};
+
+// Supported input formats for the document pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileType {
+    Pdf,
+    Docx,
+    #[serde(rename = "txt")]
+    Text,
+    Unknown,
+}
+
+// Sniff the file type from its leading bytes, falling back to the extension
+// only when the content itself is inconclusive.
+pub fn detect_file_type(filename: &str, bytes: &[u8]) -> FileType {
+    if bytes.starts_with(b"%PDF-") {
+        return FileType::Pdf;
+    }
+    if bytes.starts_with(b"PK\x03\x04") {
+        // DOCX is a zip container; make sure it actually holds a Word body
+        let is_docx = zip::ZipArchive::new(Cursor::new(bytes))
+            .map(|mut archive| archive.by_name("word/document.xml").is_ok())
+            .unwrap_or(false);
+        return if is_docx { FileType::Docx } else { FileType::Unknown };
+    }
+    if !bytes.is_empty() && std::str::from_utf8(bytes).is_ok() {
+        return FileType::Text;
+    }
+
+    let extension = filename.rsplit('.').next().unwrap_or("").to_lowercase();
+    match extension.as_str() {
+        "pdf" => FileType::Pdf,
+        "docx" => FileType::Docx,
+        "txt" | "md" | "csv" => FileType::Text,
+        _ => FileType::Unknown,
+    }
+}
+
+pub fn extract_text(file_type: FileType, bytes: &[u8]) -> Result<String, String> {
+    let text = match file_type {
+        FileType::Pdf => extract_pdf(bytes)?,
+        FileType::Docx => extract_docx(bytes)?,
+        FileType::Text => extract_plain(bytes)?,
+        FileType::Unknown => return Err("Unsupported file type".to_string()),
+    };
+
+    if text.trim().is_empty() {
+        return Err("No text could be extracted (scanned or empty document?)".to_string());
+    }
+    Ok(text)
+}
+
+fn extract_pdf(bytes: &[u8]) -> Result<String, String> {
+    // pdf-extract panics on some malformed inputs, so contain it here
+    std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(bytes))
+        .map_err(|_| "PDF parser crashed on malformed input".to_string())?
+        .map_err(|e| format!("PDF extraction failed: {}", e))
+}
+
+fn extract_docx(bytes: &[u8]) -> Result<String, String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| format!("DOCX is not a valid zip archive: {}", e))?;
+    let mut xml = String::new();
+    archive
+        .by_name("word/document.xml")
+        .map_err(|_| "DOCX is missing word/document.xml".to_string())?
+        .read_to_string(&mut xml)
+        .map_err(|e| format!("DOCX body could not be read: {}", e))?;
+
+    let mut reader = Reader::from_str(&xml);
+    let mut text = String::new();
+    let mut in_text_run = false;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) if e.name().as_ref() == b"w:t" => in_text_run = true,
+            Ok(Event::End(e)) => match e.name().as_ref() {
+                b"w:t" => in_text_run = false,
+                b"w:p" => text.push('\n'),
+                _ => {}
+            },
+            Ok(Event::Empty(e)) => match e.name().as_ref() {
+                b"w:tab" => text.push('\t'),
+                b"w:br" | b"w:cr" => text.push('\n'),
+                _ => {}
+            },
+            Ok(Event::Text(e)) if in_text_run => {
+                let run = e
+                    .unescape()
+                    .map_err(|e| format!("DOCX text could not be decoded: {}", e))?;
+                text.push_str(&run);
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("DOCX XML is malformed: {}", e)),
+            _ => {}
+        }
+    }
+
+    Ok(text)
+}
+
+fn extract_plain(bytes: &[u8]) -> Result<String, String> {
+    String::from_utf8(bytes.to_vec()).map_err(|_| "Text file is not valid UTF-8".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    fn zip_with(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, body) in entries {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(body.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn pdf_magic_wins_over_the_extension() {
+        assert_eq!(detect_file_type("minutes.txt", b"%PDF-1.7\n..."), FileType::Pdf);
+    }
+
+    #[test]
+    fn zip_is_docx_only_with_a_word_body() {
+        let docx = zip_with(&[("word/document.xml", "<w:document/>")]);
+        assert_eq!(detect_file_type("budget.bin", &docx), FileType::Docx);
+
+        let other = zip_with(&[("xl/workbook.xml", "<workbook/>")]);
+        assert_eq!(detect_file_type("budget.docx", &other), FileType::Unknown);
+    }
+
+    #[test]
+    fn utf8_content_is_text_whatever_the_name() {
+        assert_eq!(detect_file_type("report.pdf", "Strata Plan VIS 2345 – budget".as_bytes()), FileType::Text);
+    }
+
+    #[test]
+    fn inconclusive_content_falls_back_to_the_extension() {
+        let binary = [0xff, 0xfe, 0x00, 0x81];
+        assert_eq!(detect_file_type("Scan.PDF", &binary), FileType::Pdf);
+        assert_eq!(detect_file_type("notes.md", &[]), FileType::Text);
+        assert_eq!(detect_file_type("letter.docx", &binary), FileType::Docx);
+        assert_eq!(detect_file_type("photo.jpg", &binary), FileType::Unknown);
+        assert_eq!(detect_file_type("no-extension", &binary), FileType::Unknown);
+    }
+}
@@ -0,0 +1,234 @@
+use crate::api_handlers::ecs_documents::{DocumentReport, DocumentWorld, ProcessingStatus};
+use crate::db::AppState;
+use crossbeam_channel::{Receiver, Sender};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, PoisonError};
+
+// One uploaded file waiting for the pipeline
+pub struct DocumentJob {
+    pub id: String,
+    pub filename: String,
+    pub bytes: Vec<u8>,
+}
+
+// Handle used by the upload endpoint to hand files to the background worker
+#[derive(Clone)]
+pub struct DocumentQueue {
+    sender: Sender<DocumentJob>,
+}
+
+impl DocumentQueue {
+    // Spawn the worker thread that owns the DocumentWorld for the life of the
+    // server. Anything left Pending/Analyzing by a previous run is re-queued.
+    pub fn start(state: Arc<AppState>) -> Self {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let queue = Self { sender };
+
+        match load_unfinished(&state) {
+            Ok(jobs) => {
+                for job in jobs {
+                    queue.enqueue(job);
+                }
+            }
+            Err(e) => println!("Document worker: could not re-queue unfinished documents: {}", e),
+        }
+
+        std::thread::Builder::new()
+            .name("document-worker".into())
+            .spawn(move || run_worker(state, receiver))
+            .expect("Failed to spawn document worker");
+
+        queue
+    }
+
+    pub fn enqueue(&self, job: DocumentJob) {
+        // The worker lives as long as the process, so a send can only fail during shutdown
+        let _ = self.sender.send(job);
+    }
+}
+
+fn run_worker(state: Arc<AppState>, receiver: Receiver<DocumentJob>) {
+    let mut ecs = DocumentWorld::new();
+
+    // Block for the next upload, then take whatever else has queued up behind it
+    // so a burst of uploads is analyzed in a single schedule run.
+    while let Ok(first) = receiver.recv() {
+        let batch: Vec<DocumentJob> = std::iter::once(first).chain(receiver.try_iter()).collect();
+
+        for job in &batch {
+            if let Err(e) = mark_analyzing(&state, &job.id) {
+                println!("Document worker: could not update {}: {}", job.id, e);
+            }
+        }
+
+        let (reports, crashed) = isolate_failures(&mut ecs, DocumentWorld::new, &batch, analyze);
+
+        for report in reports {
+            if let Err(e) = save_report(&state, &report) {
+                println!("Document worker: could not save results for {}: {}", report.metadata.id, e);
+            }
+        }
+        for job in crashed {
+            println!("Document worker: analysis of {} panicked", job.id);
+            if let Err(e) = mark_failed(&state, &job.id, "The document could not be analyzed") {
+                println!("Document worker: could not update {}: {}", job.id, e);
+            }
+        }
+    }
+}
+
+fn analyze(ecs: &mut DocumentWorld, jobs: &[DocumentJob]) -> Vec<DocumentReport> {
+    for job in jobs {
+        ecs.add_document(job.id.clone(), job.filename.clone(), job.bytes.clone());
+    }
+    ecs.run();
+    ecs.drain_finished()
+}
+
+// Run the batch in one go. If that panics, the world may hold half-processed
+// entities, so start a fresh one and retry the jobs one at a time: only the
+// jobs that panic on their own are returned as crashed, and the worker lives on.
+fn isolate_failures<'a, W, T, R>(
+    world: &mut W,
+    fresh: impl Fn() -> W,
+    batch: &'a [T],
+    run: impl Fn(&mut W, &[T]) -> Vec<R>,
+) -> (Vec<R>, Vec<&'a T>) {
+    if let Ok(results) = panic::catch_unwind(AssertUnwindSafe(|| run(world, batch))) {
+        return (results, Vec::new());
+    }
+    *world = fresh();
+
+    let mut results = Vec::new();
+    let mut crashed = Vec::new();
+    for item in batch {
+        match panic::catch_unwind(AssertUnwindSafe(|| run(world, std::slice::from_ref(item)))) {
+            Ok(found) => results.extend(found),
+            Err(_) => {
+                *world = fresh();
+                crashed.push(item);
+            }
+        }
+    }
+    (results, crashed)
+}
+
+fn load_unfinished(state: &AppState) -> rusqlite::Result<Vec<DocumentJob>> {
+    let conn = state.conn.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut stmt = conn.prepare(
+        "SELECT d.id, d.file_name, f.content
+         FROM documents d
+         JOIN document_files f ON f.document_id = d.id
+         JOIN document_analysis a ON a.document_id = d.id
+         WHERE a.processing_status IN ('Pending', 'Analyzing')",
+    )?;
+
+    let jobs = stmt
+        .query_map([], |row| {
+            Ok(DocumentJob {
+                id: row.get(0)?,
+                filename: row.get(1)?,
+                bytes: row.get(2)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(jobs)
+}
+
+fn mark_analyzing(state: &AppState, document_id: &str) -> rusqlite::Result<()> {
+    let conn = state.conn.lock().unwrap_or_else(PoisonError::into_inner);
+    conn.execute(
+        "UPDATE document_analysis SET processing_status = ?1, updated_at = ?2 WHERE document_id = ?3",
+        (ProcessingStatus::Analyzing.as_str(), state.clock.now().to_rfc3339(), document_id),
+    )?;
+    Ok(())
+}
+
+fn mark_failed(state: &AppState, document_id: &str, message: &str) -> rusqlite::Result<()> {
+    let conn = state.conn.lock().unwrap_or_else(PoisonError::into_inner);
+    conn.execute(
+        "UPDATE document_analysis SET processing_status = ?1, error = ?2, updated_at = ?3 WHERE document_id = ?4",
+        (ProcessingStatus::Failed.as_str(), message, state.clock.now().to_rfc3339(), document_id),
+    )?;
+    Ok(())
+}
+
+fn save_report(state: &AppState, report: &DocumentReport) -> rusqlite::Result<()> {
+    let analysis = serde_json::to_string(&report.analysis).ok();
+    let classification = report
+        .classification
+        .as_ref()
+        .and_then(|c| serde_json::to_string(c).ok());
+    let file_type = serde_json::to_value(report.metadata.file_type)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string));
+
+    let conn = state.conn.lock().unwrap_or_else(PoisonError::into_inner);
+    conn.execute(
+        "UPDATE document_analysis
+         SET processing_status = ?1, file_type = ?2, analysis = ?3, classification = ?4,
+             error = ?5, extracted_text = ?6, updated_at = ?7
+         WHERE document_id = ?8",
+        (
+            report.status.as_str(),
+            file_type,
+            analysis,
+            classification,
+            report.error.as_ref().map(|e| e.message.clone()),
+            report.text.as_deref(),
+            state.clock.now().to_rfc3339(),
+            &report.metadata.id,
+        ),
+    )?;
+
+    // Auto-fill the checklist type when the uploader did not pick one
+    if let Some(classification) = &report.classification {
+        if let Some(checklist_id) = &classification.checklist_id {
+            conn.execute(
+                "UPDATE documents SET document_type = ?1 WHERE id = ?2 AND document_type = ''",
+                (checklist_id, &report.metadata.id),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Stands in for the ECS: records every run and panics on "bad"
+    fn run(world: &mut Vec<String>, jobs: &[&str]) -> Vec<String> {
+        for job in jobs {
+            world.push(job.to_string());
+        }
+        assert!(!jobs.contains(&"bad"), "malformed file");
+        jobs.iter().map(|job| format!("{job} analyzed")).collect()
+    }
+
+    #[test]
+    fn a_clean_batch_runs_once() {
+        let mut world = Vec::new();
+        let (results, crashed) = isolate_failures(&mut world, Vec::new, &["a", "b"], run);
+        assert_eq!(results, vec!["a analyzed", "b analyzed"]);
+        assert!(crashed.is_empty());
+        assert_eq!(world, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn jobs_around_a_panicking_one_are_still_analyzed() {
+        let mut world = Vec::new();
+        let batch = ["before", "bad", "after"];
+        let (results, crashed) = isolate_failures(&mut world, Vec::new, &batch, run);
+        assert_eq!(results, vec!["before analyzed", "after analyzed"]);
+        assert_eq!(crashed, vec![&"bad"]);
+        // The world the panic left behind was replaced
+        assert_eq!(world, vec!["after"]);
+
+        // and the next batch goes through as usual
+        let (results, crashed) = isolate_failures(&mut world, Vec::new, &["next"], run);
+        assert_eq!(results, vec!["next analyzed"]);
+        assert!(crashed.is_empty());
+    }
+}
+
@@ -0,0 +1,292 @@
+use axum::extract::{DefaultBodyLimit, FromRef, Json, Multipart, Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post, put};
+use axum::Router;
+use crate::api_handlers::doc_classify::DocumentType;
+use crate::api_handlers::document_worker::{DocumentJob, DocumentQueue};
+use crate::api_handlers::ecs_documents::ProcessingStatus;
+use crate::api_handlers::milestones;
+use crate::api_handlers::session::CurrentUser;
+use crate::db::AppState;
+use crate::models::Document;
+use rusqlite::OptionalExtension;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct DocumentsState {
+    pub db: Arc<AppState>,
+    pub queue: DocumentQueue,
+}
+
+impl FromRef<DocumentsState> for Arc<AppState> {
+    fn from_ref(state: &DocumentsState) -> Self {
+        state.db.clone()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentListQuery {
+    service_request_id: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentAnalysis {
+    document_id: String,
+    status: ProcessingStatus,
+    file_type: Option<String>,
+    analysis: Option<Value>,
+    classification: Option<Value>,
+    error: Option<String>,
+    updated_at: String,
+}
+
+// An admin's verdict on the automatic classification; without a type the
+// guess is confirmed as is.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassificationReview {
+    document_type: Option<DocumentType>,
+}
+
+// Scanned depreciation reports and financial packages run well past axum's 2 MB default
+const MAX_UPLOAD_BYTES: usize = 50 * 1024 * 1024;
+
+pub fn router(state: Arc<AppState>, queue: DocumentQueue) -> Router {
+    Router::new()
+        .route("/", get(list_documents))
+        .route("/upload", post(upload_document).layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES)))
+        .route("/:id/analysis", get(get_document_analysis))
+        .route("/:id/classification", put(review_classification))
+        .with_state(DocumentsState { db: state, queue })
+}
+
+// Accepts a multipart form with a `file` part plus `serviceRequestId` and the
+// optional `documentType`, `category` and `name` fields. The file is recorded
+// as uploaded by the caller, stored and queued; analysis happens in the background.
+async fn upload_document(
+    State(state): State<DocumentsState>,
+    user: CurrentUser,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<Document>), StatusCode> {
+    let mut service_request_id = None;
+    let mut document_type = String::new();
+    let mut category = "mandatory".to_string();
+    let mut name = None;
+    let mut file: Option<(String, Option<String>, Vec<u8>)> = None;
+
+    while let Some(field) = multipart.next_field().await.map_err(|_| StatusCode::BAD_REQUEST)? {
+        match field.name().unwrap_or("") {
+            "file" => {
+                let file_name = field.file_name().unwrap_or("upload").to_string();
+                let mime_type = field.content_type().map(str::to_string);
+                let bytes = field.bytes().await.map_err(|_| StatusCode::BAD_REQUEST)?;
+                file = Some((file_name, mime_type, bytes.to_vec()));
+            }
+            other => {
+                let other = other.to_string();
+                let value = field.text().await.map_err(|_| StatusCode::BAD_REQUEST)?;
+                match other.as_str() {
+                    "serviceRequestId" => service_request_id = Some(value),
+                    "documentType" => document_type = value,
+                    "category" => category = value,
+                    "name" => name = Some(value),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let service_request_id = service_request_id.ok_or(StatusCode::BAD_REQUEST)?;
+    let (file_name, mime_type, bytes) = file.ok_or(StatusCode::BAD_REQUEST)?;
+    let now = state.db.clock.now().to_rfc3339();
+
+    let document = Document {
+        id: uuid::Uuid::new_v4().to_string(),
+        service_request_id,
+        name: name.unwrap_or_else(|| file_name.clone()),
+        file_name,
+        document_type,
+        category,
+        status: "uploaded".to_string(),
+        uploaded_by: user.0.id,
+        uploaded_at: Some(now.clone()),
+        reviewed_by: None,
+        reviewed_at: None,
+        file_size: Some(bytes.len() as u64),
+        mime_type,
+    };
+
+    {
+        let mut conn = state.db.conn.lock().unwrap();
+        let tx = conn.transaction().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        tx.execute(
+            "INSERT INTO documents (id, service_request_id, name, file_name, document_type, category, status, uploaded_by, uploaded_at, file_size, mime_type)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            (
+                &document.id,
+                &document.service_request_id,
+                &document.name,
+                &document.file_name,
+                &document.document_type,
+                &document.category,
+                &document.status,
+                &document.uploaded_by,
+                &document.uploaded_at,
+                document.file_size.map(|s| s as i64),
+                &document.mime_type,
+            ),
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        tx.execute(
+            "INSERT INTO document_files (document_id, content) VALUES (?1, ?2)",
+            (&document.id, &bytes),
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        tx.execute(
+            "INSERT INTO document_analysis (document_id, processing_status, updated_at) VALUES (?1, ?2, ?3)",
+            (&document.id, ProcessingStatus::Pending.as_str(), &now),
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        // Mandatory documents mark the request's documents milestone received
+        milestones::refresh_request(&tx, &document.service_request_id, state.db.clock.as_ref())
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        tx.commit().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+
+    state.queue.enqueue(DocumentJob {
+        id: document.id.clone(),
+        filename: document.file_name.clone(),
+        bytes,
+    });
+
+    Ok((StatusCode::ACCEPTED, Json(document)))
+}
+
+async fn list_documents(
+    State(state): State<DocumentsState>,
+    Query(params): Query<DocumentListQuery>,
+) -> Result<Json<Vec<Document>>, StatusCode> {
+    let conn = state.db.conn.lock().unwrap();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, service_request_id, name, file_name, document_type, category, status, uploaded_by, uploaded_at, reviewed_by, reviewed_at, file_size, mime_type
+             FROM documents
+             WHERE ?1 IS NULL OR service_request_id = ?1
+             ORDER BY uploaded_at DESC",
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let documents = stmt
+        .query_map([&params.service_request_id], |row| {
+            Ok(Document {
+                id: row.get(0)?,
+                service_request_id: row.get(1)?,
+                name: row.get(2)?,
+                file_name: row.get(3)?,
+                document_type: row.get(4)?,
+                category: row.get(5)?,
+                status: row.get(6)?,
+                uploaded_by: row.get(7)?,
+                uploaded_at: row.get(8)?,
+                reviewed_by: row.get(9)?,
+                reviewed_at: row.get(10)?,
+                file_size: row.get::<_, Option<i64>>(11)?.map(|s| s as u64),
+                mime_type: row.get(12)?,
+            })
+        })
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(Json(documents))
+}
+
+async fn get_document_analysis(
+    State(state): State<DocumentsState>,
+    Path(id): Path<String>,
+) -> Result<Json<DocumentAnalysis>, StatusCode> {
+    let conn = state.db.conn.lock().unwrap();
+    let parse_json = |raw: Option<String>| raw.and_then(|s| serde_json::from_str(&s).ok());
+
+    let analysis = conn.query_row(
+        "SELECT document_id, processing_status, file_type, analysis, classification, error, updated_at
+         FROM document_analysis WHERE document_id = ?",
+        [id],
+        |row| {
+            let status: String = row.get(1)?;
+            Ok(DocumentAnalysis {
+                document_id: row.get(0)?,
+                status: ProcessingStatus::parse(&status).unwrap_or(ProcessingStatus::Pending),
+                file_type: row.get(2)?,
+                analysis: parse_json(row.get(3)?),
+                classification: parse_json(row.get(4)?),
+                error: row.get(5)?,
+                updated_at: row.get(6)?,
+            })
+        },
+    );
+
+    match analysis {
+        Ok(analysis) => Ok(Json(analysis)),
+        Err(_) => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+// Admins confirm or correct the classifier's guess. The document is then
+// filed under the matching checklist item and marked reviewed.
+async fn review_classification(
+    State(state): State<DocumentsState>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+    Json(payload): Json<ClassificationReview>,
+) -> Result<Json<Value>, StatusCode> {
+    if !user.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let mut conn = state.db.conn.lock().unwrap();
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT classification FROM document_analysis WHERE document_id = ?1",
+            [&id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    // Nothing to confirm until the worker has classified the file
+    let mut classification: Value = stored
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .ok_or(StatusCode::CONFLICT)?;
+
+    let document_type = match payload.document_type {
+        Some(document_type) => document_type,
+        None => serde_json::from_value(classification["document_type"].clone())
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+    };
+    let checklist_id = document_type.checklist_id().ok_or(StatusCode::BAD_REQUEST)?;
+
+    classification["document_type"] = serde_json::json!(document_type);
+    classification["checklist_id"] = serde_json::json!(checklist_id);
+    classification["confirmed"] = serde_json::json!(true);
+
+    let now = state.db.clock.now().to_rfc3339();
+    let tx = conn.transaction().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    tx.execute(
+        "UPDATE document_analysis SET classification = ?1, updated_at = ?2 WHERE document_id = ?3",
+        (classification.to_string(), &now, &id),
+    )
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    tx.execute(
+        "UPDATE documents SET document_type = ?1, status = 'reviewed', reviewed_by = ?2, reviewed_at = ?3 WHERE id = ?4",
+        (checklist_id, &user.0.id, &now, &id),
+    )
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    tx.commit().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(classification))
+}
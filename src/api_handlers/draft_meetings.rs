@@ -0,0 +1,301 @@
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{Duration, NaiveDate};
+use crate::api_handlers::clock::{self, Clock};
+use crate::api_handlers::logistics::{self, record_event};
+use crate::api_handlers::milestones;
+use crate::api_handlers::session::CurrentUser;
+use crate::api_handlers::slots::{self, Place, SlotRules, DRAFT_MEETING_MINUTES};
+use crate::db::AppState;
+use crate::models::{Appointment, AppointmentStatus, LogisticsSlot};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+const MEETING_TYPES: &[&str] = &["in-person", "zoom"];
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DraftSlotQuery {
+    service_request_id: String,
+    meeting_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookDraftMeeting {
+    service_request_id: String,
+    meeting_type: String,
+    requested_date_1: String,
+    requested_time_1: String,
+    requested_date_2: Option<String>,
+    requested_time_2: Option<String>,
+    notes: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attendee {
+    user_id: String,
+    name: String,
+    email: String,
+    required: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DraftMeetingWindow {
+    draft_sent_date: String,
+    next_projected_agm: String,
+    deadline: String, // last day the meeting can be held
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DraftMeeting {
+    appointment: Appointment,
+    attendees: Vec<Attendee>,
+    window: DraftMeetingWindow,
+}
+
+pub fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", post(book_draft_meeting))
+        .route("/slots", get(get_draft_slots))
+        .route("/:id/attendees", get(get_attendees))
+        .with_state(state)
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+// When a service request's draft meeting can be held: after the draft was
+// sent, and no later than the timeline's review deadline, the last working day
+// before the AGM notice goes out. None until the draft has been sent, or once
+// the request is closed.
+type MeetingWindow = (NaiveDate, NaiveDate, NaiveDate); // (draft sent, AGM, deadline)
+
+fn meeting_window(conn: &Connection, clock: &dyn Clock, service_request_id: &str) -> Result<Option<MeetingWindow>, StatusCode> {
+    let draft_sent: Option<String> = conn
+        .query_row("SELECT draft_sent_date FROM service_requests WHERE id = ?1", [service_request_id], |row| row.get(0))
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let Some(draft_sent) = draft_sent.as_deref().and_then(parse_date) else {
+        return Ok(None);
+    };
+
+    let Some(timeline) = milestones::request_timeline(conn, service_request_id, clock)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    else {
+        return Ok(None);
+    };
+    let timeline = timeline.map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+    Ok(Some((draft_sent, timeline.next_projected_agm, timeline.review_deadline)))
+}
+
+fn window_response(draft_sent: NaiveDate, agm: NaiveDate, deadline: NaiveDate) -> DraftMeetingWindow {
+    let format = |date: NaiveDate| date.format("%Y-%m-%d").to_string();
+    DraftMeetingWindow {
+        draft_sent_date: format(draft_sent),
+        next_projected_agm: format(agm),
+        deadline: format(deadline),
+    }
+}
+
+// Draft meetings must fall after the draft was sent and on or before the
+// deadline; other appointment types pass through.
+pub fn ensure_in_window(conn: &Connection, clock: &dyn Clock, appointment: &Appointment, date: &str) -> Result<(), StatusCode> {
+    if appointment.appointment_type != "draft_meeting" {
+        return Ok(());
+    }
+    let date = parse_date(date).ok_or(StatusCode::BAD_REQUEST)?;
+    let (draft_sent, _, deadline) = meeting_window(conn, clock, &appointment.service_request_id)?.ok_or(StatusCode::CONFLICT)?;
+    if date <= draft_sent || date > deadline {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(())
+}
+
+// Council members of the request's strata, who are all expected at the review
+fn council_contacts(conn: &Connection, service_request_id: &str) -> rusqlite::Result<Vec<Attendee>> {
+    conn.prepare(
+        "SELECT u.id, u.name, u.email FROM users u JOIN service_requests r ON r.strata_id = u.strata_id
+         WHERE r.id = ?1 AND u.role = 'client'
+         ORDER BY u.name",
+    )?
+    .query_map([service_request_id], |row| {
+        Ok(Attendee {
+            user_id: row.get(0)?,
+            name: row.get(1)?,
+            email: row.get(2)?,
+            required: true,
+        })
+    })?
+    .collect()
+}
+
+fn load_attendees(conn: &Connection, appointment_id: &str) -> rusqlite::Result<Vec<Attendee>> {
+    conn.prepare(
+        "SELECT user_id, name, email, required FROM appointment_attendees
+         WHERE appointment_id = ?1 ORDER BY name",
+    )?
+    .query_map([appointment_id], |row| {
+        Ok(Attendee {
+            user_id: row.get(0)?,
+            name: row.get(1)?,
+            email: row.get(2)?,
+            required: row.get::<_, i32>(3)? != 0,
+        })
+    })?
+    .collect()
+}
+
+// The strata's own council members, or an admin on their behalf
+fn require_party(user: &CurrentUser, strata_id: &str) -> Result<(), StatusCode> {
+    match user.strata_scope() {
+        Some(scope) if scope != strata_id => Err(StatusCode::FORBIDDEN),
+        None if !user.is_admin() => Err(StatusCode::FORBIDDEN),
+        _ => Ok(()),
+    }
+}
+
+// Open one-hour slots between the day after the draft was sent and the deadline
+async fn get_draft_slots(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Query(params): Query<DraftSlotQuery>,
+) -> Result<Json<Vec<LogisticsSlot>>, StatusCode> {
+    let conn = state.conn.lock().unwrap();
+    let strata_id: String = conn
+        .query_row("SELECT strata_id FROM service_requests WHERE id = ?1", [&params.service_request_id], |row| row.get(0))
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    require_party(&user, &strata_id)?;
+    let (draft_sent, _, to) = meeting_window(&conn, state.clock.as_ref(), &params.service_request_id)?.ok_or(StatusCode::CONFLICT)?;
+
+    let tz = clock::request_time_zone(&conn, &params.service_request_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let today = state.clock.today_in(tz);
+    let from = (draft_sent + Duration::days(1)).max(today + Duration::days(1));
+    if to < from {
+        return Ok(Json(vec![]));
+    }
+
+    let video = params.meeting_type.as_deref() == Some("zoom");
+    let rules = SlotRules {
+        duration_minutes: DRAFT_MEETING_MINUTES,
+        include_travel: !video,
+        ..SlotRules::default()
+    };
+    let place = if video {
+        Place::default()
+    } else {
+        slots::request_details(&conn, &params.service_request_id)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .map(|(place, _)| place)
+            .unwrap_or_default()
+    };
+
+    slots::generate(&conn, from, to, &rules, &place)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+// Client (or admin) requests a review meeting; the admin then confirms or
+// counter-offers through the usual appointment workflow.
+async fn book_draft_meeting(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Json(payload): Json<BookDraftMeeting>,
+) -> Result<(StatusCode, Json<DraftMeeting>), StatusCode> {
+    if !MEETING_TYPES.contains(&payload.meeting_type.as_str()) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let mut conn = state.conn.lock().unwrap();
+    let (strata_id, strata_plan): (String, String) = conn
+        .query_row(
+            "SELECT r.strata_id, COALESCE(s.strata_plan, '') FROM service_requests r
+             LEFT JOIN stratas s ON s.id = r.strata_id WHERE r.id = ?1",
+            [&payload.service_request_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    require_party(&user, &strata_id)?;
+
+    let (draft_sent, agm, deadline) = meeting_window(&conn, state.clock.as_ref(), &payload.service_request_id)?.ok_or(StatusCode::CONFLICT)?;
+    let now = state.clock.now().to_rfc3339();
+    let mut appointment = Appointment {
+        id: uuid::Uuid::new_v4().to_string(),
+        service_request_id: payload.service_request_id,
+        strata_plan,
+        appointment_type: "draft_meeting".to_string(),
+        requested_date_1: payload.requested_date_1,
+        requested_time_1: payload.requested_time_1,
+        requested_date_2: payload.requested_date_2,
+        requested_time_2: payload.requested_time_2,
+        confirmed_date: None,
+        confirmed_time: None,
+        inspector_id: None,
+        status: AppointmentStatus::Pending,
+        meeting_type: Some(payload.meeting_type),
+        notes: payload.notes,
+        cancellation_reason: None,
+        created_at: now.clone(),
+        updated_at: now,
+        starts_at: None,
+    };
+    ensure_in_window(&conn, state.clock.as_ref(), &appointment, &appointment.requested_date_1)?;
+    if let Some(date) = &appointment.requested_date_2 {
+        ensure_in_window(&conn, state.clock.as_ref(), &appointment, date)?;
+    }
+
+    let attendees = council_contacts(&conn, &appointment.service_request_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if attendees.is_empty() {
+        // Nobody from the council to review the draft with
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let tx = conn.transaction().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    logistics::insert_appointment(&tx, &mut appointment).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    for attendee in &attendees {
+        tx.execute(
+            "INSERT INTO appointment_attendees (appointment_id, user_id, name, email, required)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            (&appointment.id, &attendee.user_id, &attendee.name, &attendee.email, attendee.required),
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+    record_event(&tx, &appointment, "requested", None, &user, None)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    tx.commit().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(DraftMeeting {
+            appointment,
+            attendees,
+            window: window_response(draft_sent, agm, deadline),
+        }),
+    ))
+}
+
+async fn get_attendees(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<Attendee>>, StatusCode> {
+    let conn = state.conn.lock().unwrap();
+    let appointment = logistics::load_appointment(&conn, &id)?;
+    logistics::require_viewer(&conn, &user, &appointment)?;
+    load_attendees(&conn, &id)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
@@ -1,9 +1,16 @@
 use bevy_ecs::prelude::*;
 use serde::Serialize;
+use crate::api_handlers::doc_classify::{self, DocumentType};
+use crate::api_handlers::doc_entities::{self, KeyEntity};
+use crate::api_handlers::doc_extract::{self, FileType};
 
 // --- Components ---
 
-// The raw content of a document (simulated)
+// The uploaded file as received, before any extraction
+#[derive(Component, Debug)]
+pub struct RawFile(pub Vec<u8>);
+
+// Plain text pulled out of the uploaded file by an extractor
 #[derive(Component, Debug)]
 pub struct RawContent(pub String);
 
@@ -12,12 +19,11 @@ pub struct RawContent(pub String);
 pub struct DocumentMetadata {
     pub id: String,
     pub filename: String,
-    pub file_type: String, // pdf, docx, etc.
+    pub file_type: FileType, // pdf, docx, txt, unknown
 }
 
 // Status component to track processing state
 #[derive(Component, Debug, Clone, PartialEq, Serialize)]
-#[allow(dead_code)]
 pub enum ProcessingStatus {
     Pending,
     Analyzing,
@@ -25,19 +31,55 @@ pub enum ProcessingStatus {
     Failed,
 }
 
+impl ProcessingStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProcessingStatus::Pending => "Pending",
+            ProcessingStatus::Analyzing => "Analyzing",
+            ProcessingStatus::Completed => "Completed",
+            ProcessingStatus::Failed => "Failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Pending" => Some(ProcessingStatus::Pending),
+            "Analyzing" => Some(ProcessingStatus::Analyzing),
+            "Completed" => Some(ProcessingStatus::Completed),
+            "Failed" => Some(ProcessingStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+// Attached when a document could not be processed, alongside ProcessingStatus::Failed
+#[derive(Component, Debug, Clone, Serialize)]
+pub struct ProcessingError {
+    pub message: String,
+}
+
+// Best guess at which required document this file is, pending admin confirmation
+#[derive(Component, Debug, Clone, Serialize)]
+pub struct DocumentClassification {
+    pub document_type: DocumentType,
+    pub checklist_id: Option<String>,
+    pub confidence: f32,
+    pub signals: Vec<String>, // which filename/text keywords matched
+    pub confirmed: bool,
+}
+
 // Result component: The output of our analysis
-#[derive(Component, Debug, Default, Serialize)]
+#[derive(Component, Debug, Default, Clone, Serialize)]
 pub struct AnalysisResult {
     pub word_count: usize,
-    pub key_entities: Vec<String>,
-    pub sentiment_score: f32,
+    pub key_entities: Vec<KeyEntity>,
     pub summary: String,
 }
 
 // --- Systems ---
 
 // System 1: Ingest "Pending" documents and mark them as "Analyzing"
-pub fn ingestion_system(mut query: Query<(Entity, &mut ProcessingStatus), With<RawContent>>) {
+pub fn ingestion_system(mut query: Query<(Entity, &mut ProcessingStatus), With<RawFile>>) {
     // In a real ECS, this runs in parallel with other systems
     for (entity, mut status) in query.iter_mut() {
         if *status == ProcessingStatus::Pending {
@@ -47,7 +89,31 @@ pub fn ingestion_system(mut query: Query<(Entity, &mut ProcessingStatus), With<R
     }
 }
 
-// System 2: perform heavy analysis on "Analyzing" documents
+// System 2: Run the extractor matching each file type and attach the text.
+// Failures are recorded on the entity instead of aborting the whole batch.
+pub fn extraction_system(
+    mut commands: Commands,
+    mut query: Query<(Entity, &DocumentMetadata, &RawFile, &mut ProcessingStatus), Without<RawContent>>,
+) {
+    for (entity, meta, file, mut status) in query.iter_mut() {
+        if *status != ProcessingStatus::Analyzing {
+            continue;
+        }
+
+        match doc_extract::extract_text(meta.file_type, &file.0) {
+            Ok(text) => {
+                commands.entity(entity).insert(RawContent(text)).remove::<RawFile>();
+            }
+            Err(message) => {
+                println!("ECS: Extraction failed for {}: {}", meta.filename, message);
+                *status = ProcessingStatus::Failed;
+                commands.entity(entity).insert(ProcessingError { message }).remove::<RawFile>();
+            }
+        }
+    }
+}
+
+// System 3: perform heavy analysis on "Analyzing" documents
 // This demonstrates the "Parallel" advantage. We can iterate over chunks of entities.
 pub fn analysis_system(
     mut query: Query<(&RawContent, &mut AnalysisResult, &mut ProcessingStatus)>
@@ -57,27 +123,52 @@ pub fn analysis_system(
         if *status == ProcessingStatus::Analyzing {
             println!("ECS: Analyzing content...");
             
-            // SIMULATED HEAVY WORK (e.g., OCR, NLP)
             let text = &content.0;
             result.word_count = text.split_whitespace().count();
             
-            // Simple keyword extraction mock
-            if text.contains("Strata") {
-                result.key_entities.push("Strata Corp".to_string());
-            }
-            if text.contains("Budget") {
-                result.key_entities.push("Financials".to_string());
-            }
+            // Rule-based extraction of reserve planning facts
+            result.key_entities = doc_entities::extract_entities(text);
+            result.summary = doc_entities::summarize(&result.key_entities);
 
-            // Fake sentiment analysis
-            result.sentiment_score = 0.85; 
-            
             // Mark as done
             *status = ProcessingStatus::Completed;
         }
     }
 }
 
+// System 4: file each document under a required-document category.
+// Runs for failed extractions too, since the filename alone is often enough.
+pub fn classification_system(
+    mut commands: Commands,
+    query: Query<(Entity, &DocumentMetadata, Option<&RawContent>), Without<DocumentClassification>>,
+) {
+    for (entity, meta, content) in query.iter() {
+        let text = content.map(|c| c.0.as_str()).unwrap_or("");
+        let (document_type, confidence, signals) = doc_classify::classify(&meta.filename, text);
+        println!("ECS: Classified {} as {:?} ({:.2})", meta.filename, document_type, confidence);
+
+        commands.entity(entity).insert(DocumentClassification {
+            document_type,
+            checklist_id: document_type.checklist_id().map(str::to_string),
+            confidence,
+            signals,
+            confirmed: false,
+        });
+    }
+}
+
+// Everything known about one document after a run, as returned to the API
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentReport {
+    pub metadata: DocumentMetadata,
+    pub status: ProcessingStatus,
+    pub analysis: AnalysisResult,
+    pub classification: Option<DocumentClassification>,
+    pub error: Option<ProcessingError>,
+    #[serde(skip)]
+    pub text: Option<String>, // extracted text, kept for indexing rather than the API
+}
+
 // --- World Wrapper ---
 
 pub struct DocumentWorld {
@@ -87,20 +178,23 @@ pub struct DocumentWorld {
 
 impl DocumentWorld {
     pub fn new() -> Self {
-        let mut world = World::new();
+        let world = World::new();
         let mut schedule = Schedule::default();
 
         // Add our systems to the schedule
-        schedule.add_systems((ingestion_system, analysis_system).chain());
+        schedule.add_systems(
+            (ingestion_system, extraction_system, analysis_system, classification_system).chain(),
+        );
 
         Self { world, schedule }
     }
 
-    // specific method to add a doc
-    pub fn add_document(&mut self, id: String, filename: String, content: String) {
+    // specific method to add an uploaded file; the type is sniffed from its bytes
+    pub fn add_document(&mut self, id: String, filename: String, bytes: Vec<u8>) {
+        let file_type = doc_extract::detect_file_type(&filename, &bytes);
         self.world.spawn((
-            DocumentMetadata { id, filename, file_type: "pdf".to_string() },
-            RawContent(content),
+            DocumentMetadata { id, filename, file_type },
+            RawFile(bytes),
             ProcessingStatus::Pending,
             AnalysisResult::default(),
         ));
@@ -110,18 +204,38 @@ impl DocumentWorld {
         self.schedule.run(&mut self.world);
     }
     
-    // ✅ FIXED: Use immutable iteration pattern
-    pub fn get_results(&mut self) -> Vec<(DocumentMetadata, ProcessingStatus, AnalysisResult)> {
+    // Collect every document that has reached a final state and remove it from
+    // the world, so a long-lived world only ever holds in-flight work.
+    pub fn drain_finished(&mut self) -> Vec<DocumentReport> {
         let mut results = Vec::new();
-        let mut query = self.world.query::<(&DocumentMetadata, &ProcessingStatus, &AnalysisResult)>();
+        let mut query = self.world.query::<(
+            Entity,
+            &DocumentMetadata,
+            &ProcessingStatus,
+            &AnalysisResult,
+            Option<&DocumentClassification>,
+            Option<&ProcessingError>,
+            Option<&RawContent>,
+        )>();
         
-        for (meta, status, res) in query.iter(&self.world) {
-            results.push((meta.clone(), status.clone(), AnalysisResult {
-                word_count: res.word_count,
-                key_entities: res.key_entities.clone(),
-                sentiment_score: res.sentiment_score,
-                summary: res.summary.clone(),
-            }));
+        let mut finished = Vec::new();
+        for (entity, meta, status, res, classification, error, content) in query.iter(&self.world) {
+            if *status != ProcessingStatus::Completed && *status != ProcessingStatus::Failed {
+                continue;
+            }
+            finished.push(entity);
+            results.push(DocumentReport {
+                metadata: meta.clone(),
+                status: status.clone(),
+                analysis: res.clone(),
+                classification: classification.cloned(),
+                error: error.cloned(),
+                text: content.map(|c| c.0.clone()),
+            });
+        }
+
+        for entity in finished {
+            self.world.despawn(entity);
         }
         results
     }
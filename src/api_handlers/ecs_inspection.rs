@@ -21,6 +21,8 @@ pub struct SimulatedFinding {
 
 // --- Systems ---
 
+// Random condition scores for demos (DEMO_MODE); real findings are recorded by
+// inspectors and stored in inspection_findings
 pub fn finding_simulation_system(
     mut commands: Commands,
     query: Query<(Entity, &BuildingElement), Without<SimulatedFinding>>,
@@ -60,7 +62,7 @@ pub struct InspectionWorld {
 
 impl InspectionWorld {
     pub fn new() -> Self {
-        let mut world = World::new();
+        let world = World::new();
         let mut schedule = Schedule::default();
         schedule.add_systems(finding_simulation_system);
         Self { world, schedule }
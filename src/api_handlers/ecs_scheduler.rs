@@ -1,81 +1,667 @@
 use bevy_ecs::prelude::*;
+use crate::api_handlers::geocode;
+use chrono::{Duration, NaiveTime};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use serde::Serialize;
 
+// Objective weights. Placing a job outweighs any travel saving, urgent jobs are
+// placed first and as early as possible, and travel time settles the rest.
+const ASSIGN_REWARD: f32 = 100.0;
+const PRIORITY_WEIGHT: f32 = 10.0;
+const DELAY_WEIGHT: f32 = 0.5; // per day of delay, per priority point
+const TRAVEL_WEIGHT: f32 = 5.0; // per hour on the road
+const PIN_REWARD: f32 = 1000.0; // an admin's pin outweighs anything the scorer prefers
+const RUNNER_UPS: usize = 3;
+
+pub const DEFAULT_DAILY_HOURS: f32 = 8.0;
+pub const DEFAULT_SEED: u64 = 42;
+pub const DEFAULT_ITERATIONS: usize = 500;
+pub const MAX_ITERATIONS: usize = 5000; // keeps one request from tying up a worker thread
+
 // --- Components ---
 
 #[derive(Component, Debug, Serialize, Clone)]
 pub struct Inspector {
     pub id: String,
     pub name: String,
-    pub current_location: (f32, f32), // Lat/Long mock
+    pub current_location: (f64, f64), // lat/long home base, start and end of every route
     pub skill_level: u8,
+    pub daily_hours: f32, // on site plus travel
 }
 
 #[derive(Component, Debug, Serialize, Clone)]
 pub struct InspectionJob {
     pub id: String,
-    pub location: (f32, f32),
+    pub location: (f64, f64), // lat/long
     pub priority: u8,
     pub estimated_duration_hours: f32,
     pub required_skill_level: u8,
 }
 
+// One inspector-day, blocked by leave, closures or days off
 #[derive(Component, Debug, Serialize, Clone)]
-#[allow(dead_code)]
 pub struct AvailabilitySlot {
     pub inspector_id: String,
     pub date: String,
     pub is_blocked: bool,
+    pub start_time: Option<NaiveTime>, // working hours that day, None when not working
+    pub end_time: Option<NaiveTime>,
+}
+
+// How a placement scores: base + priority bonus - delay and travel penalties.
+// Travel is the extra driving the stop adds to the route.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreBreakdown {
+    pub base: f32,
+    pub priority_bonus: f32,
+    pub delay_penalty: f32,
+    pub extra_distance_km: f32, // great-circle (haversine), not road distance
+    pub travel_penalty: f32,
+    pub skill_margin: u8, // inspector level above the job's requirement
+    pub pin_bonus: f32,
+    pub capacity_hours: f32, // the inspector's day
+    pub booked_hours: f32,   // work and travel on that day, this job included
 }
 
+// Best placement with another inspector, had the optimizer passed this one over
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Candidate {
+    pub inspector_id: String,
+    pub date: String,
+    pub score: f32,
+}
+
+// Admin override: the job goes to this inspector (on this date, if given) or
+// stays unassigned. Takes precedence over the skill requirement.
+#[derive(Component, Debug, Serialize, Clone)]
+pub struct PinnedTo {
+    pub inspector_id: String,
+    pub date: Option<String>,
+}
+
+// Admin override: inspectors never to be given this job
+#[derive(Component, Debug, Serialize, Clone, Default)]
+pub struct ExcludedInspectors(pub Vec<String>);
+
 // Result component attached to jobs after assignment
 #[derive(Component, Debug, Serialize, Clone)]
 pub struct AssignmentResult {
     pub assigned_inspector_id: String,
+    pub date: String,
+    pub sequence: usize, // stop number on the inspector's route that day
+    pub start_time: String, // HH:MM arrival on site
     pub score: f32,
+    pub pinned: bool,
+    pub breakdown: ScoreBreakdown,
+    pub runner_ups: Vec<Candidate>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UnassignedReason {
+    Unqualified,
+    Unavailable,
+    OverCapacity,
+    PinUnsatisfiable,
+}
+
+impl UnassignedReason {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            UnassignedReason::Unqualified => "No inspector who may take the job has the required skill level",
+            UnassignedReason::Unavailable => "No qualified inspector has an open day in the period",
+            UnassignedReason::OverCapacity => "Every open day for a qualified inspector is full",
+            UnassignedReason::PinUnsatisfiable => "The pinned inspector has no open day with room for the job",
+        }
+    }
+}
+
+// Attached instead of an AssignmentResult to jobs the optimizer could not place
+#[derive(Component, Debug, Serialize, Clone)]
+pub struct Unassigned {
+    pub reason: UnassignedReason,
+}
+
+// --- Resources ---
+
+// One inspector's stops for one day, in visiting order
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DayRoute {
+    pub inspector_id: String,
+    pub date: String,
+    pub job_ids: Vec<String>,
+    pub work_hours: f32,
+    pub travel_hours: f32,
+}
+
+#[derive(Resource, Debug, Default, Clone)]
+pub struct ScheduleRoutes(pub Vec<DayRoute>);
+
+#[derive(Resource)]
+pub struct SchedulerConfig {
+    pub strategy: Box<dyn SchedulingStrategy>,
+    pub seed: u64,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            strategy: Box::new(LocalSearchStrategy { iterations: DEFAULT_ITERATIONS }),
+            seed: DEFAULT_SEED,
+        }
+    }
+}
+
+// --- Optimization model ---
+
+pub fn travel_hours(a: (f64, f64), b: (f64, f64)) -> f32 {
+    geocode::driving_hours(a, b) as f32
+}
+
+// A bookable inspector-day; `rank` counts distinct dates from the earliest
+#[derive(Debug, Clone)]
+pub struct Day {
+    pub inspector: usize,
+    pub date: String,
+    pub rank: usize,
+    pub start: NaiveTime,
+    pub capacity: f32, // hours, the shorter of the working window and the daily limit
+}
+
+// Admin overrides for one job
+#[derive(Debug, Clone, Default)]
+pub struct JobOverrides {
+    pub pinned: Option<PinnedTo>,
+    pub excluded: Vec<String>,
+}
+
+pub struct Problem {
+    pub inspectors: Vec<Inspector>,
+    pub jobs: Vec<InspectionJob>,
+    pub overrides: Vec<JobOverrides>, // same order as `jobs`
+    pub days: Vec<Day>,
+}
+
+// Job indices per day (same order as `Problem::days`), in visiting order
+#[derive(Debug, Clone, Default)]
+pub struct Plan {
+    pub routes: Vec<Vec<usize>>,
+}
+
+impl Problem {
+    pub fn new(
+        mut inspectors: Vec<Inspector>,
+        mut jobs: Vec<(InspectionJob, JobOverrides)>,
+        slots: &[AvailabilitySlot],
+    ) -> Self {
+        // Sorted so the same input always gives the same plan
+        inspectors.sort_by(|a, b| a.id.cmp(&b.id));
+        jobs.sort_by(|a, b| a.0.id.cmp(&b.0.id));
+        let (jobs, overrides) = jobs.into_iter().unzip();
+
+        let mut open: Vec<(String, usize, NaiveTime, NaiveTime)> = slots
+            .iter()
+            .filter(|slot| !slot.is_blocked)
+            .filter_map(|slot| {
+                let inspector = inspectors.iter().position(|i| i.id == slot.inspector_id)?;
+                Some((slot.date.clone(), inspector, slot.start_time?, slot.end_time?))
+            })
+            .filter(|(_, _, start, end)| start < end)
+            .collect();
+        open.sort();
+        open.dedup_by(|a, b| a.0 == b.0 && a.1 == b.1);
+
+        let mut days = Vec::new();
+        let mut rank = 0;
+        for (i, (date, inspector, start, end)) in open.iter().enumerate() {
+            if i > 0 && open[i - 1].0 != *date {
+                rank += 1;
+            }
+            let window = (*end - *start).num_minutes() as f32 / 60.0;
+            days.push(Day {
+                inspector: *inspector,
+                date: date.clone(),
+                rank,
+                start: *start,
+                capacity: window.min(inspectors[*inspector].daily_hours),
+            });
+        }
+
+        Self { inspectors, jobs, overrides, days }
+    }
+
+    fn empty_plan(&self) -> Plan {
+        Plan { routes: vec![Vec::new(); self.days.len()] }
+    }
+
+    fn pinned(&self, job: usize) -> bool {
+        self.overrides[job].pinned.is_some()
+    }
+
+    // Whether the job may go on this inspector-day: pinned jobs only to their
+    // pin, others to any inspector with the skill who is not excluded
+    fn allowed(&self, day: usize, job: usize) -> bool {
+        let inspector = &self.inspectors[self.days[day].inspector];
+        let overrides = &self.overrides[job];
+        if overrides.excluded.contains(&inspector.id) {
+            return false;
+        }
+        match &overrides.pinned {
+            Some(pin) => {
+                pin.inspector_id == inspector.id && pin.date.as_ref().is_none_or(|date| *date == self.days[day].date)
+            }
+            None => inspector.skill_level >= self.jobs[job].required_skill_level,
+        }
+    }
+
+    // (hours on site, hours driving) for a route that starts and ends at home
+    pub fn route_hours(&self, day: usize, route: &[usize]) -> (f32, f32) {
+        let home = self.inspectors[self.days[day].inspector].current_location;
+        let mut at = home;
+        let mut work = 0.0;
+        let mut travel = 0.0;
+        for &job in route {
+            travel += travel_hours(at, self.jobs[job].location);
+            work += self.jobs[job].estimated_duration_hours;
+            at = self.jobs[job].location;
+        }
+        if !route.is_empty() {
+            travel += travel_hours(at, home);
+        }
+        (work, travel)
+    }
+
+    fn fits(&self, day: usize, route: &[usize]) -> bool {
+        let (work, travel) = self.route_hours(day, route);
+        work + travel <= self.days[day].capacity + 1e-4
+            && route.iter().all(|&job| self.allowed(day, job))
+    }
+
+    pub fn feasible(&self, plan: &Plan) -> bool {
+        plan.routes.iter().enumerate().all(|(day, route)| self.fits(day, route))
+    }
+
+    fn skill_margin(&self, day: usize, job: usize) -> u8 {
+        self.inspectors[self.days[day].inspector]
+            .skill_level
+            .saturating_sub(self.jobs[job].required_skill_level)
+    }
+
+    fn job_value(&self, day: usize, job: usize) -> f32 {
+        let priority = self.jobs[job].priority.max(1) as f32;
+        let pin = if self.pinned(job) { PIN_REWARD } else { 0.0 };
+        ASSIGN_REWARD + priority * PRIORITY_WEIGHT + pin - self.days[day].rank as f32 * priority * DELAY_WEIGHT
+    }
+
+    fn route_km(&self, day: usize, route: &[usize]) -> f64 {
+        let home = self.inspectors[self.days[day].inspector].current_location;
+        let mut stops = vec![home];
+        stops.extend(route.iter().map(|&job| self.jobs[job].location));
+        if !route.is_empty() {
+            stops.push(home);
+        }
+        stops.windows(2).map(|leg| geocode::haversine_km(leg[0], leg[1])).sum()
+    }
+
+    // Score of `job` at `pos` in `route` (a route without it) on `day`
+    pub fn breakdown(&self, day: usize, route: &[usize], pos: usize, job: usize) -> ScoreBreakdown {
+        let mut with = route.to_vec();
+        with.insert(pos, job);
+        let (work, travel) = self.route_hours(day, &with);
+        let priority = self.jobs[job].priority.max(1) as f32;
+        ScoreBreakdown {
+            base: ASSIGN_REWARD,
+            priority_bonus: priority * PRIORITY_WEIGHT,
+            delay_penalty: self.days[day].rank as f32 * priority * DELAY_WEIGHT,
+            extra_distance_km: (self.route_km(day, &with) - self.route_km(day, route)) as f32,
+            travel_penalty: (travel - self.route_hours(day, route).1) * TRAVEL_WEIGHT,
+            skill_margin: self.skill_margin(day, job),
+            pin_bonus: if self.pinned(job) { PIN_REWARD } else { 0.0 },
+            capacity_hours: self.days[day].capacity,
+            booked_hours: work + travel,
+        }
+    }
+
+    pub fn unassigned_reason(&self, job: usize) -> UnassignedReason {
+        let required = self.jobs[job].required_skill_level;
+        let excluded = &self.overrides[job].excluded;
+        if self.pinned(job) {
+            UnassignedReason::PinUnsatisfiable
+        } else if !self
+            .inspectors
+            .iter()
+            .any(|inspector| inspector.skill_level >= required && !excluded.contains(&inspector.id))
+        {
+            UnassignedReason::Unqualified
+        } else if !(0..self.days.len()).any(|day| self.allowed(day, job)) {
+            UnassignedReason::Unavailable
+        } else {
+            UnassignedReason::OverCapacity
+        }
+    }
+
+    // Best feasible position for a job in one day's route, with the objective gain
+    fn best_position(&self, day: usize, route: &[usize], job: usize) -> Option<(usize, f32)> {
+        if !self.allowed(day, job) {
+            return None;
+        }
+        let before = self.route_hours(day, route).1;
+        let mut best: Option<(usize, f32)> = None;
+        for pos in 0..=route.len() {
+            let mut candidate = route.to_vec();
+            candidate.insert(pos, job);
+            if !self.fits(day, &candidate) {
+                continue;
+            }
+            let gain = self.job_value(day, job) - (self.route_hours(day, &candidate).1 - before) * TRAVEL_WEIGHT;
+            if best.is_none_or(|(_, best_gain)| gain > best_gain + 1e-4) {
+                best = Some((pos, gain));
+            }
+        }
+        best
+    }
+
+    // Top placements with other inspectors, one (their best day) per inspector
+    pub fn runner_ups(&self, plan: &Plan, assigned_day: usize, job: usize) -> Vec<Candidate> {
+        let assigned = self.days[assigned_day].inspector;
+        let mut best: Vec<(usize, usize, f32)> = Vec::new(); // inspector, day, score
+        for (day, route) in plan.routes.iter().enumerate() {
+            let inspector = self.days[day].inspector;
+            if inspector == assigned {
+                continue;
+            }
+            let Some((_, score)) = self.best_position(day, route, job) else {
+                continue;
+            };
+            match best.iter_mut().find(|(i, _, _)| *i == inspector) {
+                Some(entry) if score > entry.2 + 1e-4 => *entry = (inspector, day, score),
+                Some(_) => {}
+                None => best.push((inspector, day, score)),
+            }
+        }
+        best.sort_by(|a, b| b.2.total_cmp(&a.2));
+        best.into_iter()
+            .take(RUNNER_UPS)
+            .map(|(inspector, day, score)| Candidate {
+                inspector_id: self.inspectors[inspector].id.clone(),
+                date: self.days[day].date.clone(),
+                score,
+            })
+            .collect()
+    }
+
+    // Higher is better
+    pub fn objective(&self, plan: &Plan) -> f32 {
+        let mut total = 0.0;
+        for (day, route) in plan.routes.iter().enumerate() {
+            total += route.iter().map(|&job| self.job_value(day, job)).sum::<f32>();
+            total -= self.route_hours(day, route).1 * TRAVEL_WEIGHT;
+        }
+        total
+    }
+
+    pub fn unassigned(&self, plan: &Plan) -> Vec<usize> {
+        (0..self.jobs.len())
+            .filter(|job| !plan.routes.iter().any(|route| route.contains(job)))
+            .collect()
+    }
+
+    // Cheapest feasible (day, position) for a job, by objective change
+    fn best_insertion(&self, plan: &Plan, job: usize) -> Option<(usize, usize)> {
+        let mut best: Option<(f32, usize, usize)> = None;
+        for (day, route) in plan.routes.iter().enumerate() {
+            if let Some((pos, gain)) = self.best_position(day, route, job) {
+                if best.is_none_or(|(best_gain, _, _)| gain > best_gain + 1e-4) {
+                    best = Some((gain, day, pos));
+                }
+            }
+        }
+        best.map(|(_, day, pos)| (day, pos))
+    }
+
+    // Places whatever still fits: pinned jobs, then the most urgent and longest
+    fn fill(&self, plan: &mut Plan) {
+        let mut pending = self.unassigned(plan);
+        pending.sort_by(|&a, &b| {
+            let pins = self.pinned(b).cmp(&self.pinned(a));
+            let (a, b) = (&self.jobs[a], &self.jobs[b]);
+            pins.then(b.priority.cmp(&a.priority))
+                .then(b.estimated_duration_hours.total_cmp(&a.estimated_duration_hours))
+        });
+        for job in pending {
+            if let Some((day, pos)) = self.best_insertion(plan, job) {
+                plan.routes[day].insert(pos, job);
+            }
+        }
+    }
+}
+
+// --- Strategies ---
+
+pub trait SchedulingStrategy: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn plan(&self, problem: &Problem, rng: &mut StdRng) -> Plan;
+}
+
+// Baseline: each job, most urgent first, goes to its cheapest feasible spot
+pub struct GreedyStrategy;
+
+impl SchedulingStrategy for GreedyStrategy {
+    fn name(&self) -> &'static str {
+        "greedy"
+    }
+
+    fn plan(&self, problem: &Problem, _rng: &mut StdRng) -> Plan {
+        let mut plan = problem.empty_plan();
+        problem.fill(&mut plan);
+        plan
+    }
+}
+
+// Starts from the greedy plan and keeps random relocate, swap, reverse and
+// eject moves (refilling freed capacity after each) that improve the objective.
+pub struct LocalSearchStrategy {
+    pub iterations: usize,
+}
+
+impl LocalSearchStrategy {
+    fn perturb(plan: &mut Plan, rng: &mut StdRng) -> bool {
+        let stops: Vec<(usize, usize)> = plan
+            .routes
+            .iter()
+            .enumerate()
+            .flat_map(|(day, route)| (0..route.len()).map(move |pos| (day, pos)))
+            .collect();
+        if stops.is_empty() {
+            return false;
+        }
+        let this = rng.random_range(0..stops.len());
+        let (day, pos) = stops[this];
+
+        match rng.random_range(0..4) {
+            // Relocate a stop to any position on any day
+            0 => {
+                let job = plan.routes[day].remove(pos);
+                let to = rng.random_range(0..plan.routes.len());
+                let at = rng.random_range(0..=plan.routes[to].len());
+                plan.routes[to].insert(at, job);
+            }
+            // Swap two different stops; a lone stop has nothing to swap with
+            1 if stops.len() > 1 => {
+                let mut other = rng.random_range(0..stops.len() - 1);
+                if other >= this {
+                    other += 1;
+                }
+                let (other_day, other_pos) = stops[other];
+                let job = plan.routes[day][pos];
+                plan.routes[day][pos] = plan.routes[other_day][other_pos];
+                plan.routes[other_day][other_pos] = job;
+            }
+            // Reverse part of a route
+            2 => {
+                let end = rng.random_range(pos..plan.routes[day].len());
+                plan.routes[day][pos..=end].reverse();
+            }
+            // Drop a stop so something more valuable can take its place
+            _ => {
+                plan.routes[day].remove(pos);
+            }
+        }
+        true
+    }
+}
+
+impl SchedulingStrategy for LocalSearchStrategy {
+    fn name(&self) -> &'static str {
+        "local-search"
+    }
+
+    fn plan(&self, problem: &Problem, rng: &mut StdRng) -> Plan {
+        let mut best = GreedyStrategy.plan(problem, rng);
+        let mut best_score = problem.objective(&best);
+
+        for _ in 0..self.iterations {
+            let mut candidate = best.clone();
+            if !Self::perturb(&mut candidate, rng) {
+                break;
+            }
+            if !problem.feasible(&candidate) {
+                continue;
+            }
+            problem.fill(&mut candidate);
+            let score = problem.objective(&candidate);
+            if score > best_score + 1e-4 {
+                best = candidate;
+                best_score = score;
+            }
+        }
+        best
+    }
+}
+
+pub fn strategy_by_name(name: &str, iterations: Option<usize>) -> Option<Box<dyn SchedulingStrategy>> {
+    match name {
+        "greedy" => Some(Box::new(GreedyStrategy)),
+        "local-search" => Some(Box::new(LocalSearchStrategy {
+            iterations: iterations.unwrap_or(DEFAULT_ITERATIONS).min(MAX_ITERATIONS),
+        })),
+        _ => None,
+    }
 }
 
 // --- Systems ---
 
-// 1. Scoring System: Calculates fitness of each inspector for each unassigned job
-// In a real spatial ECS, this would use a QuadTree or k-d tree for proximity queries
+// Plans every job against inspector capacity, skills and open days with the
+// configured strategy, then records each job's slot on its route.
 pub fn assignment_scoring_system(
     mut commands: Commands,
-    jobs: Query<(Entity, &InspectionJob), Without<AssignmentResult>>,
+    config: Res<SchedulerConfig>,
+    mut routes: ResMut<ScheduleRoutes>,
+    jobs: Query<(Entity, &InspectionJob)>,
+    overrides: Query<(Option<&PinnedTo>, Option<&ExcludedInspectors>), With<InspectionJob>>,
     inspectors: Query<&Inspector>,
+    slots: Query<&AvailabilitySlot>,
 ) {
-    for (job_entity, job) in jobs.iter() {
-        let mut best_score = -1.0;
-        let mut best_inspector_id = None;
+    let slots: Vec<AvailabilitySlot> = slots.iter().cloned().collect();
+    let problem = Problem::new(
+        inspectors.iter().cloned().collect(),
+        jobs.iter()
+            .map(|(entity, job)| {
+                let (pinned, excluded) = overrides.get(entity).unwrap_or((None, None));
+                let overrides = JobOverrides {
+                    pinned: pinned.cloned(),
+                    excluded: excluded.map(|e| e.0.clone()).unwrap_or_default(),
+                };
+                (job.clone(), overrides)
+            })
+            .collect(),
+        &slots,
+    );
 
-        for inspector in inspectors.iter() {
-            // Simple logic: Can they do the job?
-            if inspector.skill_level < job.required_skill_level {
-                continue;
-            }
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let plan = config.strategy.plan(&problem, &mut rng);
 
-            // Distance Metric (Euclidean mock)
-            let dist = ((inspector.current_location.0 - job.location.0).powi(2) 
-                      + (inspector.current_location.1 - job.location.1).powi(2)).sqrt();
-            
-            // Score = 100 - Distance (Closure is better) + Priority Bonus
-            let score = 100.0 - (dist * 10.0) + (job.priority as f32 * 5.0);
+    // Re-runs start from a clean slate
+    for (entity, _) in jobs.iter() {
+        commands.entity(entity).remove::<(AssignmentResult, Unassigned)>();
+    }
+    let entity_of = |index: usize| {
+        jobs.iter()
+            .find(|(_, job)| job.id == problem.jobs[index].id)
+            .map(|(entity, _)| entity)
+    };
 
-            if score > best_score {
-                best_score = score;
-                best_inspector_id = Some(inspector.id.clone());
+    routes.0.clear();
+    for (day, route) in plan.routes.iter().enumerate() {
+        if route.is_empty() {
+            continue;
+        }
+        let inspector = &problem.inspectors[problem.days[day].inspector];
+        let date = &problem.days[day].date;
+
+        let mut at = inspector.current_location;
+        let mut clock = problem.days[day].start;
+        for (sequence, &index) in route.iter().enumerate() {
+            let job = &problem.jobs[index];
+            let leg = travel_hours(at, job.location);
+            at = job.location;
+            clock += Duration::minutes((leg * 60.0).ceil() as i64);
+            let start_time = clock.format("%H:%M").to_string();
+            clock += Duration::minutes((job.estimated_duration_hours * 60.0).ceil() as i64);
+
+            let mut others = route.clone();
+            others.remove(sequence);
+            let breakdown = problem.breakdown(day, &others, sequence, index);
+            let score = breakdown.base + breakdown.priority_bonus + breakdown.pin_bonus
+                - breakdown.delay_penalty
+                - breakdown.travel_penalty;
+            // Alternatives are weighed against the plan without this job
+            let mut without = plan.clone();
+            without.routes[day] = others;
+            if let Some(entity) = entity_of(index) {
+                commands.entity(entity).insert(AssignmentResult {
+                    assigned_inspector_id: inspector.id.clone(),
+                    date: date.clone(),
+                    sequence: sequence + 1,
+                    start_time,
+                    score,
+                    pinned: problem.pinned(index),
+                    breakdown,
+                    runner_ups: problem.runner_ups(&without, day, index),
+                });
             }
         }
 
-        if let Some(insp_id) = best_inspector_id {
-            // "Assign" the job
-            println!("ECS: Assigned Job {} to Inspector {} (Score: {:.2})", job.id, insp_id, best_score);
-            commands.entity(job_entity).insert(AssignmentResult {
-                assigned_inspector_id: insp_id,
-                score: best_score,
+        let (work_hours, travel_hours) = problem.route_hours(day, route);
+        routes.0.push(DayRoute {
+            inspector_id: inspector.id.clone(),
+            date: date.clone(),
+            job_ids: route.iter().map(|&job| problem.jobs[job].id.clone()).collect(),
+            work_hours,
+            travel_hours,
+        });
+    }
+
+    let unassigned = problem.unassigned(&plan);
+    for &index in &unassigned {
+        if let Some(entity) = entity_of(index) {
+            commands.entity(entity).insert(Unassigned {
+                reason: problem.unassigned_reason(index),
             });
         }
     }
+
+    println!(
+        "ECS: {} placed {} of {} jobs on {} routes",
+        config.strategy.name(),
+        problem.jobs.len() - unassigned.len(),
+        problem.jobs.len(),
+        routes.0.len()
+    );
 }
 
 // --- World Wrapper ---
@@ -88,6 +674,8 @@ pub struct SchedulerWorld {
 impl SchedulerWorld {
     pub fn new() -> Self {
         let mut world = World::new();
+        world.init_resource::<SchedulerConfig>();
+        world.init_resource::<ScheduleRoutes>();
         let mut schedule = Schedule::default();
 
         schedule.add_systems(assignment_scoring_system);
@@ -95,28 +683,14 @@ impl SchedulerWorld {
         Self { world, schedule }
     }
 
-    pub fn seed_simulation(&mut self) {
-        // Spawn Inspectors
-        self.world.spawn(Inspector { 
-            id: "insp-1".into(), name: "Alice".into(), current_location: (49.2827, -123.1207), skill_level: 5 // Downtown
-        });
-        self.world.spawn(Inspector { 
-            id: "insp-2".into(), name: "Bob".into(), current_location: (49.1666, -123.1336), skill_level: 3 // Richmond
-        });
-
-        // Spawn Jobs
-        self.world.spawn(InspectionJob {
-            id: "job-A".into(), location: (49.2606, -123.2460), priority: 2, estimated_duration_hours: 2.0, required_skill_level: 2 // UBC (Closer to Alice)
-        });
-        self.world.spawn(InspectionJob {
-            id: "job-B".into(), location: (49.1304, -123.0697), priority: 5, estimated_duration_hours: 4.0, required_skill_level: 3 // Delta (Closer to Bob)
-        });
+    pub fn configure(&mut self, strategy: Box<dyn SchedulingStrategy>, seed: u64) {
+        self.world.insert_resource(SchedulerConfig { strategy, seed });
     }
 
     pub fn run(&mut self) {
         self.schedule.run(&mut self.world);
     }
-    
+
     // ✅ FIXED: Proper immutable iteration
     pub fn get_assignments(&mut self) -> Vec<(InspectionJob, AssignmentResult)> {
         let mut query = self.world.query::<(&InspectionJob, &AssignmentResult)>();
@@ -125,6 +699,301 @@ impl SchedulerWorld {
         for (job, assignment) in query.iter(&self.world) {
             results.push((job.clone(), assignment.clone()));
         }
+        results.sort_by(|a, b| a.0.id.cmp(&b.0.id));
         results
     }
+
+    pub fn get_unassigned(&mut self) -> Vec<(InspectionJob, Unassigned)> {
+        let mut query = self.world.query::<(&InspectionJob, &Unassigned)>();
+        let mut results: Vec<_> = query
+            .iter(&self.world)
+            .map(|(job, unassigned)| (job.clone(), unassigned.clone()))
+            .collect();
+        results.sort_by(|a, b| a.0.id.cmp(&b.0.id));
+        results
+    }
+
+    pub fn get_routes(&self) -> Vec<DayRoute> {
+        self.world.resource::<ScheduleRoutes>().0.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inspector(id: &str, location: (f64, f64), daily_hours: f32) -> Inspector {
+        Inspector {
+            id: id.to_string(),
+            name: id.to_string(),
+            current_location: location,
+            skill_level: 3,
+            daily_hours,
+        }
+    }
+
+    fn open_day(inspector_id: &str, date: &str) -> AvailabilitySlot {
+        AvailabilitySlot {
+            inspector_id: inspector_id.to_string(),
+            date: date.to_string(),
+            is_blocked: false,
+            start_time: NaiveTime::from_hms_opt(9, 0, 0),
+            end_time: NaiveTime::from_hms_opt(17, 0, 0),
+        }
+    }
+
+    // More work around Metro Vancouver than two inspectors can do in three days
+    fn fixture() -> (Vec<Inspector>, Vec<InspectionJob>, Vec<AvailabilitySlot>) {
+        let inspectors = vec![
+            inspector("insp-a", (49.2827, -123.1207), DEFAULT_DAILY_HOURS),
+            inspector("insp-b", (49.2276, -123.0076), 5.0),
+        ];
+        let jobs = (0..14)
+            .map(|i| InspectionJob {
+                id: format!("job-{i:02}"),
+                location: (49.15 + (i % 5) as f64 * 0.04, -123.25 + (i % 7) as f64 * 0.06),
+                priority: (i % 5) as u8 + 1,
+                estimated_duration_hours: [1.5, 2.0, 3.0][i % 3],
+                required_skill_level: 2,
+            })
+            .collect();
+        let slots = ["2026-11-02", "2026-11-03", "2026-11-04"]
+            .iter()
+            .flat_map(|date| [open_day("insp-a", date), open_day("insp-b", date)])
+            .collect();
+        (inspectors, jobs, slots)
+    }
+
+    fn problem() -> Problem {
+        let (inspectors, jobs, slots) = fixture();
+        Problem::new(inspectors, jobs.into_iter().map(|job| (job, JobOverrides::default())).collect(), &slots)
+    }
+
+    // Spawns everything, overrides as components on their job, and runs the schedule
+    fn schedule(
+        inspectors: Vec<Inspector>,
+        jobs: Vec<(InspectionJob, JobOverrides)>,
+        slots: Vec<AvailabilitySlot>,
+        strategy: &str,
+        seed: u64,
+    ) -> SchedulerWorld {
+        let mut ecs = SchedulerWorld::new();
+        inspectors.into_iter().for_each(|inspector| {
+            ecs.world.spawn(inspector);
+        });
+        for (job, overrides) in jobs {
+            let mut entity = ecs.world.spawn((job, ExcludedInspectors(overrides.excluded)));
+            if let Some(pin) = overrides.pinned {
+                entity.insert(pin);
+            }
+        }
+        slots.into_iter().for_each(|slot| {
+            ecs.world.spawn(slot);
+        });
+        ecs.configure(strategy_by_name(strategy, None).unwrap(), seed);
+        ecs.run();
+        ecs
+    }
+
+    fn run(strategy: &str, seed: u64) -> Vec<DayRoute> {
+        let (inspectors, jobs, slots) = fixture();
+        let jobs = jobs.into_iter().map(|job| (job, JobOverrides::default())).collect();
+        schedule(inspectors, jobs, slots, strategy, seed).get_routes()
+    }
+
+    fn job(id: &str, duration_hours: f32, required_skill_level: u8) -> InspectionJob {
+        InspectionJob {
+            id: id.to_string(),
+            location: (49.25, -123.1),
+            priority: 3,
+            estimated_duration_hours: duration_hours,
+            required_skill_level,
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_routes() {
+        let stops = |routes: Vec<DayRoute>| {
+            routes
+                .into_iter()
+                .map(|route| (route.inspector_id, route.date, route.job_ids))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(stops(run("local-search", 7)), stops(run("local-search", 7)));
+    }
+
+    #[test]
+    fn routes_stay_within_daily_hours() {
+        let routes = run("local-search", DEFAULT_SEED);
+        assert!(!routes.is_empty());
+        for route in routes {
+            let limit = if route.inspector_id == "insp-b" { 5.0 } else { DEFAULT_DAILY_HOURS };
+            assert!(
+                route.work_hours + route.travel_hours <= limit + 1e-3,
+                "{} on {} booked {} + {} hours",
+                route.inspector_id,
+                route.date,
+                route.work_hours,
+                route.travel_hours
+            );
+        }
+    }
+
+    #[test]
+    fn local_search_never_scores_below_greedy() {
+        let problem = problem();
+        let greedy = GreedyStrategy.plan(&problem, &mut StdRng::seed_from_u64(0));
+        for seed in 0..5 {
+            let local = LocalSearchStrategy { iterations: 200 }.plan(&problem, &mut StdRng::seed_from_u64(seed));
+            assert!(problem.feasible(&local));
+            assert!(problem.objective(&local) >= problem.objective(&greedy) - 1e-3);
+        }
+    }
+
+    #[test]
+    fn breakdowns_add_up_to_the_score() {
+        let (inspectors, jobs, slots) = fixture();
+        let jobs = jobs.into_iter().map(|job| (job, JobOverrides::default())).collect();
+        let mut ecs = schedule(inspectors, jobs, slots, "local-search", DEFAULT_SEED);
+        let assignments = ecs.get_assignments();
+        assert!(!assignments.is_empty());
+        for (job, result) in assignments {
+            let b = &result.breakdown;
+            let total = b.base + b.priority_bonus + b.pin_bonus - b.delay_penalty - b.travel_penalty;
+            assert!((total - result.score).abs() < 1e-3, "{}: {} vs {}", job.id, total, result.score);
+            assert!(b.booked_hours <= b.capacity_hours + 1e-3, "{} overbooks its day", job.id);
+        }
+    }
+
+    #[test]
+    fn runner_ups_are_the_best_other_inspectors_in_order() {
+        let homes = [(49.25, -123.1), (49.2, -123.0), (49.3, -122.8), (49.1, -122.6), (48.9, -122.3)];
+        let inspectors: Vec<Inspector> = homes
+            .iter()
+            .enumerate()
+            .map(|(i, &home)| inspector(&format!("insp-{i}"), home, DEFAULT_DAILY_HOURS))
+            .collect();
+        let slots = inspectors.iter().map(|i| open_day(&i.id, "2026-11-02")).collect();
+        let jobs = vec![(job("job-1", 2.0, 1), JobOverrides::default())];
+        let mut ecs = schedule(inspectors, jobs, slots, "greedy", DEFAULT_SEED);
+
+        let (_, result) = ecs.get_assignments().pop().unwrap();
+        assert_eq!(result.assigned_inspector_id, "insp-0"); // lives next door
+        let runner_ups = &result.runner_ups;
+        assert_eq!(runner_ups.len(), RUNNER_UPS);
+        assert!(runner_ups.iter().all(|c| c.inspector_id != result.assigned_inspector_id));
+        assert!(runner_ups.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        assert!(runner_ups[0].score <= result.score + 1e-3);
+        assert_eq!(runner_ups[0].inspector_id, "insp-1");
+    }
+
+    #[test]
+    fn unplaced_jobs_report_why() {
+        let mut junior = inspector("insp-junior", (49.25, -123.1), DEFAULT_DAILY_HOURS);
+        junior.skill_level = 2;
+        let inspectors = vec![inspector("insp-a", (49.25, -123.1), DEFAULT_DAILY_HOURS), junior];
+        let slots = vec![open_day("insp-a", "2026-11-02")]; // the junior inspector has no open day
+        let pin = |inspector_id: &str| JobOverrides {
+            pinned: Some(PinnedTo { inspector_id: inspector_id.to_string(), date: None }),
+            excluded: Vec::new(),
+        };
+        let exclude = |inspector_id: &str| JobOverrides {
+            pinned: None,
+            excluded: vec![inspector_id.to_string()],
+        };
+        let jobs = vec![
+            (job("job-fits", 2.0, 2), JobOverrides::default()),
+            (job("job-expert", 2.0, 5), JobOverrides::default()),
+            (job("job-no-day", 2.0, 2), exclude("insp-a")),
+            (job("job-too-long", 12.0, 2), JobOverrides::default()),
+            (job("job-pinned", 2.0, 2), pin("insp-junior")),
+        ];
+        let mut ecs = schedule(inspectors, jobs, slots, "local-search", DEFAULT_SEED);
+
+        let placed: Vec<String> = ecs.get_assignments().into_iter().map(|(job, _)| job.id).collect();
+        assert_eq!(placed, vec!["job-fits"]);
+        let reasons: Vec<(String, UnassignedReason)> =
+            ecs.get_unassigned().into_iter().map(|(job, unassigned)| (job.id, unassigned.reason)).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                ("job-expert".to_string(), UnassignedReason::Unqualified),
+                ("job-no-day".to_string(), UnassignedReason::Unavailable),
+                ("job-pinned".to_string(), UnassignedReason::PinUnsatisfiable),
+                ("job-too-long".to_string(), UnassignedReason::OverCapacity),
+            ]
+        );
+    }
+
+    // The fixture with job-03 pinned to insp-b on the last day and job-07 to
+    // insp-b on any day; left alone, the scorer gives both to insp-a
+    fn pinned_jobs() -> (Vec<Inspector>, Vec<(InspectionJob, JobOverrides)>, Vec<AvailabilitySlot>) {
+        let (inspectors, jobs, slots) = fixture();
+        let jobs = jobs
+            .into_iter()
+            .map(|job| {
+                let pinned = match job.id.as_str() {
+                    "job-03" => Some(PinnedTo {
+                        inspector_id: "insp-b".to_string(),
+                        date: Some("2026-11-04".to_string()),
+                    }),
+                    "job-07" => Some(PinnedTo { inspector_id: "insp-b".to_string(), date: None }),
+                    _ => None,
+                };
+                (job, JobOverrides { pinned, excluded: Vec::new() })
+            })
+            .collect();
+        (inspectors, jobs, slots)
+    }
+
+    #[test]
+    fn pinned_jobs_land_on_their_pin() {
+        let (inspectors, jobs, slots) = pinned_jobs();
+        let mut ecs = schedule(inspectors, jobs, slots, "local-search", DEFAULT_SEED);
+        let assignments = ecs.get_assignments();
+        let placed = |id: &str| assignments.iter().find(|(job, _)| job.id == id).map(|(_, result)| result).unwrap();
+
+        let job_03 = placed("job-03");
+        assert!(job_03.pinned);
+        assert_eq!((job_03.assigned_inspector_id.as_str(), job_03.date.as_str()), ("insp-b", "2026-11-04"));
+        assert_eq!(placed("job-07").assigned_inspector_id, "insp-b");
+        assert!(assignments.iter().filter(|(job, _)| job.id != "job-03" && job.id != "job-07").all(|(_, r)| !r.pinned));
+    }
+
+    #[test]
+    fn local_search_never_moves_a_pinned_job() {
+        let (inspectors, jobs, slots) = pinned_jobs();
+        let problem = Problem::new(inspectors, jobs, &slots);
+        for seed in 0..10 {
+            let plan = LocalSearchStrategy { iterations: 300 }.plan(&problem, &mut StdRng::seed_from_u64(seed));
+            for job in (0..problem.jobs.len()).filter(|&job| problem.pinned(job)) {
+                let pin = problem.overrides[job].pinned.as_ref().unwrap();
+                let day = plan.routes.iter().position(|route| route.contains(&job));
+                let day = day.unwrap_or_else(|| panic!("seed {seed} dropped {}", problem.jobs[job].id));
+                assert_eq!(problem.inspectors[problem.days[day].inspector].id, pin.inspector_id);
+                assert!(pin.date.as_ref().is_none_or(|date| *date == problem.days[day].date));
+            }
+        }
+    }
+
+    #[test]
+    fn excluded_inspectors_are_never_picked() {
+        let (inspectors, jobs, slots) = fixture();
+        let exclude_a = |job: &InspectionJob| job.priority >= 3;
+        let jobs: Vec<(InspectionJob, JobOverrides)> = jobs
+            .into_iter()
+            .map(|job| {
+                let excluded = if exclude_a(&job) { vec!["insp-a".to_string()] } else { Vec::new() };
+                (job, JobOverrides { pinned: None, excluded })
+            })
+            .collect();
+        let mut ecs = schedule(inspectors, jobs, slots, "local-search", DEFAULT_SEED);
+
+        let assignments = ecs.get_assignments();
+        assert!(assignments.iter().any(|(job, _)| exclude_a(job)));
+        for (job, result) in assignments.iter().filter(|(job, _)| exclude_a(job)) {
+            assert_eq!(result.assigned_inspector_id, "insp-b", "{}", job.id);
+            assert!(result.runner_ups.iter().all(|c| c.inspector_id != "insp-a"), "{}", job.id);
+        }
+    }
 }
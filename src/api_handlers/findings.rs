@@ -0,0 +1,344 @@
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{get, put};
+use axum::{Json, Router};
+use crate::api_handlers::components;
+use crate::api_handlers::session::CurrentUser;
+use crate::db::AppState;
+use crate::models::InspectionFinding;
+use rusqlite::{Connection, OptionalExtension, Row};
+use serde::Deserialize;
+use std::sync::Arc;
+
+const FINDING_COLUMNS: &str = "id, service_request_id, component, category, condition_score, observation, recommendation,
+    estimated_cost, inspector_id, recorded_at, updated_at, component_id";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindingQuery {
+    service_request_id: Option<String>,
+}
+
+pub fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(list_findings).post(create_finding))
+        .route("/:id", put(update_finding).delete(delete_finding))
+        .with_state(state)
+}
+
+fn finding_from_row(row: &Row) -> rusqlite::Result<InspectionFinding> {
+    Ok(InspectionFinding {
+        id: row.get(0)?,
+        service_request_id: row.get(1)?,
+        component: row.get(2)?,
+        category: row.get(3)?,
+        condition_score: row.get(4)?,
+        observation: row.get(5)?,
+        recommendation: row.get(6)?,
+        estimated_cost: row.get(7)?,
+        inspector_id: row.get(8)?,
+        recorded_at: row.get(9)?,
+        updated_at: row.get(10)?,
+        component_id: row.get(11)?,
+    })
+}
+
+fn validate(finding: &InspectionFinding) -> Result<(), StatusCode> {
+    if finding.component.trim().is_empty() || finding.condition_score > 100 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    Ok(())
+}
+
+// A finding tied to an inventory component must be on that component's strata;
+// a blank component name or category is filled in from the inventory.
+fn resolve_component(
+    conn: &Connection,
+    service_request_id: &str,
+    mut finding: InspectionFinding,
+) -> Result<InspectionFinding, StatusCode> {
+    let Some(component_id) = finding.component_id.as_deref() else {
+        return Ok(finding);
+    };
+    let component = components::load_component(conn, component_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    // A request with no strata has no inventory to take components from
+    let strata_id: Option<String> = conn
+        .query_row("SELECT strata_id FROM service_requests WHERE id = ?1", [service_request_id], |row| row.get(0))
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .flatten();
+    if strata_id.is_none_or(|strata_id| component.strata_id != strata_id) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if finding.component.trim().is_empty() {
+        finding.component = component.component_type;
+    }
+    if finding.category.is_none() {
+        finding.category = Some(component.category);
+    }
+    Ok(finding)
+}
+
+fn load_finding(conn: &Connection, id: &str) -> Result<InspectionFinding, StatusCode> {
+    conn.query_row(
+        &format!("SELECT {FINDING_COLUMNS} FROM inspection_findings WHERE id = ?1"),
+        [id],
+        finding_from_row,
+    )
+    .optional()
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .ok_or(StatusCode::NOT_FOUND)
+}
+
+// Admins, or the inspector booked to inspect the request
+fn ensure_can_record(conn: &Connection, user: &CurrentUser, service_request_id: &str) -> Result<(), StatusCode> {
+    let exists: Option<i64> = conn
+        .query_row("SELECT 1 FROM service_requests WHERE id = ?1", [service_request_id], |row| row.get(0))
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if exists.is_none() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    if user.is_admin() {
+        return Ok(());
+    }
+    if !user.is_inspector() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let assigned: Option<i64> = conn
+        .query_row(
+            "SELECT 1 FROM appointments
+             WHERE service_request_id = ?1 AND inspector_id = ?2 AND appointment_type = 'inspection'
+               AND status IN ('confirmed', 'completed')",
+            (service_request_id, &user.0.id),
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    assigned.map(|_| ()).ok_or(StatusCode::FORBIDDEN)
+}
+
+// Clients see the findings for their own strata only
+async fn list_findings(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Query(params): Query<FindingQuery>,
+) -> Result<Json<Vec<InspectionFinding>>, StatusCode> {
+    let conn = state.conn.lock().unwrap();
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {FINDING_COLUMNS} FROM inspection_findings
+             WHERE (?1 IS NULL OR service_request_id = ?1)
+               AND (?2 IS NULL OR service_request_id IN (SELECT id FROM service_requests WHERE strata_id = ?2))
+             ORDER BY service_request_id, condition_score, component"
+        ))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let rows = stmt
+        .query_map((&params.service_request_id, user.strata_scope()), finding_from_row)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn create_finding(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Json(payload): Json<InspectionFinding>,
+) -> Result<(StatusCode, Json<InspectionFinding>), StatusCode> {
+    let conn = state.conn.lock().unwrap();
+    let service_request_id = payload.service_request_id.clone();
+    ensure_can_record(&conn, &user, &service_request_id)?;
+    let payload = resolve_component(&conn, &service_request_id, payload)?;
+    validate(&payload)?;
+
+    let now = state.clock.now().to_rfc3339();
+    let finding = InspectionFinding {
+        id: uuid::Uuid::new_v4().to_string(),
+        inspector_id: Some(user.0.id.clone()),
+        recorded_at: now.clone(),
+        updated_at: now,
+        ..payload
+    };
+    conn.execute(
+        &format!(
+            "INSERT INTO inspection_findings ({FINDING_COLUMNS})
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)"
+        ),
+        rusqlite::params![
+            &finding.id,
+            &finding.service_request_id,
+            &finding.component,
+            &finding.category,
+            finding.condition_score,
+            &finding.observation,
+            &finding.recommendation,
+            finding.estimated_cost,
+            &finding.inspector_id,
+            &finding.recorded_at,
+            &finding.updated_at,
+            &finding.component_id,
+        ],
+    )
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok((StatusCode::CREATED, Json(finding)))
+}
+
+// The service request and recording inspector stay as they were
+async fn update_finding(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+    Json(payload): Json<InspectionFinding>,
+) -> Result<Json<InspectionFinding>, StatusCode> {
+    let conn = state.conn.lock().unwrap();
+    let existing = load_finding(&conn, &id)?;
+    ensure_can_record(&conn, &user, &existing.service_request_id)?;
+    let payload = resolve_component(&conn, &existing.service_request_id, payload)?;
+    validate(&payload)?;
+
+    let finding = InspectionFinding {
+        id: existing.id,
+        service_request_id: existing.service_request_id,
+        inspector_id: existing.inspector_id,
+        recorded_at: existing.recorded_at,
+        updated_at: state.clock.now().to_rfc3339(),
+        ..payload
+    };
+    conn.execute(
+        "UPDATE inspection_findings
+         SET component = ?1, category = ?2, condition_score = ?3, observation = ?4, recommendation = ?5,
+             estimated_cost = ?6, updated_at = ?7, component_id = ?8
+         WHERE id = ?9",
+        rusqlite::params![
+            &finding.component,
+            &finding.category,
+            finding.condition_score,
+            &finding.observation,
+            &finding.recommendation,
+            finding.estimated_cost,
+            &finding.updated_at,
+            &finding.component_id,
+            &finding.id,
+        ],
+    )
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(finding))
+}
+
+async fn delete_finding(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    let conn = state.conn.lock().unwrap();
+    let existing = load_finding(&conn, &id)?;
+    ensure_can_record(&conn, &user, &existing.service_request_id)?;
+    conn.execute("DELETE FROM inspection_findings WHERE id = ?1", [&id])
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{PropertyType, User};
+
+    fn seeded() -> AppState {
+        let state = AppState::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        {
+            let conn = state.conn.lock().unwrap();
+            conn.execute_batch(
+                "INSERT INTO stratas (id, strata_plan, complex_name, created_at)
+                 VALUES ('strata-2', 'VR 99', 'Harbour Mews', '2026-01-01T00:00:00Z');
+                 INSERT INTO users (id, name, email, role, created_at)
+                 VALUES ('user-inspector-2', 'Other Inspector', 'other@srp.com', 'inspector', '2026-01-01T00:00:00Z');",
+            )
+            .unwrap();
+        }
+        state
+    }
+
+    fn user(id: &str, role: &str) -> CurrentUser {
+        CurrentUser(User {
+            id: id.to_string(),
+            name: id.to_string(),
+            email: format!("{id}@srp.com"),
+            role: role.to_string(),
+            strata_id: (role == "client").then(|| "strata-1".to_string()),
+            position: None,
+            phone: None,
+            cell_phone: None,
+            must_change_password: false,
+            created_at: String::new(),
+        })
+    }
+
+    fn book_inspection(conn: &Connection, inspector_id: &str, status: &str) {
+        conn.execute(
+            "INSERT INTO appointments (id, service_request_id, strata_plan, appointment_type, requested_date_1,
+                                       requested_time_1, confirmed_date, confirmed_time, inspector_id, status,
+                                       created_at, updated_at)
+             VALUES (?1, 'req-1', 'VIS 2345', 'inspection', '2026-11-03', '10:00', '2026-11-03', '10:00',
+                     ?2, ?3, '', '')",
+            (format!("appt-{inspector_id}-{status}"), inspector_id, status),
+        )
+        .unwrap();
+    }
+
+    fn finding(component_id: &str) -> InspectionFinding {
+        InspectionFinding {
+            id: String::new(),
+            service_request_id: "req-1".to_string(),
+            component_id: Some(component_id.to_string()),
+            component: String::new(),
+            category: None,
+            condition_score: 60,
+            observation: "Granule loss".to_string(),
+            recommendation: "Monitor".to_string(),
+            estimated_cost: None,
+            inspector_id: None,
+            recorded_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn only_the_booked_inspector_may_record() {
+        let state = seeded();
+        let conn = state.conn.lock().unwrap();
+        let inspector = user("user-inspector-1", "inspector");
+        assert_eq!(ensure_can_record(&conn, &user("user-admin-1", "admin"), "req-1"), Ok(()));
+        assert_eq!(ensure_can_record(&conn, &inspector, "req-1"), Err(StatusCode::FORBIDDEN));
+        assert_eq!(ensure_can_record(&conn, &inspector, "req-404"), Err(StatusCode::NOT_FOUND));
+
+        // An offer the client has not accepted does not put the inspector on site
+        book_inspection(&conn, "user-inspector-1", "offered");
+        assert_eq!(ensure_can_record(&conn, &inspector, "req-1"), Err(StatusCode::FORBIDDEN));
+        book_inspection(&conn, "user-inspector-1", "confirmed");
+        assert_eq!(ensure_can_record(&conn, &inspector, "req-1"), Ok(()));
+        let other_inspector = user("user-inspector-2", "inspector");
+        assert_eq!(ensure_can_record(&conn, &other_inspector, "req-1"), Err(StatusCode::FORBIDDEN));
+        assert_eq!(ensure_can_record(&conn, &user("user-client-1", "client"), "req-1"), Err(StatusCode::FORBIDDEN));
+    }
+
+    #[test]
+    fn components_come_from_the_request_strata() {
+        let state = seeded();
+        let conn = state.conn.lock().unwrap();
+        // strata-1 was stocked from the catalogue when the database was seeded
+        let own_id: String = conn
+            .query_row("SELECT id FROM component_inventory WHERE strata_id = 'strata-1' LIMIT 1", [], |row| row.get(0))
+            .unwrap();
+        let own = components::load_component(&conn, &own_id).unwrap().unwrap();
+        let other = components::seed_inventory(&conn, "strata-2", PropertyType::Townhouse, "").unwrap();
+
+        let resolved = resolve_component(&conn, "req-1", finding(&own.id)).unwrap();
+        assert_eq!(resolved.component, own.component_type);
+        assert_eq!(resolved.category, Some(own.category));
+        assert_eq!(resolve_component(&conn, "req-1", finding(&other[0].id)).err(), Some(StatusCode::BAD_REQUEST));
+        assert_eq!(resolve_component(&conn, "req-1", finding("missing")).err(), Some(StatusCode::BAD_REQUEST));
+    }
+}
@@ -0,0 +1,207 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+// Roads are rarely straight: driving distance over the great-circle distance,
+// and average speed across city and highway driving.
+const ROAD_FACTOR: f64 = 1.3;
+const DRIVING_SPEED_KMH: f64 = 40.0;
+
+// Approximate centroids (lat, long) of forward sortation areas, the first three
+// characters of a postal code, for the regions we inspect in. Offline on
+// purpose: scheduling must not depend on a geocoding service.
+const FSA_CENTROIDS: &[(&str, f64, f64)] = &[
+    // Vancouver
+    ("V5K", 49.2805, -123.044),
+    ("V5L", 49.278, -123.069),
+    ("V5M", 49.262, -123.043),
+    ("V5N", 49.262, -123.07),
+    ("V5P", 49.22, -123.062),
+    ("V5R", 49.239, -123.044),
+    ("V5S", 49.218, -123.04),
+    ("V5T", 49.262, -123.095),
+    ("V5V", 49.244, -123.098),
+    ("V5W", 49.232, -123.094),
+    ("V5X", 49.218, -123.103),
+    ("V5Y", 49.246, -123.111),
+    ("V5Z", 49.254, -123.121),
+    ("V6A", 49.279, -123.092),
+    ("V6B", 49.279, -123.115),
+    ("V6C", 49.286, -123.115),
+    ("V6E", 49.286, -123.131),
+    ("V6G", 49.291, -123.139),
+    ("V6H", 49.264, -123.137),
+    ("V6J", 49.266, -123.153),
+    ("V6K", 49.266, -123.164),
+    ("V6L", 49.247, -123.162),
+    ("V6M", 49.231, -123.146),
+    ("V6N", 49.229, -123.183),
+    ("V6P", 49.211, -123.134),
+    ("V6R", 49.263, -123.195),
+    ("V6S", 49.25, -123.198),
+    ("V6T", 49.262, -123.246),
+    ("V6Z", 49.28, -123.124),
+    // Burnaby and New Westminster
+    ("V5A", 49.26, -122.93),
+    ("V5B", 49.278, -122.975),
+    ("V5C", 49.28, -123.0),
+    ("V5E", 49.215, -122.96),
+    ("V5G", 49.24, -123.005),
+    ("V5H", 49.226, -123.002),
+    ("V5J", 49.21, -122.99),
+    ("V3N", 49.225, -122.94),
+    ("V3L", 49.205, -122.911),
+    ("V3M", 49.2, -122.94),
+    // Richmond
+    ("V6V", 49.19, -123.09),
+    ("V6W", 49.17, -123.07),
+    ("V6X", 49.176, -123.13),
+    ("V6Y", 49.1666, -123.1336),
+    ("V7A", 49.137, -123.11),
+    ("V7C", 49.16, -123.17),
+    ("V7E", 49.13, -123.17),
+    // North Shore
+    ("V7G", 49.315, -122.95),
+    ("V7H", 49.32, -122.99),
+    ("V7J", 49.33, -123.03),
+    ("V7K", 49.345, -123.035),
+    ("V7L", 49.32, -123.0724),
+    ("V7M", 49.314, -123.085),
+    ("V7N", 49.335, -123.08),
+    ("V7P", 49.32, -123.12),
+    ("V7R", 49.345, -123.11),
+    ("V7T", 49.327, -123.14),
+    ("V7V", 49.33, -123.16),
+    ("V7W", 49.355, -123.25),
+    // Delta, Surrey and Langley
+    ("V4C", 49.13, -122.9),
+    ("V4E", 49.15, -122.91),
+    ("V4K", 49.09, -123.08),
+    ("V4L", 49.015, -123.08),
+    ("V4M", 49.01, -123.05),
+    ("V3R", 49.1913, -122.849),
+    ("V3S", 49.105, -122.8),
+    ("V3T", 49.185, -122.84),
+    ("V3V", 49.2, -122.88),
+    ("V3W", 49.13, -122.85),
+    ("V3X", 49.11, -122.88),
+    ("V4A", 49.04, -122.8),
+    ("V4N", 49.16, -122.77),
+    ("V3A", 49.104, -122.66),
+    ("V2Y", 49.13, -122.63),
+    // Tri-Cities, Maple Ridge and the Fraser Valley
+    ("V3B", 49.284, -122.79),
+    ("V3C", 49.266, -122.77),
+    ("V3E", 49.28, -122.82),
+    ("V3H", 49.29, -122.86),
+    ("V3J", 49.255, -122.88),
+    ("V3K", 49.235, -122.86),
+    ("V2X", 49.219, -122.6),
+    ("V2S", 49.05, -122.3),
+    // Vancouver Island and the Interior
+    ("V8V", 48.415, -123.36),
+    ("V8W", 48.424, -123.365),
+    ("V9A", 48.43, -123.39),
+    ("V9R", 49.165, -123.94),
+    ("V1Y", 49.888, -119.496),
+    ("V2C", 50.674, -120.327),
+];
+
+// Fallback when only the city is known
+const CITY_CENTROIDS: &[(&str, f64, f64)] = &[
+    ("vancouver", 49.2827, -123.1207),
+    ("burnaby", 49.2488, -122.9805),
+    ("new westminster", 49.2057, -122.911),
+    ("richmond", 49.1666, -123.1336),
+    ("north vancouver", 49.32, -123.0724),
+    ("west vancouver", 49.3286, -123.1602),
+    ("delta", 49.0847, -123.0586),
+    ("surrey", 49.1913, -122.849),
+    ("white rock", 49.0253, -122.8026),
+    ("langley", 49.1044, -122.6604),
+    ("coquitlam", 49.2838, -122.7932),
+    ("port coquitlam", 49.2625, -122.7811),
+    ("port moody", 49.2849, -122.8678),
+    ("maple ridge", 49.2193, -122.5984),
+    ("abbotsford", 49.0504, -122.3045),
+    ("victoria", 48.4284, -123.3656),
+    ("nanaimo", 49.1659, -123.9401),
+    ("kelowna", 49.888, -119.496),
+    ("kamloops", 50.6745, -120.3273),
+];
+
+// Canadian postal codes, with or without the space: "V6B 1A1", "v6b1a1"
+static POSTAL_CODE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b([A-Z]\d[A-Z])\s?\d[A-Z]\d\b").unwrap());
+
+// Great-circle distance between two (lat, long) points
+pub fn haversine_km(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (lat1, lat2) = (a.0.to_radians(), b.0.to_radians());
+    let d_lat = lat2 - lat1;
+    let d_long = (b.1 - a.1).to_radians();
+    let h = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_long / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * h.sqrt().asin()
+}
+
+pub fn driving_hours(a: (f64, f64), b: (f64, f64)) -> f64 {
+    haversine_km(a, b) * ROAD_FACTOR / DRIVING_SPEED_KMH
+}
+
+fn fsa_centroid(postal_code: &str) -> Option<(f64, f64)> {
+    let fsa = POSTAL_CODE.captures(postal_code)?.get(1)?.as_str().to_uppercase();
+    FSA_CENTROIDS
+        .iter()
+        .find(|(code, _, _)| *code == fsa)
+        .map(|&(_, lat, long)| (lat, long))
+}
+
+fn city_centroid(city: &str) -> Option<(f64, f64)> {
+    let city = city.trim().to_lowercase();
+    CITY_CENTROIDS
+        .iter()
+        .find(|(name, _, _)| *name == city)
+        .map(|&(_, lat, long)| (lat, long))
+}
+
+// Best known coordinates for a site: the postal code, then a postal code
+// written into the street address, then the city centre.
+pub fn geocode(address: Option<&str>, city: Option<&str>, postal_code: Option<&str>) -> Option<(f64, f64)> {
+    postal_code
+        .and_then(fsa_centroid)
+        .or_else(|| address.and_then(fsa_centroid))
+        .or_else(|| city.and_then(city_centroid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vancouver_to_victoria_is_about_95_km() {
+        let km = haversine_km((49.2827, -123.1207), (48.4284, -123.3656));
+        assert!((km - 95.0).abs() < 3.0, "{} km", km);
+        assert_eq!(haversine_km((49.2827, -123.1207), (49.2827, -123.1207)), 0.0);
+    }
+
+    #[test]
+    fn postal_codes_win_over_the_city() {
+        assert_eq!(geocode(None, Some("Vancouver"), Some("V8W 1A1")), Some((48.424, -123.365)));
+        assert_eq!(geocode(Some("900 Douglas St, V8W 2B4"), Some("Vancouver"), None), Some((48.424, -123.365)));
+        assert_eq!(geocode(None, Some("  Vancouver "), None), Some((49.2827, -123.1207)));
+    }
+
+    #[test]
+    fn postal_codes_are_matched_in_any_case_and_spacing() {
+        for code in ["V6B 1A1", "v6b 1a1", "V6B1A1", "v6b1a1"] {
+            assert_eq!(geocode(None, None, Some(code)), Some((49.279, -123.115)), "{}", code);
+        }
+    }
+
+    #[test]
+    fn unknown_places_are_not_guessed() {
+        assert_eq!(geocode(None, None, None), None);
+        assert_eq!(geocode(Some("1 Main St"), Some("Atlantis"), Some("A1A 1A1")), None);
+        assert_eq!(geocode(None, None, Some("not a code")), None);
+    }
+}
@@ -1,53 +1,728 @@
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
 use axum::Json;
 use axum::routing::{get, post};
 use axum::Router;
-use chrono::{Datelike, Duration, Weekday};
-use crate::models::{LogisticsSlot, Appointment};
+use chrono::{Duration, NaiveDate};
+use crate::api_handlers::slots::{self, Place, SlotRules};
+use crate::api_handlers::draft_meetings;
+use crate::api_handlers::milestones;
+use crate::api_handlers::notifications;
+use crate::api_handlers::clock::{self, Clock};
+use crate::api_handlers::session::CurrentUser;
+use crate::db::AppState;
+use crate::models::{LogisticsSlot, Appointment, AppointmentEvent, AppointmentStatus};
+use rusqlite::{Connection, OptionalExtension, Row};
+use serde::Deserialize;
+use std::sync::Arc;
 
-pub fn router() -> Router {
+pub const APPOINTMENT_COLUMNS: &str = "id, service_request_id, strata_plan, appointment_type, requested_date_1, requested_time_1,
+    requested_date_2, requested_time_2, confirmed_date, confirmed_time, inspector_id, status, meeting_type, notes,
+    cancellation_reason, created_at, updated_at, starts_at";
+
+const SLOT_HORIZON_DAYS: i64 = 28;
+const MAX_SLOT_RANGE_DAYS: i64 = 92;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotQuery {
+    from: Option<String>, // YYYY-MM-DD, inclusive
+    to: Option<String>,
+    service_request_id: Option<String>,
+    duration_hours: Option<f64>,
+    buffer_minutes: Option<i64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppointmentListQuery {
+    service_request_id: Option<String>,
+    inspector_id: Option<String>,
+}
+
+// Admin counter-offer, or a reschedule of a confirmed appointment
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotChange {
+    date: String,
+    time: String,
+    inspector_id: Option<String>, // required for offers; keeps the current inspector otherwise
+    appointment_type: Option<String>,
+    notes: Option<String>,
+    reason: Option<String>,
+}
+
+// Admin approves the client's first or second choice
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmChoice {
+    choice: u8,
+    inspector_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct TransitionReason {
+    reason: Option<String>,
+}
+
+pub fn router(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/available-slots", get(get_available_slots))
         .route("/book-inspection", post(book_inspection))
+        .route("/appointments", get(list_appointments))
+        .route("/appointments/:id", get(get_appointment))
+        .route("/appointments/:id/history", get(get_appointment_history))
+        .route("/appointments/:id/offer", post(offer_appointment))
+        .route("/appointments/:id/confirm", post(confirm_appointment))
+        .route("/appointments/:id/accept", post(accept_offer))
+        .route("/appointments/:id/adjust", post(adjust_appointment))
+        .route("/appointments/:id/reject", post(reject_appointment))
+        .route("/appointments/:id/cancel", post(cancel_appointment))
+        .with_state(state)
 }
 
-async fn get_available_slots() -> Json<Vec<LogisticsSlot>> {
-    let mut slots = Vec::new();
-    let today = chrono::Local::now().naive_local().date();
-    
-    // Find the next 4 Fridays
-    let mut current = today;
-    let mut fridays_found = 0;
-    
-    while fridays_found < 4 {
-        current = current + Duration::days(1);
-        if current.weekday() == Weekday::Fri {
-            // Add 10:00 AM slot
-            slots.push(LogisticsSlot {
-                date: current.format("%Y-%m-%d").to_string(),
-                time: "10:00 AM".to_string(),
-                id: format!("{}-10am", current.format("%Y-%m-%d")),
-            });
-            // Add 2:00 PM slot
-            slots.push(LogisticsSlot {
-                date: current.format("%Y-%m-%d").to_string(),
-                time: "2:00 PM".to_string(),
-                id: format!("{}-2pm", current.format("%Y-%m-%d")),
-            });
-            fridays_found += 1;
+// Open slots between `from` and `to` (default: the next four weeks). With a
+// serviceRequestId the visit length and travel come from that request's site;
+// durationHours and bufferMinutes override the defaults.
+async fn get_available_slots(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SlotQuery>,
+) -> Result<Json<Vec<LogisticsSlot>>, StatusCode> {
+    let parse = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").map_err(|_| StatusCode::BAD_REQUEST))
+            .transpose()
+    };
+    let conn = state.conn.lock().unwrap();
+    // "Tomorrow" is the site's tomorrow, not the server's
+    let tz = match &params.service_request_id {
+        Some(request_id) => clock::request_time_zone(&conn, request_id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+        None => clock::DEFAULT_TIME_ZONE,
+    };
+    let today = state.clock.today_in(tz);
+    let from = parse(&params.from)?.unwrap_or(today + Duration::days(1));
+    let to = parse(&params.to)?.unwrap_or(from + Duration::days(SLOT_HORIZON_DAYS - 1));
+    if to < from || (to - from).num_days() > MAX_SLOT_RANGE_DAYS {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let mut rules = SlotRules::default();
+    let mut place = Place::default();
+    if let Some(request_id) = &params.service_request_id {
+        let (site, hours) = slots::request_details(&conn, request_id)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::NOT_FOUND)?;
+        place = site;
+        if let Some(hours) = hours {
+            rules.duration_minutes = (hours * 60.0) as i64;
         }
     }
-    
-    Json(slots)
+    if let Some(hours) = params.duration_hours {
+        rules.duration_minutes = (hours * 60.0) as i64;
+    }
+    if let Some(buffer) = params.buffer_minutes {
+        rules.buffer_minutes = buffer;
+    }
+    if rules.duration_minutes <= 0 || rules.buffer_minutes < 0 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    slots::generate(&conn, from, to, &rules, &place)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
 }
 
+// Stores the request as pending under a server-issued id. Either requested slot
+// overlapping a confirmed appointment for the same inspector is rejected with 409.
+// Clients may only book for their own strata.
 async fn book_inspection(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
     Json(payload): Json<Appointment>,
-) -> Json<Appointment> {
-    // In a real app, we would save payload to the DB here.
-    // For now, we just echo it back as "confirmed" if it was strictly valid.
-    let mut confirmed = payload;
-    confirmed.status = "Pending Review".to_string();
-    confirmed.updated_at = chrono::Local::now().to_rfc3339();
-    
-    Json(confirmed)
+) -> Result<(StatusCode, Json<Appointment>), StatusCode> {
+    let now = state.clock.now().to_rfc3339();
+    let mut appointment = payload;
+    appointment.id = uuid::Uuid::new_v4().to_string();
+    appointment.status = AppointmentStatus::Pending;
+    appointment.confirmed_date = None;
+    appointment.confirmed_time = None;
+    appointment.cancellation_reason = None;
+    appointment.created_at = now.clone();
+    appointment.updated_at = now;
+
+    let conn = state.conn.lock().unwrap();
+    require_party(&conn, &user, &appointment)?;
+    localize(&conn, &mut appointment).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if let Some(inspector_id) = &appointment.inspector_id {
+        let requested = [
+            Some((&appointment.requested_date_1, &appointment.requested_time_1)),
+            appointment.requested_date_2.as_ref().zip(appointment.requested_time_2.as_ref()),
+        ];
+        for (date, time) in requested.into_iter().flatten() {
+            ensure_slot_free(&conn, &appointment, inspector_id, date, time)?;
+        }
+    }
+
+    insert_appointment(&conn, &mut appointment).map_err(|err| {
+        if is_unique_violation(&err) {
+            StatusCode::CONFLICT
+        } else {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    })?;
+    record_event(&conn, &appointment, "requested", None, &user, None)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok((StatusCode::CREATED, Json(appointment)))
+}
+
+// Filter by serviceRequestId and/or inspectorId; no filter lists everything the
+// caller may see. Clients see their own strata, inspectors their own bookings.
+async fn list_appointments(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Query(params): Query<AppointmentListQuery>,
+) -> Result<Json<Vec<Appointment>>, StatusCode> {
+    let inspector_id = if user.is_inspector() {
+        if params.inspector_id.as_ref().is_some_and(|id| *id != user.0.id) {
+            return Err(StatusCode::FORBIDDEN);
+        }
+        Some(user.0.id.clone())
+    } else {
+        params.inspector_id.clone()
+    };
+    let conn = state.conn.lock().unwrap();
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {APPOINTMENT_COLUMNS} FROM appointments
+             WHERE (?1 IS NULL OR service_request_id = ?1)
+               AND (?2 IS NULL OR inspector_id = ?2)
+               AND (?3 IS NULL OR service_request_id IN (SELECT id FROM service_requests WHERE strata_id = ?3))
+             ORDER BY COALESCE(confirmed_date, requested_date_1), COALESCE(confirmed_time, requested_time_1)"
+        ))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let appointments = stmt
+        .query_map((&params.service_request_id, inspector_id, user.strata_scope()), appointment_from_row)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(Json(appointments))
+}
+
+async fn get_appointment(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+) -> Result<Json<Appointment>, StatusCode> {
+    let conn = state.conn.lock().unwrap();
+    let appointment = load_appointment(&conn, &id)?;
+    require_viewer(&conn, &user, &appointment)?;
+    Ok(Json(appointment))
+}
+
+async fn get_appointment_history(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<AppointmentEvent>>, StatusCode> {
+    let conn = state.conn.lock().unwrap();
+    let appointment = load_appointment(&conn, &id)?;
+    require_viewer(&conn, &user, &appointment)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, appointment_id, action, from_status, to_status, actor_id, reason, date, time, inspector_id, created_at
+             FROM appointment_events WHERE appointment_id = ?1
+             ORDER BY created_at, rowid",
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let events = stmt
+        .query_map([&id], |row| {
+            let from_status: Option<String> = row.get(3)?;
+            let to_status: String = row.get(4)?;
+            Ok(AppointmentEvent {
+                id: row.get(0)?,
+                appointment_id: row.get(1)?,
+                action: row.get(2)?,
+                from_status: from_status.as_deref().and_then(AppointmentStatus::parse),
+                to_status: AppointmentStatus::parse(&to_status).unwrap_or_default(),
+                actor_id: row.get(5)?,
+                reason: row.get(6)?,
+                date: row.get(7)?,
+                time: row.get(8)?,
+                inspector_id: row.get(9)?,
+                created_at: row.get(10)?,
+            })
+        })
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(Json(events))
+}
+
+// ADMIN: propose a different slot; the client then accepts or cancels
+async fn offer_appointment(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+    Json(payload): Json<SlotChange>,
+) -> Result<Json<Appointment>, StatusCode> {
+    require_admin(&user)?;
+    let mut conn = state.conn.lock().unwrap();
+    let mut appointment = load_appointment(&conn, &id)?;
+    require_status(&appointment, &[AppointmentStatus::Pending, AppointmentStatus::Offered])?;
+
+    let inspector_id = payload.inspector_id.ok_or(StatusCode::BAD_REQUEST)?;
+    ensure_slot_free(&conn, &appointment, &inspector_id, &payload.date, &payload.time)?;
+    draft_meetings::ensure_in_window(&conn, state.clock.as_ref(), &appointment, &payload.date)?;
+
+    appointment.updated_at = state.clock.now().to_rfc3339();
+    appointment.status = AppointmentStatus::Offered;
+    appointment.confirmed_date = Some(payload.date);
+    appointment.confirmed_time = Some(payload.time);
+    appointment.inspector_id = Some(inspector_id);
+    if let Some(appointment_type) = payload.appointment_type {
+        appointment.appointment_type = appointment_type;
+    }
+    if payload.notes.is_some() {
+        appointment.notes = payload.notes;
+    }
+    apply_transition(&mut conn, state.clock.as_ref(), &mut appointment, "offered", &user, payload.reason, |tx, appointment| {
+        notify_client(tx, appointment, "New appointment time offered", "Please review and accept the offered appointment time.")
+    })?;
+
+    Ok(Json(appointment))
+}
+
+// ADMIN: approve the client's first or second choice
+async fn confirm_appointment(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+    Json(payload): Json<ConfirmChoice>,
+) -> Result<Json<Appointment>, StatusCode> {
+    require_admin(&user)?;
+    let mut conn = state.conn.lock().unwrap();
+    let mut appointment = load_appointment(&conn, &id)?;
+    require_status(&appointment, &[AppointmentStatus::Pending])?;
+
+    let (date, time) = match payload.choice {
+        1 => (Some(appointment.requested_date_1.clone()), Some(appointment.requested_time_1.clone())),
+        2 => (appointment.requested_date_2.clone(), appointment.requested_time_2.clone()),
+        _ => (None, None),
+    };
+    let (date, time) = date.zip(time).ok_or(StatusCode::BAD_REQUEST)?;
+    let inspector_id = payload
+        .inspector_id
+        .or(appointment.inspector_id.clone())
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    ensure_slot_free(&conn, &appointment, &inspector_id, &date, &time)?;
+    draft_meetings::ensure_in_window(&conn, state.clock.as_ref(), &appointment, &date)?;
+
+    appointment.updated_at = state.clock.now().to_rfc3339();
+    appointment.status = AppointmentStatus::Confirmed;
+    appointment.confirmed_date = Some(date);
+    appointment.confirmed_time = Some(time);
+    appointment.inspector_id = Some(inspector_id);
+    apply_transition(&mut conn, state.clock.as_ref(), &mut appointment, "confirmed", &user, None, notify_confirmed)?;
+
+    Ok(Json(appointment))
+}
+
+// CLIENT: accept the admin's counter-offer
+async fn accept_offer(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+) -> Result<Json<Appointment>, StatusCode> {
+    let mut conn = state.conn.lock().unwrap();
+    let mut appointment = load_appointment(&conn, &id)?;
+    require_party(&conn, &user, &appointment)?;
+    require_status(&appointment, &[AppointmentStatus::Offered])?;
+
+    // The slot may have been given away since it was offered
+    let (Some(inspector_id), Some(date), Some(time)) =
+        (&appointment.inspector_id, &appointment.confirmed_date, &appointment.confirmed_time)
+    else {
+        return Err(StatusCode::CONFLICT);
+    };
+    ensure_slot_free(&conn, &appointment, inspector_id, date, time)?;
+
+    appointment.updated_at = state.clock.now().to_rfc3339();
+    appointment.status = AppointmentStatus::Confirmed;
+    apply_transition(&mut conn, state.clock.as_ref(), &mut appointment, "accepted", &user, None, notify_confirmed)?;
+
+    Ok(Json(appointment))
+}
+
+// ADMIN: reschedule a confirmed appointment, optionally reassigning the inspector
+async fn adjust_appointment(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+    Json(payload): Json<SlotChange>,
+) -> Result<Json<Appointment>, StatusCode> {
+    require_admin(&user)?;
+    let mut conn = state.conn.lock().unwrap();
+    let mut appointment = load_appointment(&conn, &id)?;
+    require_status(&appointment, &[AppointmentStatus::Confirmed])?;
+
+    let inspector_id = payload
+        .inspector_id
+        .or(appointment.inspector_id.clone())
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    ensure_slot_free(&conn, &appointment, &inspector_id, &payload.date, &payload.time)?;
+    draft_meetings::ensure_in_window(&conn, state.clock.as_ref(), &appointment, &payload.date)?;
+
+    appointment.updated_at = state.clock.now().to_rfc3339();
+    appointment.confirmed_date = Some(payload.date);
+    appointment.confirmed_time = Some(payload.time);
+    appointment.inspector_id = Some(inspector_id);
+    if payload.notes.is_some() {
+        appointment.notes = payload.notes;
+    }
+    apply_transition(&mut conn, state.clock.as_ref(), &mut appointment, "adjusted", &user, payload.reason, notify_confirmed)?;
+
+    Ok(Json(appointment))
+}
+
+// ADMIN: turn down a client's request
+async fn reject_appointment(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+    Json(payload): Json<TransitionReason>,
+) -> Result<Json<Appointment>, StatusCode> {
+    require_admin(&user)?;
+    let mut conn = state.conn.lock().unwrap();
+    let mut appointment = load_appointment(&conn, &id)?;
+    require_status(&appointment, &[AppointmentStatus::Pending, AppointmentStatus::Offered])?;
+
+    appointment.updated_at = state.clock.now().to_rfc3339();
+    appointment.status = AppointmentStatus::Rejected;
+    appointment.cancellation_reason = payload.reason.clone();
+    apply_transition(&mut conn, state.clock.as_ref(), &mut appointment, "rejected", &user, payload.reason, |tx, appointment| {
+        notify_client(tx, appointment, "Appointment request declined", "Please choose new dates for your appointment.")
+    })?;
+
+    Ok(Json(appointment))
+}
+
+// ADMIN or CLIENT: cancel; the slot becomes available again
+async fn cancel_appointment(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+    Json(payload): Json<TransitionReason>,
+) -> Result<Json<Appointment>, StatusCode> {
+    let mut conn = state.conn.lock().unwrap();
+    let mut appointment = load_appointment(&conn, &id)?;
+    require_party(&conn, &user, &appointment)?;
+    require_status(
+        &appointment,
+        &[AppointmentStatus::Pending, AppointmentStatus::Offered, AppointmentStatus::Confirmed],
+    )?;
+
+    appointment.updated_at = state.clock.now().to_rfc3339();
+    appointment.status = AppointmentStatus::Cancelled;
+    appointment.cancellation_reason = payload.reason.clone();
+    apply_transition(&mut conn, state.clock.as_ref(), &mut appointment, "cancelled", &user, payload.reason, |tx, appointment| {
+        let message = match &appointment.cancellation_reason {
+            Some(reason) => format!("The appointment for {} was cancelled: {}", appointment.strata_plan, reason),
+            None => format!("The appointment for {} was cancelled.", appointment.strata_plan),
+        };
+        notify_client(tx, appointment, "Appointment cancelled", &message)?;
+        notify_inspector(tx, appointment, "Appointment cancelled", &message)
+    })?;
+
+    Ok(Json(appointment))
+}
+
+pub fn load_appointment(conn: &Connection, id: &str) -> Result<Appointment, StatusCode> {
+    conn.query_row(
+        &format!("SELECT {APPOINTMENT_COLUMNS} FROM appointments WHERE id = ?1"),
+        [id],
+        appointment_from_row,
+    )
+    .optional()
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .ok_or(StatusCode::NOT_FOUND)
+}
+
+fn require_admin(user: &CurrentUser) -> Result<(), StatusCode> {
+    if user.is_admin() {
+        Ok(())
+    } else {
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+// Admins, or clients of the strata the appointment's service request belongs to
+fn require_party(conn: &Connection, user: &CurrentUser, appointment: &Appointment) -> Result<(), StatusCode> {
+    if user.is_admin() {
+        return Ok(());
+    }
+    let Some(scope) = user.strata_scope() else {
+        return Err(StatusCode::FORBIDDEN);
+    };
+    let strata_id: Option<String> = conn
+        .query_row(
+            "SELECT strata_id FROM service_requests WHERE id = ?1",
+            [&appointment.service_request_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if strata_id == Some(scope) {
+        Ok(())
+    } else {
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+// Parties to the appointment, plus the inspector it is booked with
+pub fn require_viewer(conn: &Connection, user: &CurrentUser, appointment: &Appointment) -> Result<(), StatusCode> {
+    if user.is_inspector() {
+        return if appointment.inspector_id.as_ref() == Some(&user.0.id) {
+            Ok(())
+        } else {
+            Err(StatusCode::FORBIDDEN)
+        };
+    }
+    require_party(conn, user, appointment)
+}
+
+// Transitions not allowed from the current status are reported as 409
+fn require_status(appointment: &Appointment, allowed: &[AppointmentStatus]) -> Result<(), StatusCode> {
+    if allowed.contains(&appointment.status) {
+        Ok(())
+    } else {
+        Err(StatusCode::CONFLICT)
+    }
+}
+
+// 409 when the appointment at `date` `time` would overlap, or leave no time to
+// travel between, the inspector's other confirmed visits that day
+pub fn ensure_slot_free(
+    conn: &Connection,
+    appointment: &Appointment,
+    inspector_id: &str,
+    date: &str,
+    time: &str,
+) -> Result<(), StatusCode> {
+    let start = clock::parse_local_time(time).ok_or(StatusCode::BAD_REQUEST)?;
+    if slots::slot_is_free(conn, inspector_id, appointment, date, start).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? {
+        Ok(())
+    } else {
+        Err(StatusCode::CONFLICT)
+    }
+}
+
+fn is_unique_violation(err: &rusqlite::Error) -> bool {
+    matches!(
+        err,
+        rusqlite::Error::SqliteFailure(e, _)
+            if e.extended_code == rusqlite::ffi::SQLITE_CONSTRAINT_UNIQUE
+                || e.extended_code == rusqlite::ffi::SQLITE_CONSTRAINT_PRIMARYKEY
+    )
+}
+
+// Persist the appointment's new state, its audit entry, the notifications
+// about it and the request's refreshed milestones in one transaction, so a
+// failure part way leaves nothing behind. The entry and notifications carry
+// the appointment's updated_at, which the caller stamps.
+fn apply_transition(
+    conn: &mut Connection,
+    clock: &dyn Clock,
+    appointment: &mut Appointment,
+    action: &str,
+    user: &CurrentUser,
+    reason: Option<String>,
+    notify: impl FnOnce(&Connection, &Appointment) -> Result<(), StatusCode>,
+) -> Result<(), StatusCode> {
+    let tx = conn.transaction().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let from: String = tx
+        .query_row("SELECT status FROM appointments WHERE id = ?1", [&appointment.id], |row| row.get(0))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let from = AppointmentStatus::parse(&from).unwrap_or_default();
+    localize(&tx, appointment).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    tx.execute(
+        "UPDATE appointments
+         SET appointment_type = ?1, confirmed_date = ?2, confirmed_time = ?3, inspector_id = ?4, status = ?5,
+             notes = ?6, cancellation_reason = ?7, updated_at = ?8, starts_at = ?9
+         WHERE id = ?10",
+        rusqlite::params![
+            &appointment.appointment_type,
+            &appointment.confirmed_date,
+            &appointment.confirmed_time,
+            &appointment.inspector_id,
+            appointment.status.as_str(),
+            &appointment.notes,
+            &appointment.cancellation_reason,
+            &appointment.updated_at,
+            &appointment.starts_at,
+            &appointment.id,
+        ],
+    )
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    record_event(&tx, appointment, action, Some(from), user, reason)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    notify(&tx, appointment)?;
+    milestones::refresh_request(&tx, &appointment.service_request_id, clock)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    tx.commit().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+// Times are stored as 24-hour "HH:MM" in the strata's time zone, alongside the
+// UTC instant of the slot they describe
+pub fn localize(conn: &Connection, appointment: &mut Appointment) -> rusqlite::Result<()> {
+    appointment.requested_time_1 = clock::normalize_time(&appointment.requested_time_1);
+    for time in [&mut appointment.requested_time_2, &mut appointment.confirmed_time].into_iter().flatten() {
+        *time = clock::normalize_time(time);
+    }
+
+    let (date, time) = match (&appointment.confirmed_date, &appointment.confirmed_time) {
+        (Some(date), Some(time)) => (date, time),
+        _ => (&appointment.requested_date_1, &appointment.requested_time_1),
+    };
+    let slot = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok().zip(clock::parse_local_time(time));
+    appointment.starts_at = match slot {
+        Some((date, time)) => {
+            let tz = clock::request_time_zone(conn, &appointment.service_request_id)?;
+            Some(clock::to_utc(date, time, tz).to_rfc3339())
+        }
+        None => None,
+    };
+    Ok(())
+}
+
+pub fn insert_appointment(conn: &Connection, appointment: &mut Appointment) -> rusqlite::Result<()> {
+    localize(conn, appointment)?;
+    conn.execute(
+        &format!(
+            "INSERT INTO appointments ({APPOINTMENT_COLUMNS})
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)"
+        ),
+        rusqlite::params![
+            &appointment.id,
+            &appointment.service_request_id,
+            &appointment.strata_plan,
+            &appointment.appointment_type,
+            &appointment.requested_date_1,
+            &appointment.requested_time_1,
+            &appointment.requested_date_2,
+            &appointment.requested_time_2,
+            &appointment.confirmed_date,
+            &appointment.confirmed_time,
+            &appointment.inspector_id,
+            appointment.status.as_str(),
+            &appointment.meeting_type,
+            &appointment.notes,
+            &appointment.cancellation_reason,
+            &appointment.created_at,
+            &appointment.updated_at,
+            &appointment.starts_at,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn record_event(
+    conn: &Connection,
+    appointment: &Appointment,
+    action: &str,
+    from: Option<AppointmentStatus>,
+    user: &CurrentUser,
+    reason: Option<String>,
+) -> rusqlite::Result<()> {
+    let (date, time) = match appointment.status {
+        AppointmentStatus::Pending => (
+            Some(appointment.requested_date_1.clone()),
+            Some(appointment.requested_time_1.clone()),
+        ),
+        _ => (appointment.confirmed_date.clone(), appointment.confirmed_time.clone()),
+    };
+    conn.execute(
+        "INSERT INTO appointment_events (id, appointment_id, action, from_status, to_status, actor_id, reason, date, time, inspector_id, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        rusqlite::params![
+            uuid::Uuid::new_v4().to_string(),
+            &appointment.id,
+            action,
+            from.map(|s| s.as_str()),
+            appointment.status.as_str(),
+            &user.0.id,
+            reason,
+            date,
+            time,
+            &appointment.inspector_id,
+            &appointment.updated_at,
+        ],
+    )?;
+    Ok(())
+}
+
+fn notify_confirmed(conn: &Connection, appointment: &Appointment) -> Result<(), StatusCode> {
+    let message = format!(
+        "{} on {} at {}.",
+        appointment.strata_plan,
+        appointment.confirmed_date.as_deref().unwrap_or(""),
+        appointment.confirmed_time.as_deref().unwrap_or(""),
+    );
+    notify_client(conn, appointment, "Appointment confirmed", &message)?;
+    notify_inspector(conn, appointment, "Appointment assigned", &message)
+}
+
+// Every client user of the strata behind the service request
+pub fn notify_client(conn: &Connection, appointment: &Appointment, title: &str, message: &str) -> Result<(), StatusCode> {
+    let user_ids: Vec<String> = conn
+        .prepare(
+            "SELECT u.id FROM users u JOIN service_requests r ON r.strata_id = u.strata_id
+             WHERE r.id = ?1 AND u.role = 'client'",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map([&appointment.service_request_id], |row| row.get(0))?
+                .collect()
+        })
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    for user_id in user_ids {
+        notifications::notify(conn, &user_id, title, message, "info", Some("/client/inspection"), &appointment.updated_at)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+    Ok(())
+}
+
+fn notify_inspector(conn: &Connection, appointment: &Appointment, title: &str, message: &str) -> Result<(), StatusCode> {
+    let Some(inspector_id) = &appointment.inspector_id else {
+        return Ok(());
+    };
+    notifications::notify(conn, inspector_id, title, message, "info", Some("/inspector/schedule"), &appointment.updated_at)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+pub fn appointment_from_row(row: &Row) -> rusqlite::Result<Appointment> {
+    Ok(Appointment {
+        id: row.get(0)?,
+        service_request_id: row.get(1)?,
+        strata_plan: row.get(2)?,
+        appointment_type: row.get(3)?,
+        requested_date_1: row.get(4)?,
+        requested_time_1: row.get(5)?,
+        requested_date_2: row.get(6)?,
+        requested_time_2: row.get(7)?,
+        confirmed_date: row.get(8)?,
+        confirmed_time: row.get(9)?,
+        inspector_id: row.get(10)?,
+        status: AppointmentStatus::parse(&row.get::<_, String>(11)?).unwrap_or_default(),
+        meeting_type: row.get(12)?,
+        notes: row.get(13)?,
+        cancellation_reason: row.get(14)?,
+        created_at: row.get(15)?,
+        updated_at: row.get(16)?,
+        starts_at: row.get(17)?,
+    })
 }
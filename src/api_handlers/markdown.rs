@@ -0,0 +1,97 @@
+use pulldown_cmark::{html, Options, Parser};
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::sync::LazyLock;
+
+// @handle, matched against users.handle ("@john.doe"). Must not follow a word
+// character, so email addresses are not mentions.
+static MENTION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|[^\w.@])@([A-Za-z0-9][A-Za-z0-9._-]*)").unwrap());
+
+// Render user-written Markdown to HTML that is safe to inject into the page
+pub fn render(markdown: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, Parser::new_ext(markdown, options));
+    ammonia::clean(&unsafe_html)
+}
+
+// Distinct, lowercased handles mentioned in the text
+pub fn mentions(text: &str) -> BTreeSet<String> {
+    MENTION
+        .captures_iter(text)
+        .map(|caps| caps[1].trim_end_matches(['.', '-', '_']).to_lowercase())
+        .filter(|handle| !handle.is_empty())
+        .collect()
+}
+
+// Normalize free-form tags: lowercase, no leading '#', spaces become dashes
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    tags.iter()
+        .map(|tag| {
+            tag.trim()
+                .trim_start_matches('#')
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join("-")
+                .to_lowercase()
+        })
+        .filter(|tag| !tag.is_empty() && seen.insert(tag.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_strips_scripts_and_event_handlers() {
+        let html = render("Hello <script>alert(1)</script><img src=x onerror=\"alert(2)\">");
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("onerror"));
+        assert!(html.contains("Hello"));
+    }
+
+    #[test]
+    fn render_drops_javascript_links() {
+        let html = render("[click](javascript:alert(1))");
+        assert!(!html.contains("javascript:"));
+        assert!(html.contains("click"));
+    }
+
+    #[test]
+    fn render_keeps_markdown_formatting() {
+        let html = render("**bold** and ~~gone~~\n\n| a |\n|---|\n| 1 |");
+        assert!(html.contains("<strong>bold</strong>"));
+        assert!(html.contains("<del>gone</del>"));
+        assert!(html.contains("<table>"));
+    }
+
+    #[test]
+    fn mentions_are_lowercased_and_deduplicated() {
+        let found = mentions("@John.Doe please check, cc @jane and @john.doe.");
+        assert_eq!(found.into_iter().collect::<Vec<_>>(), vec!["jane", "john.doe"]);
+    }
+
+    #[test]
+    fn email_addresses_are_not_mentions() {
+        assert!(mentions("write to admin@srp.com or a.b@c.d").is_empty());
+    }
+
+    #[test]
+    fn mention_at_start_of_line_counts() {
+        assert!(mentions("@inspector on site Friday").contains("inspector"));
+        assert!(mentions("(@inspector)").contains("inspector"));
+    }
+
+    #[test]
+    fn tags_are_normalized() {
+        let tags = vec!["#Roof".to_string(), "roof".to_string(), " Site Visit ".to_string(), "#".to_string()];
+        assert_eq!(normalize_tags(&tags), vec!["roof", "site-visit"]);
+    }
+}
@@ -0,0 +1,479 @@
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{Duration, NaiveDate};
+use crate::api_handlers::availability;
+use crate::api_handlers::business_calendar::BusinessCalendar;
+use crate::api_handlers::clock::{self, Clock};
+use crate::api_handlers::session::CurrentUser;
+use crate::api_handlers::timeline_engine::{self, ReportScope, Timeline, TimelineError, TimelineInput};
+use crate::db::AppState;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+// Clients have this long after the file opens to upload their documents
+const DOCUMENTS_DUE_DAYS: i64 = 30;
+
+// An unbooked inspection is projected this long before the draft deadline,
+// leaving time to write the report
+const INSPECTION_LEAD_DAYS: i64 = 21;
+
+const DEFAULT_FISCAL_START_MONTH: u8 = 1;
+const DEFAULT_REPORT_SCOPE: &str = "This Fiscal";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MilestoneQuery {
+    from: Option<String>, // YYYY-MM-DD, inclusive, on the due date
+    to: Option<String>,
+    strata_id: Option<String>,
+    overdue: Option<bool>,
+    sort: Option<String>, // date (default), strata or overdue
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Milestone {
+    pub service_request_id: String,
+    pub strata_id: String,
+    pub strata_plan: String,
+    pub kind: &'static str,
+    pub label: &'static str,
+    pub due_date: String,
+    pub completed_date: Option<String>,
+    pub overdue: bool,
+    pub days_remaining: i64, // from today in the strata's time zone; negative once past
+    pub projected: bool, // estimated rather than booked or recorded
+}
+
+// A request whose inputs the timeline engine rejected
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedRequest {
+    service_request_id: String,
+    strata_plan: String,
+    error: TimelineError,
+    message: String,
+}
+
+#[derive(Serialize)]
+pub struct MilestoneOverview {
+    milestones: Vec<Milestone>,
+    skipped: Vec<SkippedRequest>,
+}
+
+struct OpenRequest {
+    id: String,
+    strata_id: String,
+    strata_plan: String,
+    province: Option<String>,
+    time_zone: Option<String>,
+    created_at: String,
+    file_opened_date: Option<String>,
+    fiscal_year_start_month: Option<u8>,
+    agm_date: Option<String>,
+    last_depreciation_report_date: Option<String>,
+    target_date: Option<String>,
+    report_scope: Option<String>,
+    draft_sent_date: Option<String>,
+    documents_received: Option<String>,
+    inspection_date: Option<String>,
+    inspection_status: Option<String>,
+}
+
+pub fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(list_milestones))
+        .with_state(state)
+}
+
+fn parse_day(value: &str) -> Option<NaiveDate> {
+    value.get(..10).and_then(|day| NaiveDate::parse_from_str(day, "%Y-%m-%d").ok())
+}
+
+fn load_open_requests(
+    conn: &Connection,
+    strata_id: Option<&str>,
+    service_request_id: Option<&str>,
+) -> rusqlite::Result<Vec<OpenRequest>> {
+    // Booked inspection: a confirmed or completed visit, the latest if rescheduled
+    let mut stmt = conn.prepare(
+        "SELECT r.id, r.strata_id, s.strata_plan, s.province, s.time_zone, r.created_at, r.file_opened_date, r.fiscal_year_start_month,
+                r.agm_date, r.last_depreciation_report_date, r.target_date, r.report_scope, r.draft_sent_date,
+                (SELECT MAX(d.uploaded_at) FROM documents d
+                 WHERE d.service_request_id = r.id AND d.category = 'mandatory' AND d.status != 'rejected'),
+                a.confirmed_date, a.status
+         FROM service_requests r
+         JOIN stratas s ON s.id = r.strata_id
+         LEFT JOIN appointments a ON a.id = (
+             SELECT id FROM appointments
+             WHERE service_request_id = r.id AND appointment_type = 'inspection'
+               AND status IN ('confirmed', 'completed') AND confirmed_date IS NOT NULL
+             ORDER BY confirmed_date DESC LIMIT 1)
+         WHERE r.status NOT IN ('Completed', 'Cancelled') AND (?1 IS NULL OR r.strata_id = ?1)
+           AND (?2 IS NULL OR r.id = ?2)
+         ORDER BY r.id",
+    )?;
+    let rows = stmt.query_map([strata_id, service_request_id], |row| {
+        Ok(OpenRequest {
+            id: row.get(0)?,
+            strata_id: row.get(1)?,
+            strata_plan: row.get(2)?,
+            province: row.get(3)?,
+            time_zone: row.get(4)?,
+            created_at: row.get(5)?,
+            file_opened_date: row.get(6)?,
+            fiscal_year_start_month: row.get(7)?,
+            agm_date: row.get(8)?,
+            last_depreciation_report_date: row.get(9)?,
+            target_date: row.get(10)?,
+            report_scope: row.get(11)?,
+            draft_sent_date: row.get(12)?,
+            documents_received: row.get(13)?,
+            inspection_date: row.get(14)?,
+            inspection_status: row.get(15)?,
+        })
+    })?;
+    rows.collect()
+}
+
+fn timeline_input(request: &OpenRequest, today: NaiveDate) -> Result<TimelineInput, TimelineError> {
+    let file_opened = match request.file_opened_date.as_deref() {
+        Some(date) => timeline_engine::parse_date("file_opened_date", date)?,
+        None => parse_day(&request.created_at).ok_or_else(|| TimelineError::InvalidDate {
+            field: "created_at",
+            value: request.created_at.clone(),
+        })?,
+    };
+    let scope = request.report_scope.as_deref().unwrap_or(DEFAULT_REPORT_SCOPE);
+    Ok(TimelineInput {
+        file_opened,
+        fiscal_year_start_month: request.fiscal_year_start_month.unwrap_or(DEFAULT_FISCAL_START_MONTH),
+        last_agm: timeline_engine::parse_optional_date("agm_date", request.agm_date.as_deref())?,
+        last_depr_report: timeline_engine::parse_optional_date(
+            "last_depreciation_report_date",
+            request.last_depreciation_report_date.as_deref(),
+        )?,
+        target_agm: timeline_engine::parse_optional_date("target_date", request.target_date.as_deref())?,
+        report_scope: ReportScope::parse(scope).ok_or_else(|| TimelineError::UnknownReportScope {
+            value: scope.to_string(),
+        })?,
+        today,
+    })
+}
+
+// Timeline of one open request in its strata's calendar and time zone; None
+// once the request is closed or if it does not exist
+pub fn request_timeline(
+    conn: &Connection,
+    service_request_id: &str,
+    clock: &dyn Clock,
+) -> rusqlite::Result<Option<Result<Timeline, TimelineError>>> {
+    let Some(request) = load_open_requests(conn, None, Some(service_request_id))?.into_iter().next() else {
+        return Ok(None);
+    };
+    let calendar = BusinessCalendar::load(conn, request.province.as_deref())?;
+    let tz = request.time_zone.as_deref().and_then(clock::parse_time_zone).unwrap_or(clock::DEFAULT_TIME_ZONE);
+    Ok(Some(
+        timeline_input(&request, clock.today_in(tz)).and_then(|input| timeline_engine::compute(&input, &calendar)),
+    ))
+}
+
+fn milestones_for(
+    request: &OpenRequest,
+    calendar: &BusinessCalendar,
+    today: NaiveDate,
+) -> Result<Vec<Milestone>, TimelineError> {
+    let input = timeline_input(request, today)?;
+    let file_opened = input.file_opened;
+    let timeline = timeline_engine::compute(&input, calendar)?;
+
+    let draft_sent = timeline_engine::parse_optional_date("draft_sent_date", request.draft_sent_date.as_deref())?;
+    let documents_received = request.documents_received.as_deref().and_then(parse_day);
+    let inspection = request.inspection_date.as_deref().and_then(parse_day);
+    let inspected = inspection.filter(|_| request.inspection_status.as_deref() == Some("completed"));
+
+    // (kind, label, due, completed, projected)
+    let entries = [
+        ("file_opened", "File opened", file_opened, Some(file_opened), false),
+        (
+            "documents_due",
+            "Documents due",
+            calendar.on_or_before(file_opened + Duration::days(DOCUMENTS_DUE_DAYS)),
+            documents_received,
+            false,
+        ),
+        (
+            "inspection",
+            "Inspection",
+            inspection.unwrap_or_else(|| calendar.on_or_before(timeline.draft_deadline - Duration::days(INSPECTION_LEAD_DAYS))),
+            inspected,
+            inspection.is_none(),
+        ),
+        ("draft_deadline", "Draft deadline", timeline.draft_deadline, draft_sent, false),
+        (
+            "draft_sent",
+            "Draft sent",
+            draft_sent.unwrap_or(timeline.draft_deadline),
+            draft_sent,
+            draft_sent.is_none(),
+        ),
+        ("agm", "AGM", timeline.next_projected_agm, None, input.target_agm.is_none()),
+    ];
+
+    Ok(entries
+        .into_iter()
+        .map(|(kind, label, due, completed, projected)| Milestone {
+            service_request_id: request.id.clone(),
+            strata_id: request.strata_id.clone(),
+            strata_plan: request.strata_plan.clone(),
+            kind,
+            label,
+            due_date: due.format("%Y-%m-%d").to_string(),
+            completed_date: completed.map(|date| date.format("%Y-%m-%d").to_string()),
+            overdue: completed.is_none() && due < today,
+            days_remaining: (due - today).num_days(),
+            projected,
+        })
+        .collect())
+}
+
+// Milestones of the given open requests, the ids of those computed, and the
+// requests whose inputs the engine rejected
+fn compute_milestones(
+    conn: &Connection,
+    requests: &[OpenRequest],
+    clock: &dyn Clock,
+) -> rusqlite::Result<(Vec<Milestone>, Vec<String>, Vec<SkippedRequest>)> {
+    let closures = availability::load_closures(conn)?;
+
+    let mut milestones = Vec::new();
+    let mut computed = Vec::new();
+    let mut skipped = Vec::new();
+    for request in requests {
+        // Deadlines follow the holidays of the province the strata is in
+        let calendar = BusinessCalendar::new(request.province.as_deref(), closures.clone());
+        let tz = request.time_zone.as_deref().and_then(clock::parse_time_zone).unwrap_or(clock::DEFAULT_TIME_ZONE);
+        match milestones_for(request, &calendar, clock.today_in(tz)) {
+            Ok(found) => {
+                milestones.extend(found);
+                computed.push(request.id.clone());
+            }
+            Err(error) => skipped.push(SkippedRequest {
+                service_request_id: request.id.clone(),
+                strata_plan: request.strata_plan.clone(),
+                message: error.to_string(),
+                error,
+            }),
+        }
+    }
+    Ok((milestones, computed, skipped))
+}
+
+// Milestones of every open request, optionally for one strata, without
+// touching the stored copies
+pub fn current(
+    conn: &Connection,
+    strata_id: Option<&str>,
+    clock: &dyn Clock,
+) -> rusqlite::Result<(Vec<Milestone>, Vec<SkippedRequest>)> {
+    let requests = load_open_requests(conn, strata_id, None)?;
+    let (milestones, _, skipped) = compute_milestones(conn, &requests, clock)?;
+    Ok((milestones, skipped))
+}
+
+// Recompute and store the milestones of every open request, optionally for one
+// strata. Run by the deadline monitor, and inside the transaction of writes
+// that move every deadline, such as company closures.
+pub fn refresh(
+    conn: &Connection,
+    strata_id: Option<&str>,
+    clock: &dyn Clock,
+) -> rusqlite::Result<(Vec<Milestone>, Vec<SkippedRequest>)> {
+    let requests = load_open_requests(conn, strata_id, None)?;
+    let (milestones, computed, skipped) = compute_milestones(conn, &requests, clock)?;
+    persist(conn, &computed, &milestones, &clock.now().to_rfc3339())?;
+    Ok((milestones, skipped))
+}
+
+// Recompute and store one request's milestones, inside the transaction that
+// changed its inputs so the stored copy never disagrees with them
+pub fn refresh_request(conn: &Connection, service_request_id: &str, clock: &dyn Clock) -> rusqlite::Result<()> {
+    let requests = load_open_requests(conn, None, Some(service_request_id))?;
+    let (milestones, computed, _) = compute_milestones(conn, &requests, clock)?;
+    persist(conn, &computed, &milestones, &clock.now().to_rfc3339())
+}
+
+// Replace the stored milestones of the requests just computed and drop those
+// of requests that have since closed. Callers hold a transaction.
+fn persist(conn: &Connection, computed: &[String], milestones: &[Milestone], now: &str) -> rusqlite::Result<()> {
+    for id in computed {
+        conn.execute("DELETE FROM timeline_milestones WHERE service_request_id = ?1", [id])?;
+    }
+    conn.execute(
+        "DELETE FROM timeline_milestones WHERE service_request_id IN
+            (SELECT id FROM service_requests WHERE status IN ('Completed', 'Cancelled'))",
+        [],
+    )?;
+    for milestone in milestones {
+        conn.execute(
+            "INSERT INTO timeline_milestones (service_request_id, kind, due_date, completed_date, projected, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            (
+                &milestone.service_request_id,
+                milestone.kind,
+                &milestone.due_date,
+                &milestone.completed_date,
+                milestone.projected,
+                now,
+            ),
+        )?;
+    }
+    Ok(())
+}
+
+// Every milestone of every open request, recomputed on each call. Read-only:
+// the stored copies are kept current by the deadline monitor and by the writes
+// to their inputs (request dates, documents, inspections and closures).
+async fn list_milestones(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Query(params): Query<MilestoneQuery>,
+) -> Result<Json<MilestoneOverview>, StatusCode> {
+    let parse = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").map_err(|_| StatusCode::BAD_REQUEST))
+            .transpose()
+    };
+    let from = parse(&params.from)?;
+    let to = parse(&params.to)?;
+    if from.zip(to).is_some_and(|(from, to)| to < from) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // Clients only ever see their own strata
+    let strata_id = match user.strata_scope() {
+        Some(own) if params.strata_id.as_ref().is_some_and(|id| *id != own) => return Err(StatusCode::FORBIDDEN),
+        Some(own) => Some(own),
+        None => params.strata_id.clone(),
+    };
+
+    let (mut milestones, skipped) = {
+        let conn = state.conn.lock().unwrap();
+        current(&conn, strata_id.as_deref(), state.clock.as_ref()).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    };
+
+    // ISO dates compare correctly as strings
+    let from = from.map(|date| date.format("%Y-%m-%d").to_string());
+    let to = to.map(|date| date.format("%Y-%m-%d").to_string());
+    milestones.retain(|m| {
+        from.as_ref().is_none_or(|from| m.due_date >= *from)
+            && to.as_ref().is_none_or(|to| m.due_date <= *to)
+            && params.overdue.is_none_or(|overdue| m.overdue == overdue)
+    });
+
+    match params.sort.as_deref().unwrap_or("date") {
+        "date" => milestones.sort_by(|a, b| a.due_date.cmp(&b.due_date)),
+        "strata" => milestones.sort_by(|a, b| {
+            (&a.strata_plan, &a.service_request_id, &a.due_date).cmp(&(&b.strata_plan, &b.service_request_id, &b.due_date))
+        }),
+        // Overdue first, most overdue at the top
+        "overdue" => milestones.sort_by(|a, b| b.overdue.cmp(&a.overdue).then_with(|| a.due_date.cmp(&b.due_date))),
+        _ => return Err(StatusCode::BAD_REQUEST),
+    }
+
+    Ok(Json(MilestoneOverview { milestones, skipped }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_handlers::clock::FixedClock;
+
+    fn request(time_zone: &str) -> OpenRequest {
+        OpenRequest {
+            id: "req-1".to_string(),
+            strata_id: "strata-1".to_string(),
+            strata_plan: "VIS 1".to_string(),
+            province: Some("BC".to_string()),
+            time_zone: Some(time_zone.to_string()),
+            created_at: "2026-01-05T17:00:00Z".to_string(),
+            file_opened_date: Some("2026-01-05".to_string()),
+            fiscal_year_start_month: Some(4),
+            agm_date: None,
+            last_depreciation_report_date: None,
+            target_date: None,
+            report_scope: Some("This Fiscal".to_string()),
+            draft_sent_date: None,
+            documents_received: None,
+            inspection_date: None,
+            inspection_status: None,
+        }
+    }
+
+    fn closures_only() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE company_closures (id TEXT PRIMARY KEY, name TEXT NOT NULL, date TEXT NOT NULL, recurring INTEGER NOT NULL DEFAULT 0)",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    fn file_opened_days(milestones: &[Milestone]) -> i64 {
+        milestones.iter().find(|m| m.kind == "file_opened").map(|m| m.days_remaining).unwrap()
+    }
+
+    #[test]
+    fn days_remaining_count_from_the_fixed_clock() {
+        let conn = closures_only();
+        let clock = FixedClock("2026-03-08T20:00:00Z".parse().unwrap());
+        let (milestones, computed, skipped) = compute_milestones(&conn, &[request("America/Vancouver")], &clock).unwrap();
+        assert!(skipped.is_empty());
+        assert_eq!(computed, vec!["req-1".to_string()]);
+        // January 5 to March 8
+        assert_eq!(file_opened_days(&milestones), -62);
+    }
+
+    #[test]
+    fn today_is_taken_in_the_strata_time_zone() {
+        let conn = closures_only();
+        // Saturday evening in Vancouver, already Sunday in Toronto
+        let clock = FixedClock("2026-03-08T06:30:00Z".parse().unwrap());
+        let requests = [request("America/Vancouver")];
+        let (pacific, _, _) = compute_milestones(&conn, &requests, &clock).unwrap();
+        let requests = [request("America/Toronto")];
+        let (eastern, _, _) = compute_milestones(&conn, &requests, &clock).unwrap();
+        assert_eq!(file_opened_days(&pacific), -61);
+        assert_eq!(file_opened_days(&eastern), -62);
+    }
+
+    #[test]
+    fn stored_milestones_roll_back_with_the_write_that_refreshed_them() {
+        let state = AppState::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        let mut conn = state.conn.lock().unwrap();
+        let clock = FixedClock("2026-03-08T20:00:00Z".parse().unwrap());
+        let stored = |conn: &Connection| -> Vec<(String, String)> {
+            conn.prepare("SELECT kind, due_date FROM timeline_milestones WHERE service_request_id = 'req-1' ORDER BY kind")
+                .unwrap()
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .unwrap()
+                .collect::<rusqlite::Result<_>>()
+                .unwrap()
+        };
+        refresh_request(&conn, "req-1", &clock).unwrap();
+        let before = stored(&conn);
+        assert!(!before.is_empty());
+
+        let tx = conn.transaction().unwrap();
+        tx.execute("UPDATE service_requests SET file_opened_date = '2026-02-02' WHERE id = 'req-1'", []).unwrap();
+        refresh_request(&tx, "req-1", &clock).unwrap();
+        assert_ne!(stored(&tx), before);
+        drop(tx);
+        assert_eq!(stored(&conn), before);
+    }
+}
@@ -4,8 +4,30 @@ pub mod util;
 pub mod auth;
 pub mod stratas;
 pub mod timelines;
+pub mod timeline_engine;
+pub mod clock;
+pub mod milestones;
+pub mod deadline_alerts;
 pub mod surveys;
 pub mod logistics;
 pub mod ecs_documents;
+pub mod doc_extract;
+pub mod doc_entities;
+pub mod doc_classify;
+pub mod document_worker;
+pub mod documents;
+pub mod session;
+pub mod search;
+pub mod markdown;
+pub mod notifications;
+pub mod availability;
+pub mod business_calendar;
+pub mod slots;
+pub mod geocode;
+pub mod calendar;
+pub mod draft_meetings;
 pub mod ecs_scheduler;
+pub mod scheduler;
 pub mod ecs_inspection;
+pub mod findings;
+pub mod components;
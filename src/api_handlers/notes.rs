@@ -1,19 +1,29 @@
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    routing::{delete, get, put},
+    extract::{DefaultBodyLimit, Multipart, Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::{delete, get, post, put},
     Json, Router,
 };
+use rusqlite::{Connection, OptionalExtension, Row};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::api_handlers::markdown;
+use crate::api_handlers::notifications;
+use crate::api_handlers::search;
+use crate::api_handlers::session::CurrentUser;
 use crate::db::AppState;
 
 #[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CreateNote {
     title: String,
     content: String,
+    strata_id: Option<String>,
+    service_request_id: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
 }
 
 #[derive(Deserialize)]
@@ -21,6 +31,7 @@ pub struct UpdateNote {
     id: i64,
     title: String,
     content: String,
+    tags: Option<Vec<String>>, // omitted keeps the current tags
 }
 
 #[derive(Deserialize)]
@@ -34,52 +45,274 @@ pub struct TogglePin {
 }
 
 #[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SearchQuery {
     q: Option<String>,
+    strata_id: Option<String>,
+    service_request_id: Option<String>,
+    pinned: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkAttachment {
+    note_id: i64,
+    document_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct DeleteAttachment {
+    id: String,
+}
+
+#[derive(Serialize)]
+pub struct NoteAttachment {
+    id: String,
+    note_id: i64,
+    kind: String, // photo, file, document
+    document_id: Option<String>,
+    file_name: String,
+    mime_type: Option<String>,
+    file_size: Option<i64>,
+    created_at: String,
 }
 
 #[derive(Serialize)]
 pub struct NoteResponse {
     id: i64,
+    owner_id: String,
+    strata_id: Option<String>,
+    service_request_id: Option<String>,
     title: String,
-    content: String,
+    content: String,      // Markdown source
+    content_html: String, // rendered and sanitized
+    tags: Vec<String>,
+    attachments: Vec<NoteAttachment>,
     pinned: bool,
     created_at: i64,
+    updated_at: i64,
+}
+
+const NOTE_COLUMNS: &str =
+    "n.id, n.owner_id, n.strata_id, n.service_request_id, n.title, n.content, n.content_html, n.tags, n.pinned, n.created_at, n.updated_at";
+
+const ATTACHMENT_COLUMNS: &str =
+    "id, note_id, kind, document_id, file_name, mime_type, file_size, created_at";
+
+fn note_from_row(row: &Row) -> rusqlite::Result<NoteResponse> {
+    Ok(NoteResponse {
+        id: row.get(0)?,
+        owner_id: row.get(1)?,
+        strata_id: row.get(2)?,
+        service_request_id: row.get(3)?,
+        title: row.get(4)?,
+        content: row.get(5)?,
+        content_html: row.get(6)?,
+        tags: row
+            .get::<_, String>(7)?
+            .split_whitespace()
+            .map(str::to_string)
+            .collect(),
+        attachments: vec![],
+        pinned: row.get::<_, i32>(8)? != 0,
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+    })
+}
+
+fn attachment_from_row(row: &Row) -> rusqlite::Result<NoteAttachment> {
+    Ok(NoteAttachment {
+        id: row.get(0)?,
+        note_id: row.get(1)?,
+        kind: row.get(2)?,
+        document_id: row.get(3)?,
+        file_name: row.get(4)?,
+        mime_type: row.get(5)?,
+        file_size: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+fn load_attachments(conn: &Connection, note_id: i64) -> rusqlite::Result<Vec<NoteAttachment>> {
+    conn.prepare(&format!(
+        "SELECT {ATTACHMENT_COLUMNS} FROM note_attachments WHERE note_id = ?1 ORDER BY created_at"
+    ))?
+    .query_map([note_id], attachment_from_row)?
+    .collect()
+}
+
+// Only the author or an admin may change a note or its attachments
+fn can_edit_note(conn: &Connection, user: &CurrentUser, note_id: i64) -> Result<bool, StatusCode> {
+    let owner: Option<String> = conn
+        .query_row("SELECT owner_id FROM notes WHERE id = ?1", [note_id], |row| row.get(0))
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(owner.is_some_and(|owner| user.is_admin() || owner == user.0.id))
+}
+
+// Anyone who may edit the note, plus users mentioned in it
+fn can_view_note(conn: &Connection, user: &CurrentUser, note_id: i64) -> Result<bool, StatusCode> {
+    if can_edit_note(conn, user, note_id)? {
+        return Ok(true);
+    }
+    let mentioned: Option<i64> = conn
+        .query_row(
+            "SELECT 1 FROM note_mentions WHERE note_id = ?1 AND user_id = ?2",
+            (note_id, &user.0.id),
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(mentioned.is_some())
+}
+
+// Give users @mentioned in the saved note read access to it, and notify those
+// not already mentioned in `previous` so editing a note does not re-notify
+// everyone. A client can only be mentioned into a note on their own strata.
+fn sync_mentions(
+    conn: &Connection,
+    author: &CurrentUser,
+    note_id: i64,
+    previous: &str,
+    now: &str,
+) -> rusqlite::Result<()> {
+    let (title, content, strata_id): (String, String, Option<String>) = conn.query_row(
+        "SELECT title, content, strata_id FROM notes WHERE id = ?1",
+        [note_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+    let strata_id = strata_id.as_deref();
+    conn.execute("DELETE FROM note_mentions WHERE note_id = ?1", [note_id])?;
+
+    let already = markdown::mentions(previous);
+    for handle in markdown::mentions(&content) {
+        let mentioned: Option<(String, String, Option<String>)> = conn
+            .query_row(
+                "SELECT id, role, strata_id FROM users WHERE handle = ?1",
+                [&handle],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+        let Some((user_id, role, user_strata)) = mentioned else { continue };
+        if user_id == author.0.id {
+            continue;
+        }
+        if role == "client" && (strata_id.is_none() || user_strata.as_deref() != strata_id) {
+            continue;
+        }
+
+        conn.execute(
+            "INSERT OR IGNORE INTO note_mentions (note_id, user_id) VALUES (?1, ?2)",
+            (note_id, &user_id),
+        )?;
+        if already.contains(&handle) {
+            continue;
+        }
+        notifications::notify(
+            conn,
+            &user_id,
+            &format!("{} mentioned you", author.0.name),
+            &format!("You were mentioned in the note \"{}\".", title),
+            "info",
+            Some(&format!("/notes?id={}", note_id)),
+            now,
+        )?;
+    }
+    Ok(())
+}
+
+// Work out which strata a new note belongs to. A service request implies its
+// strata; clients may only file notes against their own strata.
+fn resolve_strata(
+    conn: &Connection,
+    user: &CurrentUser,
+    strata_id: Option<String>,
+    service_request_id: Option<&String>,
+) -> Result<Option<String>, StatusCode> {
+    let strata_id = match service_request_id {
+        Some(request_id) => {
+            let request_strata: String = conn
+                .query_row(
+                    "SELECT strata_id FROM service_requests WHERE id = ?1",
+                    [request_id],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+                .ok_or(StatusCode::NOT_FOUND)?;
+            if strata_id.as_ref().is_some_and(|s| *s != request_strata) {
+                return Err(StatusCode::BAD_REQUEST);
+            }
+            Some(request_strata)
+        }
+        None => strata_id,
+    };
+
+    if let (Some(scope), Some(strata)) = (user.strata_scope(), &strata_id) {
+        if scope != *strata {
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+    Ok(strata_id)
 }
 
 // CREATE
 async fn create_note(
     State(state): State<Arc<AppState>>,
+    user: CurrentUser,
     Json(payload): Json<CreateNote>,
 ) -> Result<Json<NoteResponse>, StatusCode> {
     let conn = state
         .conn
         .lock()
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .as_secs() as i64;
+    let now = state.clock.now();
+    let timestamp = now.timestamp();
+    let strata_id = resolve_strata(&conn, &user, payload.strata_id, payload.service_request_id.as_ref())?;
+    let content_html = markdown::render(&payload.content);
+    let tags = markdown::normalize_tags(&payload.tags);
 
     conn.execute(
-        "INSERT INTO notes (title, content, created_at) VALUES (?1, ?2, ?3)",
-        (&payload.title, &payload.content, timestamp),
+        "INSERT INTO notes (owner_id, strata_id, service_request_id, title, content, content_html, tags, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)",
+        (
+            &user.0.id,
+            &strata_id,
+            &payload.service_request_id,
+            &payload.title,
+            &payload.content,
+            &content_html,
+            tags.join(" "),
+            timestamp,
+        ),
     )
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     let id = conn.last_insert_rowid();
+    sync_mentions(&conn, &user, id, "", &now.to_rfc3339())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
     Ok(Json(NoteResponse {
         id,
+        owner_id: user.0.id,
+        strata_id,
+        service_request_id: payload.service_request_id,
         title: payload.title,
         content: payload.content,
+        content_html,
+        tags,
+        attachments: vec![],
         pinned: false,
         created_at: timestamp,
+        updated_at: timestamp,
     }))
 }
 
-// READ ALL (with optional search)
+// READ ALL (with optional search and filters). Authors see their own notes and
+// the ones they are mentioned in; admins see everyone's.
 async fn get_notes(
     State(state): State<Arc<AppState>>,
+    user: CurrentUser,
     Query(params): Query<SearchQuery>,
 ) -> Result<Json<Vec<NoteResponse>>, StatusCode> {
     let conn = state
@@ -87,56 +320,50 @@ async fn get_notes(
         .lock()
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    let notes: Vec<NoteResponse> = if let Some(query) = params.q.filter(|q| !q.trim().is_empty()) {
-        // Full-text search with FTS5
-        let search_term = format!("{}*", query.replace('"', "\"\""));
-        let mut stmt = conn
-            .prepare(
-                "SELECT n.id, n.title, n.content, n.pinned, n.created_at
-                 FROM notes n
-                 JOIN notes_fts fts ON n.id = fts.rowid
-                 WHERE notes_fts MATCH ?1
-                 ORDER BY n.pinned DESC, rank",
-            )
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-        let rows = stmt
-            .query_map([&search_term], |row| {
-                Ok(NoteResponse {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    content: row.get(2)?,
-                    pinned: row.get::<_, i32>(3)? != 0,
-                    created_at: row.get(4)?,
-                })
-            })
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-        rows.filter_map(|r| r.ok()).collect()
+    let search_term = params.q.as_deref().and_then(search::build_match_expression);
+
+    // Full-text search with FTS5 joins in the index and ranks by relevance
+    let (from, search_filter, order) = if search_term.is_some() {
+        (
+            "notes n JOIN notes_fts fts ON n.id = fts.rowid",
+            "notes_fts MATCH ?6",
+            "n.pinned DESC, rank",
+        )
     } else {
-        // Return all notes, pinned first
-        let mut stmt = conn
-            .prepare(
-                "SELECT id, title, content, pinned, created_at
-                 FROM notes
-                 ORDER BY pinned DESC, created_at DESC",
-            )
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-        let rows = stmt
-            .query_map([], |row| {
-                Ok(NoteResponse {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    content: row.get(2)?,
-                    pinned: row.get::<_, i32>(3)? != 0,
-                    created_at: row.get(4)?,
-                })
-            })
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-        rows.filter_map(|r| r.ok()).collect()
+        ("notes n", "?6 IS NULL", "n.pinned DESC, n.created_at DESC")
     };
+    let sql = format!(
+        "SELECT {NOTE_COLUMNS}
+         FROM {from}
+         WHERE (?1 OR n.owner_id = ?2 OR n.id IN (SELECT note_id FROM note_mentions WHERE user_id = ?2))
+           AND (?3 IS NULL OR n.strata_id = ?3)
+           AND (?4 IS NULL OR n.service_request_id = ?4)
+           AND (?5 IS NULL OR n.pinned = ?5)
+           AND {search_filter}
+         ORDER BY {order}"
+    );
+
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let rows = stmt
+        .query_map(
+            (
+                user.is_admin(),
+                &user.0.id,
+                &params.strata_id,
+                &params.service_request_id,
+                params.pinned,
+                &search_term,
+            ),
+            note_from_row,
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let mut notes: Vec<NoteResponse> = rows.filter_map(|r| r.ok()).collect();
+    for note in notes.iter_mut() {
+        note.attachments = load_attachments(&conn, note.id).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
 
     Ok(Json(notes))
 }
@@ -144,56 +371,373 @@ async fn get_notes(
 // UPDATE
 async fn update_note(
     State(state): State<Arc<AppState>>,
+    user: CurrentUser,
     Json(payload): Json<UpdateNote>,
 ) -> Result<StatusCode, StatusCode> {
     let conn = state
         .conn
         .lock()
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // Someone else's note is reported as missing rather than forbidden
+    if !can_edit_note(&conn, &user, payload.id)? {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let previous: String = conn
+        .query_row("SELECT content FROM notes WHERE id = ?1", [payload.id], |row| row.get(0))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let now = state.clock.now();
+    let tags = payload.tags.as_deref().map(|tags| markdown::normalize_tags(tags).join(" "));
+
     conn.execute(
-        "UPDATE notes SET title = ?1, content = ?2 WHERE id = ?3",
-        (&payload.title, &payload.content, payload.id),
+        "UPDATE notes SET title = ?1, content = ?2, content_html = ?3, tags = COALESCE(?4, tags), updated_at = ?5
+         WHERE id = ?6",
+        (
+            &payload.title,
+            &payload.content,
+            markdown::render(&payload.content),
+            tags,
+            now.timestamp(),
+            payload.id,
+        ),
     )
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    sync_mentions(&conn, &user, payload.id, &previous, &now.to_rfc3339())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     Ok(StatusCode::OK)
 }
 
 // TOGGLE PIN
 async fn toggle_pin(
     State(state): State<Arc<AppState>>,
+    user: CurrentUser,
     Json(payload): Json<TogglePin>,
 ) -> Result<StatusCode, StatusCode> {
     let conn = state
         .conn
         .lock()
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    conn.execute(
-        "UPDATE notes SET pinned = NOT pinned WHERE id = ?1",
-        [payload.id],
-    )
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let changed = conn
+        .execute(
+            "UPDATE notes SET pinned = NOT pinned WHERE id = ?1 AND (?2 OR owner_id = ?3)",
+            (payload.id, user.is_admin(), &user.0.id),
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if changed == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
     Ok(StatusCode::OK)
 }
 
 // DELETE
 async fn delete_note(
     State(state): State<Arc<AppState>>,
+    user: CurrentUser,
     Json(payload): Json<DeleteNote>,
 ) -> Result<StatusCode, StatusCode> {
     let conn = state
         .conn
         .lock()
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !can_edit_note(&conn, &user, payload.id)? {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    conn.execute("DELETE FROM note_attachments WHERE note_id = ?1", [payload.id])
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    conn.execute("DELETE FROM note_mentions WHERE note_id = ?1", [payload.id])
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     conn.execute("DELETE FROM notes WHERE id = ?1", [payload.id])
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     Ok(StatusCode::OK)
 }
 
+// ATTACH UPLOAD: multipart with `noteId` and a `file` part (e.g. an inspection photo)
+async fn upload_attachment(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    mut multipart: Multipart,
+) -> Result<Json<NoteAttachment>, StatusCode> {
+    let mut note_id = None;
+    let mut file = None;
+    while let Some(field) = multipart.next_field().await.map_err(|err| err.status())? {
+        match field.name().unwrap_or("") {
+            "noteId" => {
+                let value = field.text().await.map_err(|err| err.status())?;
+                note_id = Some(value.parse::<i64>().map_err(|_| StatusCode::BAD_REQUEST)?);
+            }
+            "file" => {
+                let file_name = field.file_name().unwrap_or("attachment").to_string();
+                let mime_type = field.content_type().map(str::to_string);
+                let bytes = field.bytes().await.map_err(|err| err.status())?;
+                file = Some((file_name, mime_type, bytes.to_vec()));
+            }
+            _ => {}
+        }
+    }
+    let note_id = note_id.ok_or(StatusCode::BAD_REQUEST)?;
+    let (file_name, mime_type, bytes) = file.ok_or(StatusCode::BAD_REQUEST)?;
+
+    let conn = state
+        .conn
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !can_edit_note(&conn, &user, note_id)? {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let is_image = mime_type.as_deref().is_some_and(|m| m.starts_with("image/"));
+    let attachment = NoteAttachment {
+        id: uuid::Uuid::new_v4().to_string(),
+        note_id,
+        kind: if is_image { "photo" } else { "file" }.to_string(),
+        document_id: None,
+        file_name,
+        mime_type,
+        file_size: Some(bytes.len() as i64),
+        created_at: state.clock.now().to_rfc3339(),
+    };
+    conn.execute(
+        "INSERT INTO note_attachments (id, note_id, kind, file_name, mime_type, file_size, content, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        (
+            &attachment.id,
+            note_id,
+            &attachment.kind,
+            &attachment.file_name,
+            &attachment.mime_type,
+            attachment.file_size,
+            &bytes,
+            &attachment.created_at,
+        ),
+    )
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(attachment))
+}
+
+// ATTACH DOCUMENT: reference a file already uploaded through /api/documents
+async fn link_attachment(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Json(payload): Json<LinkAttachment>,
+) -> Result<Json<NoteAttachment>, StatusCode> {
+    let conn = state
+        .conn
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !can_edit_note(&conn, &user, payload.note_id)? {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let (attachment, strata_id) = conn
+        .query_row(
+            "SELECT d.file_name, d.mime_type, d.file_size, r.strata_id
+             FROM documents d LEFT JOIN service_requests r ON r.id = d.service_request_id
+             WHERE d.id = ?1",
+            [&payload.document_id],
+            |row| {
+                let attachment = NoteAttachment {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    note_id: payload.note_id,
+                    kind: "document".to_string(),
+                    document_id: Some(payload.document_id.clone()),
+                    file_name: row.get(0)?,
+                    mime_type: row.get(1)?,
+                    file_size: row.get(2)?,
+                    created_at: state.clock.now().to_rfc3339(),
+                };
+                Ok((attachment, row.get::<_, Option<String>>(3)?))
+            },
+        )
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if user.strata_scope().is_some_and(|scope| strata_id.as_ref() != Some(&scope)) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    conn.execute(
+        "INSERT INTO note_attachments (id, note_id, kind, document_id, file_name, mime_type, file_size, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        (
+            &attachment.id,
+            attachment.note_id,
+            &attachment.kind,
+            &attachment.document_id,
+            &attachment.file_name,
+            &attachment.mime_type,
+            attachment.file_size,
+            &attachment.created_at,
+        ),
+    )
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(attachment))
+}
+
+// DOWNLOAD: stored bytes, or the linked document's file
+async fn get_attachment(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = state
+        .conn
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let (note_id, mime_type, bytes) = conn
+        .query_row(
+            "SELECT a.note_id, a.mime_type, COALESCE(a.content, f.content)
+             FROM note_attachments a
+             LEFT JOIN document_files f ON f.document_id = a.document_id
+             WHERE a.id = ?1",
+            [&id],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<Vec<u8>>>(2)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if !can_view_note(&conn, &user, note_id)? {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let bytes = bytes.ok_or(StatusCode::NOT_FOUND)?;
+    let mime_type = mime_type.unwrap_or_else(|| "application/octet-stream".to_string());
+    Ok(([(header::CONTENT_TYPE, mime_type)], bytes))
+}
+
+// DETACH
+async fn delete_attachment(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Json(payload): Json<DeleteAttachment>,
+) -> Result<StatusCode, StatusCode> {
+    let conn = state
+        .conn
+        .lock()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let note_id: i64 = conn
+        .query_row("SELECT note_id FROM note_attachments WHERE id = ?1", [&payload.id], |row| row.get(0))
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if !can_edit_note(&conn, &user, note_id)? {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    conn.execute("DELETE FROM note_attachments WHERE id = ?1", [&payload.id])
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(StatusCode::OK)
+}
+
+// Site photos straight off a phone camera run well past axum's 2 MB default
+const MAX_ATTACHMENT_BYTES: usize = 25 * 1024 * 1024;
+
 pub fn router(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/notes", get(get_notes).post(create_note))
         .route("/notes/update", put(update_note))
         .route("/notes/pin", put(toggle_pin))
         .route("/notes/delete", delete(delete_note))
+        .route("/notes/attachments", post(upload_attachment).layer(DefaultBodyLimit::max(MAX_ATTACHMENT_BYTES)))
+        .route("/notes/attachments/link", post(link_attachment))
+        .route("/notes/attachments/delete", delete(delete_attachment))
+        .route("/notes/attachments/:id", get(get_attachment))
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_handlers::clock::SystemClock;
+    use crate::api_handlers::deadline_alerts::AlertThresholds;
+    use axum::body::Body;
+    use axum::http::Request;
+    use std::sync::Mutex;
+    use tower::util::ServiceExt;
+
+    fn notes_index() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE VIRTUAL TABLE notes_fts USING fts5(title, content, tags);
+             INSERT INTO notes_fts (rowid, title, content, tags) VALUES
+                 (1, 'Roof report', 'foo OR bar near the -x gutter', ''),
+                 (2, 'Parkade', 'membrane replacement', '');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn matching(conn: &Connection, query: &str) -> rusqlite::Result<Vec<i64>> {
+        let expression = search::build_match_expression(query).unwrap();
+        let mut stmt = conn.prepare("SELECT rowid FROM notes_fts WHERE notes_fts MATCH ?1 ORDER BY rowid")?;
+        let rows = stmt.query_map([expression], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    #[test]
+    fn operator_characters_are_searched_as_text() {
+        let conn = notes_index();
+        for query in ["foo OR", "NEAR(", "-x", "\"gutter", "title:roof", "AND", "foo*  bar^"] {
+            assert!(matching(&conn, query).is_ok(), "{}", query);
+        }
+        assert_eq!(matching(&conn, "foo OR").unwrap(), vec![1]);
+        assert_eq!(matching(&conn, "membr").unwrap(), vec![2]);
+    }
+
+    #[test]
+    fn blank_queries_do_not_search() {
+        assert_eq!(search::build_match_expression("   "), None);
+    }
+
+    // Just enough schema for an admin session to reach the attachment handler
+    fn attachment_app() -> Router {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE users (id TEXT PRIMARY KEY, name TEXT, email TEXT, role TEXT, strata_id TEXT,
+                                 position TEXT, phone TEXT, cell_phone TEXT, must_change_password INTEGER,
+                                 created_at TEXT);
+             CREATE TABLE sessions (token TEXT PRIMARY KEY, user_id TEXT, created_at TEXT, expires_at TEXT);
+             CREATE TABLE notes (id INTEGER PRIMARY KEY, owner_id TEXT);
+             INSERT INTO users VALUES ('admin', 'Admin', 'admin@srp.com', 'admin', NULL, NULL, NULL, NULL, 0, '');
+             INSERT INTO sessions VALUES ('token', 'admin', '', '9999-12-31');",
+        )
+        .unwrap();
+        router(Arc::new(AppState {
+            conn: Mutex::new(conn),
+            clock: Arc::new(SystemClock),
+            alert_thresholds: AlertThresholds::from_env(),
+        }))
+    }
+
+    async fn upload(app: Router, file_size: usize) -> StatusCode {
+        let mut body = b"--boundary\r\nContent-Disposition: form-data; name=\"noteId\"\r\n\r\n42\r\n".to_vec();
+        body.extend_from_slice(
+            b"--boundary\r\nContent-Disposition: form-data; name=\"file\"; filename=\"roof.jpg\"\r\n\
+              Content-Type: image/jpeg\r\n\r\n",
+        );
+        body.resize(body.len() + file_size, b'x');
+        body.extend_from_slice(b"\r\n--boundary--\r\n");
+        let request = Request::post("/notes/attachments")
+            .header(header::AUTHORIZATION, "Bearer token")
+            .header(header::CONTENT_TYPE, "multipart/form-data; boundary=boundary")
+            .body(Body::from(body))
+            .unwrap();
+        app.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn attachments_past_the_default_limit_are_accepted() {
+        // Note 42 does not exist, so getting that far means the body was read in full
+        assert_eq!(upload(attachment_app(), 5 * 1024 * 1024).await, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn oversized_attachments_are_rejected() {
+        assert_eq!(upload(attachment_app(), MAX_ATTACHMENT_BYTES + 1).await, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
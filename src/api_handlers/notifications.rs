@@ -0,0 +1,100 @@
+use axum::extract::{Json, State};
+use axum::http::StatusCode;
+use axum::routing::{get, put};
+use axum::Router;
+use crate::api_handlers::session::CurrentUser;
+use crate::db::AppState;
+use crate::models::Notification;
+use rusqlite::Connection;
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Deserialize)]
+pub struct MarkRead {
+    id: String,
+}
+
+pub fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(list_notifications))
+        .route("/read", put(mark_read))
+        .with_state(state)
+}
+
+// Drop an item into a user's inbox, stamped with the time of the change it
+// reports. Callers already hold the connection lock.
+pub fn notify(
+    conn: &Connection,
+    user_id: &str,
+    title: &str,
+    message: &str,
+    kind: &str,
+    link: Option<&str>,
+    created_at: &str,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO notifications (id, user_id, title, message, type, link, read, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 0, ?7)",
+        (
+            uuid::Uuid::new_v4().to_string(),
+            user_id,
+            title,
+            message,
+            kind,
+            link,
+            created_at,
+        ),
+    )?;
+    Ok(())
+}
+
+async fn list_notifications(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+) -> Result<Json<Vec<Notification>>, StatusCode> {
+    let conn = state.conn.lock().unwrap();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, user_id, title, message, type, link, read, created_at
+             FROM notifications WHERE user_id = ?1
+             ORDER BY created_at DESC",
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let notifications = stmt
+        .query_map([&user.0.id], |row| {
+            Ok(Notification {
+                id: row.get(0)?,
+                user_id: row.get(1)?,
+                title: row.get(2)?,
+                message: row.get(3)?,
+                kind: row.get(4)?,
+                link: row.get(5)?,
+                read: row.get::<_, i32>(6)? != 0,
+                created_at: row.get(7)?,
+            })
+        })
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(Json(notifications))
+}
+
+async fn mark_read(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Json(payload): Json<MarkRead>,
+) -> Result<StatusCode, StatusCode> {
+    let conn = state.conn.lock().unwrap();
+    let changed = conn
+        .execute(
+            "UPDATE notifications SET read = 1 WHERE id = ?1 AND user_id = ?2",
+            (&payload.id, &user.0.id),
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if changed == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(StatusCode::OK)
+}
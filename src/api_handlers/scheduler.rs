@@ -0,0 +1,768 @@
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{delete, get, post, put};
+use axum::{Json, Router};
+use chrono::{Duration, NaiveDate, NaiveTime};
+use crate::api_handlers::ecs_scheduler::{
+    self, AvailabilitySlot, Candidate, DayRoute, ExcludedInspectors, InspectionJob, Inspector, PinnedTo,
+    ScoreBreakdown, SchedulerWorld, UnassignedReason, DEFAULT_DAILY_HOURS, DEFAULT_SEED,
+};
+use crate::api_handlers::slots::{DEFAULT_BUFFER_MINUTES, DEFAULT_DURATION_HOURS};
+use crate::api_handlers::session::CurrentUser;
+use crate::api_handlers::{availability, clock, geocode, logistics};
+use crate::db::AppState;
+use crate::models::{Appointment, AppointmentStatus, InspectorProfile, OverrideKind, PropertyType, SchedulerOverride};
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const PLAN_HORIZON_DAYS: i64 = 14;
+const MAX_PLAN_RANGE_DAYS: i64 = 92;
+const DEFAULT_SKILL_LEVEL: u8 = 3;
+
+// Downtown office: home base for inspectors without one, and the stand-in
+// location for sites the geocoder cannot place
+const OFFICE_LOCATION: (f64, f64) = (49.2827, -123.1207);
+
+// Any of these means the request already has its inspection in hand
+const ACTIVE_INSPECTION: &str = "SELECT 1 FROM appointments a
+    WHERE a.service_request_id = r.id AND a.appointment_type = 'inspection'
+      AND a.status IN ('pending', 'offered', 'confirmed', 'completed')";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizeQuery {
+    from: Option<String>, // YYYY-MM-DD, inclusive; defaults to the next two weeks
+    to: Option<String>,
+    strategy: Option<String>, // greedy or local-search (default)
+    seed: Option<u64>,
+    iterations: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverrideQuery {
+    service_request_id: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposedAssignment {
+    service_request_id: String,
+    strata_plan: String,
+    inspector_id: String,
+    inspector_name: String,
+    date: String,
+    time: String,
+    sequence: usize,
+    score: f32,
+    pinned: bool,
+    breakdown: ScoreBreakdown,
+    runner_ups: Vec<Candidate>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnassignedJob {
+    service_request_id: String,
+    strata_plan: String,
+    reason: UnassignedReason,
+    detail: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchedulePreview {
+    strategy: String,
+    seed: u64,
+    from: String,
+    to: String,
+    assignments: Vec<ProposedAssignment>,
+    unassigned: Vec<UnassignedJob>,
+    routes: Vec<DayRoute>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitAssignment {
+    service_request_id: String,
+    inspector_id: String,
+    date: String,
+    time: String,
+}
+
+#[derive(Deserialize)]
+pub struct CommitRequest {
+    assignments: Vec<CommitAssignment>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectorProfileUpdate {
+    skill_level: u8,
+    home_address: Option<String>,
+    home_city: Option<String>,
+    home_postal_code: Option<String>,
+}
+
+pub fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/optimize", put(preview_schedule))
+        .route("/commit", post(commit_schedule))
+        .route("/inspectors", get(list_inspectors))
+        .route("/inspectors/:id", put(update_inspector))
+        .route("/overrides", get(list_overrides).post(create_override))
+        .route("/overrides/:id", delete(delete_override))
+        .with_state(state)
+}
+
+fn require_admin(user: &CurrentUser) -> Result<(), StatusCode> {
+    if user.is_admin() {
+        Ok(())
+    } else {
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+// Service request priority as entered by staff, on the scheduler's 1-5 scale
+fn priority_level(priority: Option<&str>) -> u8 {
+    match priority.map(|p| p.trim().to_lowercase()).as_deref() {
+        Some("urgent") => 5,
+        Some("high") => 4,
+        Some("low") => 2,
+        _ => 3,
+    }
+}
+
+// Larger buildings and mixed or industrial uses call for more experienced
+// inspectors; an unknown type is treated like a townhouse complex
+fn required_skill(property_type: Option<PropertyType>) -> u8 {
+    match property_type {
+        Some(PropertyType::MixedUse) | Some(PropertyType::Industrial) => 4,
+        Some(PropertyType::Apartment) => 3,
+        Some(PropertyType::BareLand) => 1,
+        Some(PropertyType::Townhouse) | None => 2,
+    }
+}
+
+fn load_profiles(conn: &Connection) -> rusqlite::Result<Vec<InspectorProfile>> {
+    conn.prepare(
+        "SELECT id, name, skill_level, home_address, home_city, home_postal_code
+         FROM users WHERE role = 'inspector' ORDER BY id",
+    )?
+    .query_map([], |row| {
+        Ok(InspectorProfile {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            skill_level: row.get::<_, Option<u8>>(2)?.unwrap_or(DEFAULT_SKILL_LEVEL),
+            home_address: row.get(3)?,
+            home_city: row.get(4)?,
+            home_postal_code: row.get(5)?,
+        })
+    })?
+    .collect()
+}
+
+// Service requests still waiting for an inspection to be arranged, with the
+// strata plan of each by request id
+fn load_jobs(conn: &Connection) -> rusqlite::Result<(Vec<InspectionJob>, HashMap<String, String>)> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT r.id, COALESCE(s.strata_plan, ''), r.priority, r.estimated_duration_hours, s.property_type,
+                s.address, s.city, s.postal_code
+         FROM service_requests r LEFT JOIN stratas s ON s.id = r.strata_id
+         WHERE r.status NOT IN ('Completed', 'Cancelled') AND NOT EXISTS ({ACTIVE_INSPECTION})
+         ORDER BY r.id"
+    ))?;
+    let rows = stmt.query_map([], |row| {
+        let location = geocode::geocode(
+            row.get::<_, Option<String>>(5)?.as_deref(),
+            row.get::<_, Option<String>>(6)?.as_deref(),
+            row.get::<_, Option<String>>(7)?.as_deref(),
+        );
+        let job = InspectionJob {
+            id: row.get(0)?,
+            location: location.unwrap_or(OFFICE_LOCATION),
+            priority: priority_level(row.get::<_, Option<String>>(2)?.as_deref()),
+            estimated_duration_hours: row.get::<_, Option<f64>>(3)?.unwrap_or(DEFAULT_DURATION_HOURS) as f32,
+            required_skill_level: required_skill(row.get::<_, Option<String>>(4)?.as_deref().and_then(PropertyType::parse)),
+        };
+        Ok((job, row.get::<_, String>(1)?))
+    })?;
+
+    let mut jobs = Vec::new();
+    let mut strata_plans = HashMap::new();
+    for row in rows {
+        let (job, strata_plan) = row?;
+        strata_plans.insert(job.id.clone(), strata_plan);
+        jobs.push(job);
+    }
+    Ok((jobs, strata_plans))
+}
+
+// Routes are planned after the last visit already offered or confirmed that
+// day, leaving the usual buffer; a day booked to the end is blocked.
+fn after_booked_visits(conn: &Connection, day: &mut AvailabilitySlot) -> rusqlite::Result<()> {
+    let (Some(start), Some(end)) = (day.start_time, day.end_time) else {
+        return Ok(());
+    };
+    let booked: Vec<(Option<String>, Option<f64>)> = conn
+        .prepare(
+            "SELECT a.confirmed_time, r.estimated_duration_hours
+             FROM appointments a LEFT JOIN service_requests r ON r.id = a.service_request_id
+             WHERE a.inspector_id = ?1 AND a.confirmed_date = ?2 AND a.status IN ('offered', 'confirmed')",
+        )?
+        .query_map((&day.inspector_id, &day.date), |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let mut free_from = start;
+    for (time, hours) in booked {
+        let Some(visit_start) = time.as_deref().and_then(clock::parse_local_time) else {
+            continue;
+        };
+        let minutes = (hours.unwrap_or(DEFAULT_DURATION_HOURS) * 60.0) as i64 + DEFAULT_BUFFER_MINUTES;
+        let (visit_end, overflow) = visit_start.overflowing_add_signed(Duration::minutes(minutes));
+        if overflow != 0 {
+            free_from = end;
+        } else {
+            free_from = free_from.max(visit_end);
+        }
+    }
+    if free_from >= end {
+        day.is_blocked = true;
+    }
+    day.start_time = Some(free_from);
+    Ok(())
+}
+
+// Inspectors, waiting jobs and open days in [from, to], spawned into a fresh world
+fn load_world(
+    conn: &Connection,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> rusqlite::Result<(SchedulerWorld, Vec<InspectorProfile>, HashMap<String, String>)> {
+    let mut ecs = SchedulerWorld::new();
+
+    let profiles = load_profiles(conn)?;
+    for profile in &profiles {
+        let home = geocode::geocode(
+            profile.home_address.as_deref(),
+            profile.home_city.as_deref(),
+            profile.home_postal_code.as_deref(),
+        );
+        ecs.world.spawn(Inspector {
+            id: profile.id.clone(),
+            name: profile.name.clone(),
+            current_location: home.unwrap_or(OFFICE_LOCATION),
+            skill_level: profile.skill_level,
+            daily_hours: DEFAULT_DAILY_HOURS,
+        });
+    }
+
+    let (jobs, strata_plans) = load_jobs(conn)?;
+    let overrides = load_overrides(conn, None)?;
+    for job in jobs {
+        let mine = || overrides.iter().filter(|o| o.service_request_id == job.id);
+        let pin = mine().find(|o| o.kind == OverrideKind::Pin).map(|o| PinnedTo {
+            inspector_id: o.inspector_id.clone(),
+            date: o.date.clone(),
+        });
+        let excluded: Vec<String> = mine()
+            .filter(|o| o.kind == OverrideKind::Exclude)
+            .map(|o| o.inspector_id.clone())
+            .collect();
+
+        let mut entity = ecs.world.spawn(job);
+        if let Some(pin) = pin {
+            entity.insert(pin);
+        }
+        if !excluded.is_empty() {
+            entity.insert(ExcludedInspectors(excluded));
+        }
+    }
+
+    for mut day in availability::inspector_days(conn, from, to)? {
+        if !day.is_blocked {
+            after_booked_visits(conn, &mut day)?;
+        }
+        ecs.world.spawn(day);
+    }
+
+    Ok((ecs, profiles, strata_plans))
+}
+
+// Runs the optimizer over real data without changing anything; admins review
+// the proposal and send back what they want through /commit.
+async fn preview_schedule(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Query(params): Query<OptimizeQuery>,
+) -> Result<Json<SchedulePreview>, StatusCode> {
+    require_admin(&user)?;
+    let parse = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").map_err(|_| StatusCode::BAD_REQUEST))
+            .transpose()
+    };
+    let today = state.clock.today_in(clock::DEFAULT_TIME_ZONE);
+    let from = parse(&params.from)?.unwrap_or(today + Duration::days(1));
+    let to = parse(&params.to)?.unwrap_or(from + Duration::days(PLAN_HORIZON_DAYS - 1));
+    if to < from || (to - from).num_days() > MAX_PLAN_RANGE_DAYS {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let strategy = ecs_scheduler::strategy_by_name(params.strategy.as_deref().unwrap_or("local-search"), params.iterations)
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    let strategy_name = strategy.name().to_string();
+    let seed = params.seed.unwrap_or(DEFAULT_SEED);
+
+    let (mut ecs, profiles, strata_plans) = {
+        let conn = state.conn.lock().unwrap();
+        load_world(&conn, from, to).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    };
+    // The search is CPU-bound; keep it off the runtime's only thread
+    let (assigned, unassigned, routes) = tokio::task::spawn_blocking(move || {
+        ecs.configure(strategy, seed);
+        ecs.run();
+        (ecs.get_assignments(), ecs.get_unassigned(), ecs.get_routes())
+    })
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let assignments = assigned
+        .into_iter()
+        .map(|(job, result)| ProposedAssignment {
+            strata_plan: strata_plans.get(&job.id).cloned().unwrap_or_default(),
+            inspector_name: profiles
+                .iter()
+                .find(|p| p.id == result.assigned_inspector_id)
+                .map(|p| p.name.clone())
+                .unwrap_or_default(),
+            service_request_id: job.id,
+            inspector_id: result.assigned_inspector_id,
+            date: result.date,
+            time: result.start_time,
+            sequence: result.sequence,
+            score: result.score,
+            pinned: result.pinned,
+            breakdown: result.breakdown,
+            runner_ups: result.runner_ups,
+        })
+        .collect();
+    let unassigned = unassigned
+        .into_iter()
+        .map(|(job, unassigned)| UnassignedJob {
+            strata_plan: strata_plans.get(&job.id).cloned().unwrap_or_default(),
+            service_request_id: job.id,
+            reason: unassigned.reason,
+            detail: unassigned.reason.describe(),
+        })
+        .collect();
+
+    Ok(Json(SchedulePreview {
+        strategy: strategy_name,
+        seed,
+        from: from.format("%Y-%m-%d").to_string(),
+        to: to.format("%Y-%m-%d").to_string(),
+        assignments,
+        unassigned,
+        routes,
+    }))
+}
+
+// Turns the accepted proposals into offered appointments for the clients to
+// accept. All or nothing: any request already arranged, unknown inspector or
+// clashing slot rolls the whole batch back.
+async fn commit_schedule(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Json(payload): Json<CommitRequest>,
+) -> Result<(StatusCode, Json<Vec<Appointment>>), StatusCode> {
+    require_admin(&user)?;
+    let mut conn = state.conn.lock().unwrap();
+    let tx = conn.transaction().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let mut created = Vec::new();
+    for assignment in payload.assignments {
+        NaiveDate::parse_from_str(&assignment.date, "%Y-%m-%d").map_err(|_| StatusCode::BAD_REQUEST)?;
+        NaiveTime::parse_from_str(&assignment.time, "%H:%M").map_err(|_| StatusCode::BAD_REQUEST)?;
+
+        let is_inspector: Option<i64> = tx
+            .query_row(
+                "SELECT 1 FROM users WHERE id = ?1 AND role = 'inspector'",
+                [&assignment.inspector_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        if is_inspector.is_none() {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        let (strata_plan, arranged): (String, bool) = tx
+            .query_row(
+                &format!(
+                    "SELECT COALESCE(s.strata_plan, ''), EXISTS ({ACTIVE_INSPECTION})
+                     FROM service_requests r LEFT JOIN stratas s ON s.id = r.strata_id WHERE r.id = ?1"
+                ),
+                [&assignment.service_request_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::NOT_FOUND)?;
+        if arranged {
+            return Err(StatusCode::CONFLICT);
+        }
+
+        let now = state.clock.now().to_rfc3339();
+        let mut appointment = Appointment {
+            id: uuid::Uuid::new_v4().to_string(),
+            service_request_id: assignment.service_request_id,
+            strata_plan,
+            appointment_type: "inspection".to_string(),
+            requested_date_1: assignment.date.clone(),
+            requested_time_1: assignment.time.clone(),
+            requested_date_2: None,
+            requested_time_2: None,
+            confirmed_date: Some(assignment.date.clone()),
+            confirmed_time: Some(assignment.time.clone()),
+            inspector_id: Some(assignment.inspector_id.clone()),
+            status: AppointmentStatus::Offered,
+            meeting_type: None,
+            notes: None,
+            cancellation_reason: None,
+            created_at: now.clone(),
+            updated_at: now,
+            starts_at: None,
+        };
+        logistics::ensure_slot_free(&tx, &appointment, &assignment.inspector_id, &assignment.date, &assignment.time)?;
+        logistics::insert_appointment(&tx, &mut appointment).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        logistics::record_event(&tx, &appointment, "offered", None, &user, Some("Scheduled by the optimizer".to_string()))
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        logistics::notify_client(&tx, &appointment, "Inspection time offered", "Please review and accept the offered inspection time.")?;
+        created.push(appointment);
+    }
+
+    tx.commit().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+// --- Inspector profiles ---
+
+async fn list_inspectors(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+) -> Result<Json<Vec<InspectorProfile>>, StatusCode> {
+    require_admin(&user)?;
+    let conn = state.conn.lock().unwrap();
+    load_profiles(&conn)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn update_inspector(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+    Json(payload): Json<InspectorProfileUpdate>,
+) -> Result<Json<InspectorProfile>, StatusCode> {
+    require_admin(&user)?;
+    if !(1..=5).contains(&payload.skill_level) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let conn = state.conn.lock().unwrap();
+    let changed = conn
+        .execute(
+            "UPDATE users SET skill_level = ?1, home_address = ?2, home_city = ?3, home_postal_code = ?4
+             WHERE id = ?5 AND role = 'inspector'",
+            (
+                payload.skill_level,
+                &payload.home_address,
+                &payload.home_city,
+                &payload.home_postal_code,
+                &id,
+            ),
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if changed == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    load_profiles(&conn)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .find(|profile| profile.id == id)
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+// --- Overrides ---
+
+fn load_overrides(conn: &Connection, service_request_id: Option<&str>) -> rusqlite::Result<Vec<SchedulerOverride>> {
+    conn.prepare(
+        "SELECT id, service_request_id, inspector_id, kind, date, reason, created_by, created_at
+         FROM scheduler_overrides
+         WHERE ?1 IS NULL OR service_request_id = ?1
+         ORDER BY service_request_id, kind, inspector_id",
+    )?
+    .query_map([service_request_id], |row| {
+        Ok(SchedulerOverride {
+            id: row.get(0)?,
+            service_request_id: row.get(1)?,
+            inspector_id: row.get(2)?,
+            kind: row.get(3)?,
+            date: row.get(4)?,
+            reason: row.get(5)?,
+            created_by: row.get(6)?,
+            created_at: row.get(7)?,
+        })
+    })?
+    .collect()
+}
+
+async fn list_overrides(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Query(params): Query<OverrideQuery>,
+) -> Result<Json<Vec<SchedulerOverride>>, StatusCode> {
+    require_admin(&user)?;
+    let conn = state.conn.lock().unwrap();
+    load_overrides(&conn, params.service_request_id.as_deref())
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+// A request has at most one pin, so a new pin replaces the old one. Pinning
+// an excluded inspector, or excluding the pinned one, is a 409.
+async fn create_override(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Json(payload): Json<SchedulerOverride>,
+) -> Result<(StatusCode, Json<SchedulerOverride>), StatusCode> {
+    require_admin(&user)?;
+    if payload.kind == OverrideKind::Exclude && payload.date.is_some() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if let Some(date) = &payload.date {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| StatusCode::BAD_REQUEST)?;
+    }
+
+    let mut conn = state.conn.lock().unwrap();
+    let request_exists: Option<i64> = conn
+        .query_row("SELECT 1 FROM service_requests WHERE id = ?1", [&payload.service_request_id], |row| row.get(0))
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let is_inspector: Option<i64> = conn
+        .query_row(
+            "SELECT 1 FROM users WHERE id = ?1 AND role = 'inspector'",
+            [&payload.inspector_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if request_exists.is_none() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    if is_inspector.is_none() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let existing = load_overrides(&conn, Some(&payload.service_request_id)).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let opposite = match payload.kind {
+        OverrideKind::Pin => OverrideKind::Exclude,
+        OverrideKind::Exclude => OverrideKind::Pin,
+    };
+    if existing.iter().any(|o| o.kind == opposite && o.inspector_id == payload.inspector_id) {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let item = SchedulerOverride {
+        id: uuid::Uuid::new_v4().to_string(),
+        created_by: Some(user.0.id.clone()),
+        created_at: state.clock.now().to_rfc3339(),
+        ..payload
+    };
+    // Dropping the old pin and adding the new one happen together or not at all
+    let tx = conn.transaction().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if item.kind == OverrideKind::Pin {
+        tx.execute(
+            "DELETE FROM scheduler_overrides WHERE service_request_id = ?1 AND kind = 'pin'",
+            [&item.service_request_id],
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+    tx.execute(
+        "INSERT OR REPLACE INTO scheduler_overrides (id, service_request_id, inspector_id, kind, date, reason, created_by, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        rusqlite::params![
+            &item.id,
+            &item.service_request_id,
+            &item.inspector_id,
+            &item.kind,
+            &item.date,
+            &item.reason,
+            &item.created_by,
+            &item.created_at,
+        ],
+    )
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    tx.commit().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok((StatusCode::CREATED, Json(item)))
+}
+
+async fn delete_override(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    require_admin(&user)?;
+    let conn = state.conn.lock().unwrap();
+    let changed = conn
+        .execute("DELETE FROM scheduler_overrides WHERE id = ?1", [&id])
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if changed == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::User;
+
+    fn seeded() -> Arc<AppState> {
+        let state = AppState::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        state
+            .conn
+            .lock()
+            .unwrap()
+            .execute_batch(
+                "INSERT INTO service_requests (id, strata_id, status, service_type, estimated_duration_hours, created_at)
+                 VALUES ('req-2', 'strata-1', 'New', 'Depreciation Report', 3.0, '2026-10-01T00:00:00Z'),
+                        ('req-3', 'strata-1', 'New', 'Depreciation Report', 3.0, '2026-10-01T00:00:00Z');",
+            )
+            .unwrap();
+        Arc::new(state)
+    }
+
+    fn admin() -> CurrentUser {
+        CurrentUser(User {
+            id: "user-admin-1".to_string(),
+            name: "Admin".to_string(),
+            email: "admin@srp.com".to_string(),
+            role: "admin".to_string(),
+            strata_id: None,
+            position: None,
+            phone: None,
+            cell_phone: None,
+            must_change_password: false,
+            created_at: String::new(),
+        })
+    }
+
+    // A confirmed visit for `service_request_id` at `time` on 2026-11-03
+    fn confirm_visit(conn: &Connection, service_request_id: &str, time: &str) {
+        let mut appointment = Appointment {
+            id: format!("visit-{}", service_request_id),
+            service_request_id: service_request_id.to_string(),
+            strata_plan: String::new(),
+            appointment_type: "inspection".to_string(),
+            requested_date_1: "2026-11-03".to_string(),
+            requested_time_1: time.to_string(),
+            requested_date_2: None,
+            requested_time_2: None,
+            confirmed_date: Some("2026-11-03".to_string()),
+            confirmed_time: Some(time.to_string()),
+            inspector_id: Some("user-inspector-1".to_string()),
+            status: AppointmentStatus::Confirmed,
+            meeting_type: None,
+            notes: None,
+            cancellation_reason: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+            starts_at: None,
+        };
+        logistics::insert_appointment(conn, &mut appointment).unwrap();
+    }
+
+    fn working_day() -> AvailabilitySlot {
+        AvailabilitySlot {
+            inspector_id: "user-inspector-1".to_string(),
+            date: "2026-11-03".to_string(),
+            is_blocked: false,
+            start_time: NaiveTime::from_hms_opt(9, 0, 0),
+            end_time: NaiveTime::from_hms_opt(17, 0, 0),
+        }
+    }
+
+    #[test]
+    fn priorities_map_onto_the_scheduler_scale() {
+        assert_eq!(priority_level(Some("Urgent")), 5);
+        assert_eq!(priority_level(Some(" high ")), 4);
+        assert_eq!(priority_level(Some("Low")), 2);
+        assert_eq!(priority_level(Some("normal")), 3);
+        assert_eq!(priority_level(None), 3);
+    }
+
+    #[test]
+    fn property_types_set_the_required_skill() {
+        assert_eq!(required_skill(Some(PropertyType::MixedUse)), 4);
+        assert_eq!(required_skill(Some(PropertyType::Industrial)), 4);
+        assert_eq!(required_skill(Some(PropertyType::Apartment)), 3);
+        assert_eq!(required_skill(Some(PropertyType::Townhouse)), 2);
+        assert_eq!(required_skill(Some(PropertyType::BareLand)), 1);
+        assert_eq!(required_skill(None), 2);
+    }
+
+    #[test]
+    fn booked_visits_push_the_day_start_back() {
+        let state = seeded();
+        let conn = state.conn.lock().unwrap();
+        let mut day = working_day();
+        after_booked_visits(&conn, &mut day).unwrap();
+        assert_eq!(day.start_time, NaiveTime::from_hms_opt(9, 0, 0));
+
+        confirm_visit(&conn, "req-3", "10:00");
+        let mut day = working_day();
+        after_booked_visits(&conn, &mut day).unwrap();
+        let expected = NaiveTime::from_hms_opt(13, 0, 0).unwrap() + Duration::minutes(DEFAULT_BUFFER_MINUTES);
+        assert_eq!(day.start_time, Some(expected));
+        assert!(!day.is_blocked);
+
+        confirm_visit(&conn, "req-2", "15:00");
+        let mut day = working_day();
+        after_booked_visits(&conn, &mut day).unwrap();
+        assert!(day.is_blocked);
+    }
+
+    #[tokio::test]
+    async fn a_clashing_slot_rolls_back_the_whole_batch() {
+        let state = seeded();
+        confirm_visit(&state.conn.lock().unwrap(), "req-3", "10:00");
+        let assignment = |service_request_id: &str, date: &str, time: &str| CommitAssignment {
+            service_request_id: service_request_id.to_string(),
+            inspector_id: "user-inspector-1".to_string(),
+            date: date.to_string(),
+            time: time.to_string(),
+        };
+        let payload = CommitRequest {
+            assignments: vec![
+                assignment("req-1", "2026-11-04", "09:00"),
+                assignment("req-2", "2026-11-03", "10:00"),
+            ],
+        };
+
+        let result = commit_schedule(State(state.clone()), admin(), Json(payload)).await;
+        assert_eq!(result.err(), Some(StatusCode::CONFLICT));
+        let conn = state.conn.lock().unwrap();
+        let count = |sql: &str| conn.query_row(sql, [], |row| row.get::<_, i64>(0)).unwrap();
+        assert_eq!(count("SELECT COUNT(*) FROM appointments"), 1);
+        assert_eq!(count("SELECT COUNT(*) FROM appointment_events"), 0);
+        assert_eq!(count("SELECT COUNT(*) FROM notifications WHERE title = 'Inspection time offered'"), 0);
+    }
+}
@@ -0,0 +1,143 @@
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use crate::api_handlers::session::CurrentUser;
+use crate::db::AppState;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+const SOURCE_TYPES: &[&str] = &["document", "note", "strata", "survey_answer"];
+const MAX_RESULTS: i64 = 50;
+
+// FTS highlight markers; swapped for <mark> after the text has been escaped
+const MARK_OPEN: &str = "\u{1}";
+const MARK_CLOSE: &str = "\u{2}";
+
+#[derive(Deserialize)]
+pub struct SearchParams {
+    q: Option<String>,
+    #[serde(rename = "type")]
+    source_type: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    source_type: String,
+    source_id: String,
+    strata_id: Option<String>,
+    title: String,   // HTML-escaped, matches wrapped in <mark>
+    snippet: String, // HTML-escaped, matches wrapped in <mark>
+    link: String,
+    rank: f64,
+}
+
+pub fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(search))
+        .with_state(state)
+}
+
+async fn search(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Query(params): Query<SearchParams>,
+) -> Result<Json<Vec<SearchHit>>, StatusCode> {
+    let Some(match_expr) = params.q.as_deref().and_then(build_match_expression) else {
+        return Ok(Json(vec![]));
+    };
+    if let Some(source_type) = &params.source_type {
+        if !SOURCE_TYPES.contains(&source_type.as_str()) {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    let conn = state.conn.lock().unwrap();
+    let mut stmt = conn
+        .prepare(
+            "SELECT source_type, source_id, strata_id,
+                    highlight(search_index, 0, ?4, ?5),
+                    snippet(search_index, 1, ?4, ?5, '…', 16),
+                    rank
+             FROM search_index
+             WHERE search_index MATCH ?1
+               AND (?2 IS NULL OR source_type = ?2)
+               AND CASE WHEN source_type = 'note'
+                        THEN (?7 OR owner_id = ?8
+                              OR source_id IN (SELECT CAST(note_id AS TEXT) FROM note_mentions WHERE user_id = ?8))
+                        ELSE (?3 IS NULL OR strata_id = ?3)
+                   END
+             ORDER BY rank
+             LIMIT ?6",
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let hits = stmt
+        .query_map(
+            (
+                &match_expr,
+                &params.source_type,
+                user.strata_scope(),
+                MARK_OPEN,
+                MARK_CLOSE,
+                MAX_RESULTS,
+                user.is_admin(),
+                &user.0.id,
+            ),
+            |row| {
+                let source_type: String = row.get(0)?;
+                let source_id: String = row.get(1)?;
+                Ok(SearchHit {
+                    link: link_for(&source_type, &source_id),
+                    source_type,
+                    source_id,
+                    strata_id: row.get(2)?,
+                    title: render_marks(&row.get::<_, String>(3)?),
+                    snippet: render_marks(&row.get::<_, String>(4)?),
+                    rank: row.get(5)?,
+                })
+            },
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(Json(hits))
+}
+
+// Quote every term so user input cannot inject FTS5 operators, and prefix-match
+// each one so results appear while the user is still typing.
+pub fn build_match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+fn link_for(source_type: &str, source_id: &str) -> String {
+    match source_type {
+        "strata" => format!("/admin/stratas/{}", source_id),
+        "document" => format!("/client/documents?documentId={}", source_id),
+        "note" => format!("/notes?id={}", source_id),
+        "survey_answer" => {
+            let request_id = source_id.split('/').next().unwrap_or(source_id);
+            format!("/admin/requests?id={}", request_id)
+        }
+        _ => "/".to_string(),
+    }
+}
+
+fn render_marks(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace(MARK_OPEN, "<mark>")
+        .replace(MARK_CLOSE, "</mark>")
+}
@@ -1,12 +1,11 @@
+use axum::extract::State;
 use crate::api_handlers::util::{ApiError, ApiResponse, Message};
+use crate::db::AppState;
+use std::sync::Arc;
 
-pub async fn get_time() -> Result<ApiResponse, ApiError> {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map(|duration| {
-            ApiResponse::JsonData(vec![Message {
-                message: duration.as_secs().to_string(),
-            }])
-        })
-        .map_err(|_| ApiError::InternalServerError)
+// Seconds since the epoch by the server's clock, so FIXED_NOW shows here too
+pub async fn get_time(State(state): State<Arc<AppState>>) -> Result<ApiResponse, ApiError> {
+    Ok(ApiResponse::JsonData(vec![Message {
+        message: state.clock.now().timestamp().to_string(),
+    }]))
 }
@@ -0,0 +1,101 @@
+use axum::async_trait;
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::header::AUTHORIZATION;
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use crate::db::AppState;
+use crate::models::User;
+use rusqlite::Connection;
+use std::sync::Arc;
+
+// Signed-in sessions last a working week before the user has to log in again
+const SESSION_TTL_DAYS: i64 = 7;
+
+// The user making the request, resolved from the bearer token issued at login
+pub struct CurrentUser(pub User);
+
+impl CurrentUser {
+    pub fn is_admin(&self) -> bool {
+        self.0.role == "admin"
+    }
+
+    pub fn is_inspector(&self) -> bool {
+        self.0.role == "inspector"
+    }
+
+    // Clients are confined to their own strata; staff roles see every strata
+    pub fn strata_scope(&self) -> Option<String> {
+        if self.0.role == "client" {
+            Some(self.0.strata_id.clone().unwrap_or_default())
+        } else {
+            None
+        }
+    }
+}
+
+// Start a session for a user who has just logged in and return its token
+pub fn create_session(conn: &Connection, user_id: &str, now: chrono::DateTime<chrono::Utc>) -> rusqlite::Result<String> {
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    let expires_at = now + chrono::Duration::days(SESSION_TTL_DAYS);
+    conn.execute(
+        "INSERT INTO sessions (token, user_id, created_at, expires_at) VALUES (?1, ?2, ?3, ?4)",
+        (&token, user_id, now.to_rfc3339(), expires_at.to_rfc3339()),
+    )?;
+    Ok(token)
+}
+
+pub fn end_session(conn: &Connection, token: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM sessions WHERE token = ?1", [token])?;
+    Ok(())
+}
+
+// The token from an `Authorization: Bearer <token>` header
+pub fn bearer_token(parts: &Parts) -> Option<&str> {
+    parts
+        .headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for CurrentUser
+where
+    Arc<AppState>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let token = bearer_token(parts).ok_or(StatusCode::UNAUTHORIZED)?;
+
+        let state = Arc::<AppState>::from_ref(state);
+        let now = state.clock.now().to_rfc3339();
+        let conn = state.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT u.id, u.name, u.email, u.role, u.strata_id, u.position, u.phone, u.cell_phone,
+                    u.must_change_password, u.created_at
+             FROM sessions s JOIN users u ON u.id = s.user_id
+             WHERE s.token = ?1 AND s.expires_at > ?2",
+            (token, now),
+            |row| {
+                Ok(User {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    email: row.get(2)?,
+                    role: row.get(3)?,
+                    strata_id: row.get(4)?,
+                    position: row.get(5)?,
+                    phone: row.get(6)?,
+                    cell_phone: row.get(7)?,
+                    must_change_password: row.get::<_, i32>(8)? != 0,
+                    created_at: row.get(9)?,
+                })
+            },
+        )
+        .map(CurrentUser)
+        .map_err(|_| StatusCode::UNAUTHORIZED)
+    }
+}
@@ -0,0 +1,281 @@
+use chrono::{Duration, NaiveDate, NaiveTime, Timelike};
+use crate::api_handlers::{availability, clock, geocode};
+use crate::models::{Appointment, LogisticsSlot};
+use rusqlite::{Connection, OptionalExtension};
+
+// Half-day visit when the service request has no estimate
+pub const DEFAULT_DURATION_HOURS: f64 = 4.0;
+pub const DEFAULT_BUFFER_MINUTES: i64 = 30;
+pub const DEFAULT_STEP_MINUTES: i64 = 60;
+pub const DRAFT_MEETING_MINUTES: i64 = 60;
+
+// Travel estimates for sites the geocoder cannot place: same postal area (first
+// three characters of the postal code), same city, elsewhere, or unknown.
+const TRAVEL_SAME_AREA_MINUTES: i64 = 15;
+const TRAVEL_SAME_CITY_MINUTES: i64 = 30;
+const TRAVEL_OTHER_CITY_MINUTES: i64 = 60;
+const TRAVEL_UNKNOWN_MINUTES: i64 = 30;
+
+#[derive(Debug, Clone)]
+pub struct SlotRules {
+    pub duration_minutes: i64,
+    pub buffer_minutes: i64, // kept clear after travel, before and after every visit
+    pub step_minutes: i64,   // spacing between candidate start times
+    pub include_travel: bool, // false for video meetings
+}
+
+impl Default for SlotRules {
+    fn default() -> Self {
+        Self {
+            duration_minutes: (DEFAULT_DURATION_HOURS * 60.0) as i64,
+            buffer_minutes: DEFAULT_BUFFER_MINUTES,
+            step_minutes: DEFAULT_STEP_MINUTES,
+            include_travel: true,
+        }
+    }
+}
+
+// Where a visit takes place, as far as the strata record tells us
+#[derive(Debug, Clone, Default)]
+pub struct Place {
+    pub address: Option<String>,
+    pub city: Option<String>,
+    pub postal_code: Option<String>,
+}
+
+// An already confirmed visit on the inspector's day
+#[derive(Debug, Clone)]
+pub struct Visit {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub place: Place,
+    pub remote: bool, // video meeting, no travel to or from it
+}
+
+fn minutes(time: NaiveTime) -> i64 {
+    (time.num_seconds_from_midnight() / 60) as i64
+}
+
+// How long an appointment keeps its inspector busy, and whether getting there counts
+pub fn appointment_rules(appointment_type: &str, meeting_type: Option<&str>, estimated_hours: Option<f64>) -> SlotRules {
+    if appointment_type == "draft_meeting" {
+        SlotRules {
+            duration_minutes: DRAFT_MEETING_MINUTES,
+            include_travel: meeting_type != Some("zoom"),
+            ..SlotRules::default()
+        }
+    } else {
+        SlotRules {
+            duration_minutes: (estimated_hours.unwrap_or(DEFAULT_DURATION_HOURS) * 60.0) as i64,
+            ..SlotRules::default()
+        }
+    }
+}
+
+// End of a visit starting at `start`; one running past midnight blocks the rest of the day
+fn visit_end(start: NaiveTime, duration_minutes: i64) -> NaiveTime {
+    let (end, overflow) = start.overflowing_add_signed(Duration::minutes(duration_minutes));
+    if overflow != 0 { NaiveTime::from_hms_opt(23, 59, 59).unwrap_or(end) } else { end }
+}
+
+// True when a visit from `start` to `end` at `place` leaves the buffer and the
+// travel time clear on both sides of every booked visit. Compared in minutes
+// since midnight so adding the gap cannot wrap.
+pub fn clears_visits(start: NaiveTime, end: NaiveTime, visits: &[Visit], rules: &SlotRules, place: &Place) -> bool {
+    visits.iter().all(|visit| {
+        let travel = if rules.include_travel && !visit.remote { estimate_travel_minutes(place, &visit.place) } else { 0 };
+        let gap = rules.buffer_minutes + travel;
+        // Entirely before the visit, or entirely after it
+        minutes(end) + gap <= minutes(visit.start) || minutes(visit.end) + gap <= minutes(start)
+    })
+}
+
+impl Place {
+    pub fn coordinates(&self) -> Option<(f64, f64)> {
+        geocode::geocode(self.address.as_deref(), self.city.as_deref(), self.postal_code.as_deref())
+    }
+}
+
+pub fn estimate_travel_minutes(a: &Place, b: &Place) -> i64 {
+    // Driving time between known coordinates, never less than a short hop
+    if let (Some(x), Some(y)) = (a.coordinates(), b.coordinates()) {
+        let minutes = (geocode::driving_hours(x, y) * 60.0).ceil() as i64;
+        return minutes.max(TRAVEL_SAME_AREA_MINUTES);
+    }
+
+    let area = |place: &Place| {
+        place
+            .postal_code
+            .as_ref()
+            .map(|code| code.replace(' ', "").to_uppercase())
+            .filter(|code| code.len() >= 3)
+            .map(|code| code[..3].to_string())
+    };
+    let city = |place: &Place| {
+        place
+            .city
+            .as_ref()
+            .map(|city| city.trim().to_lowercase())
+            .filter(|city| !city.is_empty())
+    };
+
+    match (area(a), area(b)) {
+        (Some(x), Some(y)) if x == y => return TRAVEL_SAME_AREA_MINUTES,
+        _ => {}
+    }
+    match (city(a), city(b)) {
+        (Some(x), Some(y)) if x == y => TRAVEL_SAME_CITY_MINUTES,
+        (Some(_), Some(_)) => TRAVEL_OTHER_CITY_MINUTES,
+        _ => TRAVEL_UNKNOWN_MINUTES,
+    }
+}
+
+// Start times within one work window that leave room for the visit, the buffer
+// and travel to and from every visit already booked that day. The first visit
+// of the day is not charged travel from the inspector's home.
+pub fn free_starts(
+    window_start: NaiveTime,
+    window_end: NaiveTime,
+    visits: &[Visit],
+    rules: &SlotRules,
+    place: &Place,
+) -> Vec<NaiveTime> {
+    let duration = Duration::minutes(rules.duration_minutes);
+    let step = Duration::minutes(rules.step_minutes.max(1));
+
+    let mut starts = Vec::new();
+    let mut start = window_start;
+    loop {
+        let (end, overflow) = start.overflowing_add_signed(duration);
+        if overflow != 0 || end > window_end {
+            break;
+        }
+
+        if clears_visits(start, end, visits, rules, place) {
+            starts.push(start);
+        }
+
+        let (next, overflow) = start.overflowing_add_signed(step);
+        if overflow != 0 {
+            break;
+        }
+        start = next;
+    }
+    starts
+}
+
+// Site and estimated duration of a service request
+pub fn request_details(conn: &Connection, service_request_id: &str) -> rusqlite::Result<Option<(Place, Option<f64>)>> {
+    conn.query_row(
+        "SELECT s.address, s.city, s.postal_code, r.estimated_duration_hours
+         FROM service_requests r LEFT JOIN stratas s ON s.id = r.strata_id
+         WHERE r.id = ?1",
+        [service_request_id],
+        |row| {
+            let place = Place {
+                address: row.get(0)?,
+                city: row.get(1)?,
+                postal_code: row.get(2)?,
+            };
+            Ok((place, row.get(3)?))
+        },
+    )
+    .optional()
+}
+
+// Confirmed visits on the inspector's day, other than the appointment being moved
+fn confirmed_visits(conn: &Connection, inspector_id: &str, date: &str, exclude_id: Option<&str>) -> rusqlite::Result<Vec<Visit>> {
+    let mut stmt = conn.prepare(
+        "SELECT a.confirmed_time, a.appointment_type, a.meeting_type, r.estimated_duration_hours,
+                s.address, s.city, s.postal_code
+         FROM appointments a
+         LEFT JOIN service_requests r ON r.id = a.service_request_id
+         LEFT JOIN stratas s ON s.id = r.strata_id
+         WHERE a.inspector_id = ?1 AND a.status = 'confirmed' AND a.confirmed_date = ?2
+           AND (?3 IS NULL OR a.id != ?3)",
+    )?;
+    let rows = stmt.query_map((inspector_id, date, exclude_id), |row| {
+        Ok((
+            row.get::<_, Option<String>>(0)?,
+            appointment_rules(
+                &row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?.as_deref(),
+                row.get::<_, Option<f64>>(3)?,
+            ),
+            Place {
+                address: row.get(4)?,
+                city: row.get(5)?,
+                postal_code: row.get(6)?,
+            },
+        ))
+    })?;
+
+    let mut visits = Vec::new();
+    for row in rows {
+        let (time, rules, place) = row?;
+        let Some(start) = time.as_deref().and_then(clock::parse_local_time) else {
+            continue;
+        };
+        visits.push(Visit {
+            start,
+            end: visit_end(start, rules.duration_minutes),
+            place,
+            remote: !rules.include_travel,
+        });
+    }
+    Ok(visits)
+}
+
+// Whether the inspector can take this appointment at `date` `start`: the same
+// test the slot generator applies, against every other confirmed visit that day.
+pub fn slot_is_free(
+    conn: &Connection,
+    inspector_id: &str,
+    appointment: &Appointment,
+    date: &str,
+    start: NaiveTime,
+) -> rusqlite::Result<bool> {
+    let (place, hours) = request_details(conn, &appointment.service_request_id)?.unwrap_or_default();
+    let rules = appointment_rules(&appointment.appointment_type, appointment.meeting_type.as_deref(), hours);
+    let place = if rules.include_travel { place } else { Place::default() };
+
+    let visits = confirmed_visits(conn, inspector_id, date, Some(&appointment.id))?;
+    Ok(clears_visits(start, visit_end(start, rules.duration_minutes), &visits, &rules, &place))
+}
+
+// Bookable slots in [from, to] across every inspector who is not blocked that
+// day. Slots starting at the same time are merged, listing each free inspector.
+pub fn generate(
+    conn: &Connection,
+    from: NaiveDate,
+    to: NaiveDate,
+    rules: &SlotRules,
+    place: &Place,
+) -> rusqlite::Result<Vec<LogisticsSlot>> {
+    let mut slots: Vec<LogisticsSlot> = Vec::new();
+
+    for day in availability::inspector_days(conn, from, to)?.into_iter().filter(|day| !day.is_blocked) {
+        let (Some(window_start), Some(window_end)) = (day.start_time, day.end_time) else {
+            continue;
+        };
+
+        let visits = confirmed_visits(conn, &day.inspector_id, &day.date, None)?;
+        for start in free_starts(window_start, window_end, &visits, rules, place) {
+            let id = format!("{}-{}", day.date, start.format("%H%M"));
+            match slots.iter_mut().find(|slot| slot.id == id) {
+                Some(slot) => slot.inspector_ids.push(day.inspector_id.clone()),
+                None => slots.push(LogisticsSlot {
+                    date: day.date.clone(),
+                    time: start.format("%-I:%M %p").to_string(),
+                    id,
+                    start_time: start.format("%H:%M").to_string(),
+                    end_time: (start + Duration::minutes(rules.duration_minutes)).format("%H:%M").to_string(),
+                    inspector_ids: vec![day.inspector_id.clone()],
+                }),
+            }
+        }
+    }
+
+    slots.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(slots)
+}
@@ -1,16 +1,65 @@
 use axum::extract::{State, Json, Path};
-use axum::routing::{get, post};
+use axum::routing::{get, post, put};
 use axum::Router;
 use axum::http::StatusCode;
-use crate::models::{Strata, ServiceRequest};
+use crate::api_handlers::clock;
+use crate::api_handlers::logistics::{self, APPOINTMENT_COLUMNS};
+use crate::api_handlers::milestones;
+use crate::api_handlers::session::CurrentUser;
+use crate::api_handlers::timeline_engine::ReportScope;
+use crate::models::{Appointment, Strata, ServiceRequest};
 use crate::db::AppState;
+use serde::{Deserialize, Deserializer};
 use std::sync::Arc;
 
+// Dates that gate the draft review meeting
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DraftDates {
+    agm_date: Option<String>, // most recent AGM
+    draft_sent_date: Option<String>,
+}
+
+// Inputs to the timeline engine; omitted fields keep their stored value and
+// an explicit null clears it
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineInputs {
+    #[serde(default, deserialize_with = "present")]
+    file_opened_date: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    fiscal_year_start_month: Option<Option<u8>>,
+    #[serde(default, deserialize_with = "present")]
+    last_depreciation_report_date: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    target_date: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    report_scope: Option<Option<String>>,
+}
+
+// Some(None) for a field sent as null; absent fields stay None through `default`
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeZoneUpdate {
+    time_zone: String, // IANA name, e.g. America/Edmonton
+}
+
 pub fn router(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/", get(list_stratas))
         .route("/requests", get(list_requests))
+        .route("/requests/:id/draft", put(update_draft_dates))
+        .route("/requests/:id/timeline", put(update_timeline_inputs))
         .route("/:id", get(get_strata))
+        .route("/:id/time-zone", put(update_time_zone))
         .route("/update", post(update_strata))
         .with_state(state)
 }
@@ -20,7 +69,7 @@ async fn list_stratas(
 ) -> Result<Json<Vec<Strata>>, StatusCode> {
     let conn = state.conn.lock().unwrap();
     let mut stmt = conn
-        .prepare("SELECT id, strata_plan, complex_name, address, city, province, postal_code, country, property_type, strata_manager_id, property_manager_id, created_at FROM stratas")
+        .prepare("SELECT id, strata_plan, complex_name, address, city, province, postal_code, country, property_type, strata_manager_id, property_manager_id, created_at, time_zone FROM stratas")
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     let strata_iter = stmt
@@ -39,6 +88,7 @@ async fn list_stratas(
                 company_id: "company-1".to_string(), // Default for now
                 property_manager_id: row.get(10)?,
                 user_ids: vec![], // For simplicity, handled elsewhere if needed
+                time_zone: row.get(12)?,
                 created_at: row.get(11)?,
             })
         })
@@ -56,7 +106,11 @@ async fn list_requests(
 ) -> Result<Json<Vec<ServiceRequest>>, StatusCode> {
     let conn = state.conn.lock().unwrap();
     let mut stmt = conn
-        .prepare("SELECT id, strata_id, status, progress, service_type, created_at FROM service_requests")
+        .prepare(
+            "SELECT id, strata_id, status, progress, service_type, created_at, agm_date, draft_sent_date,
+                    file_opened_date, fiscal_year_start_month, last_depreciation_report_date, target_date, report_scope
+             FROM service_requests",
+        )
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     let request_iter = stmt
@@ -71,14 +125,14 @@ async fn list_requests(
                 status: row.get(2)?,
                 progress: row.get(3)?,
                 request_date: row.get(5)?, 
-                file_opened_date: None,
-                fiscal_year_start_month: None,
-                agm_date: None,
-                last_depreciation_report_date: None,
-                target_date: None,
-                report_scope: None,
+                file_opened_date: row.get(8)?,
+                fiscal_year_start_month: row.get(9)?,
+                agm_date: row.get(6)?,
+                last_depreciation_report_date: row.get(10)?,
+                target_date: row.get(11)?,
+                report_scope: row.get(12)?,
                 draft_deadline: None,
-                draft_sent_date: None,
+                draft_sent_date: row.get(7)?,
                 created_at: row.get(5)?,
                 updated_at: row.get(5)?,
             })
@@ -91,13 +145,114 @@ async fn list_requests(
 
     Ok(Json(requests))
 }
+
+// ADMIN: record the last AGM and when the draft report went to the council
+async fn update_draft_dates(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+    Json(payload): Json<DraftDates>,
+) -> Result<StatusCode, StatusCode> {
+    if !user.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    for date in [&payload.agm_date, &payload.draft_sent_date].into_iter().flatten() {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| StatusCode::BAD_REQUEST)?;
+    }
+
+    let mut conn = state.conn.lock().unwrap();
+    let tx = conn.transaction().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let changed = tx
+        .execute(
+            "UPDATE service_requests
+             SET agm_date = COALESCE(?1, agm_date), draft_sent_date = COALESCE(?2, draft_sent_date)
+             WHERE id = ?3",
+            (&payload.agm_date, &payload.draft_sent_date, &id),
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if changed == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    milestones::refresh_request(&tx, &id, state.clock.as_ref()).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    tx.commit().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(StatusCode::OK)
+}
+
+// ADMIN: fiscal year, prior report and scope used to project the request's timeline
+async fn update_timeline_inputs(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+    Json(payload): Json<TimelineInputs>,
+) -> Result<StatusCode, StatusCode> {
+    if !user.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let dates = [&payload.file_opened_date, &payload.last_depreciation_report_date, &payload.target_date];
+    for date in dates.into_iter().flatten().flatten() {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| StatusCode::BAD_REQUEST)?;
+    }
+    if payload.fiscal_year_start_month.flatten().is_some_and(|month| !(1..=12).contains(&month)) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if payload.report_scope.as_ref().and_then(Option::as_deref).is_some_and(|scope| ReportScope::parse(scope).is_none()) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // Only the fields sent are written
+    use rusqlite::types::Value;
+    let text = |value: Option<String>| value.map(Value::Text).unwrap_or(Value::Null);
+    let mut columns = Vec::new();
+    let mut values = Vec::new();
+    if let Some(value) = payload.file_opened_date {
+        columns.push("file_opened_date");
+        values.push(text(value));
+    }
+    if let Some(value) = payload.fiscal_year_start_month {
+        columns.push("fiscal_year_start_month");
+        values.push(value.map(|month| Value::Integer(month.into())).unwrap_or(Value::Null));
+    }
+    if let Some(value) = payload.last_depreciation_report_date {
+        columns.push("last_depreciation_report_date");
+        values.push(text(value));
+    }
+    if let Some(value) = payload.target_date {
+        columns.push("target_date");
+        values.push(text(value));
+    }
+    if let Some(value) = payload.report_scope {
+        columns.push("report_scope");
+        values.push(text(value));
+    }
+    let assignments: Vec<String> = columns.iter().map(|column| format!("{column} = ?")).collect();
+    values.push(Value::Text(id.clone()));
+
+    let mut conn = state.conn.lock().unwrap();
+    let tx = conn.transaction().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // With nothing to set, the UPDATE still tells us whether the request exists
+    let sql = if assignments.is_empty() {
+        "UPDATE service_requests SET id = id WHERE id = ?".to_string()
+    } else {
+        format!("UPDATE service_requests SET {} WHERE id = ?", assignments.join(", "))
+    };
+    let changed = tx
+        .execute(&sql, rusqlite::params_from_iter(values))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if changed == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    milestones::refresh_request(&tx, &id, state.clock.as_ref()).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    tx.commit().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(StatusCode::OK)
+}
+
 async fn get_strata(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
 ) -> Result<Json<Strata>, StatusCode> {
     let conn = state.conn.lock().unwrap();
     let strata_res: Result<Strata, _> = conn.query_row(
-        "SELECT id, strata_plan, complex_name, address, city, province, postal_code, country, property_type, strata_manager_id, property_manager_id, created_at FROM stratas WHERE id = ?",
+        "SELECT id, strata_plan, complex_name, address, city, province, postal_code, country, property_type, strata_manager_id, property_manager_id, created_at, time_zone FROM stratas WHERE id = ?",
         [id],
         |row| {
             Ok(Strata {
@@ -114,6 +269,7 @@ async fn get_strata(
                 company_id: "company-1".to_string(),
                 property_manager_id: row.get(10)?,
                 user_ids: vec![],
+                time_zone: row.get(12)?,
                 created_at: row.get(11)?,
             })
         },
@@ -125,6 +281,54 @@ async fn get_strata(
     }
 }
 
+// ADMIN: appointments keep their agreed local date and time, so the instants
+// they refer to are re-derived in the new zone
+async fn update_time_zone(
+    State(state): State<Arc<AppState>>,
+    user: CurrentUser,
+    Path(id): Path<String>,
+    Json(payload): Json<TimeZoneUpdate>,
+) -> Result<StatusCode, StatusCode> {
+    if !user.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let tz = clock::parse_time_zone(&payload.time_zone).ok_or(StatusCode::BAD_REQUEST)?;
+
+    let mut conn = state.conn.lock().unwrap();
+    let tx = conn.transaction().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let changed = tx
+        .execute("UPDATE stratas SET time_zone = ?1 WHERE id = ?2", (tz.name(), &id))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if changed == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let appointments: Vec<Appointment> = {
+        let mut stmt = tx
+            .prepare(&format!(
+                "SELECT {APPOINTMENT_COLUMNS} FROM appointments
+                 WHERE service_request_id IN (SELECT id FROM service_requests WHERE strata_id = ?1)"
+            ))
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let rows = stmt
+            .query_map([&id], logistics::appointment_from_row)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        rows.collect::<rusqlite::Result<_>>().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    };
+    for mut appointment in appointments {
+        logistics::localize(&tx, &mut appointment).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        tx.execute(
+            "UPDATE appointments SET starts_at = ?1 WHERE id = ?2",
+            (&appointment.starts_at, &appointment.id),
+        )
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+    // Whether a deadline has passed depends on the date at the site
+    milestones::refresh(&tx, Some(&id), state.clock.as_ref()).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    tx.commit().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(StatusCode::OK)
+}
+
 async fn update_strata(
     Json(payload): Json<Strata>,
 ) -> Json<Strata> {
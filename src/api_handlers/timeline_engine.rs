@@ -0,0 +1,273 @@
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use crate::api_handlers::business_calendar::BusinessCalendar;
+use serde::Serialize;
+use std::fmt;
+
+// The draft report goes to the council this many days before the AGM
+pub const DRAFT_LEAD_DAYS: i64 = 30;
+
+// The AGM notice, with the reviewed report, goes out at least two weeks before
+// the meeting, so the council's review of the draft has to be done by then
+pub const AGM_NOTICE_DAYS: i64 = 14;
+
+// Strata Property Act s. 40(2): the AGM is held no later than two months
+// after the strata corporation's fiscal year end
+pub const AGM_WINDOW_MONTHS: u32 = 2;
+
+// Depreciation reports are renewed every five years (Strata Property
+// Regulation 6.2, as amended in 2024; three years before that)
+pub const DEPRECIATION_RENEWAL_YEARS: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum ReportScope {
+    ThisFiscal,
+    NextFiscal,
+}
+
+impl ReportScope {
+    // "This Fiscal" or "Next Fiscal", in any case
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "this fiscal" => Some(ReportScope::ThisFiscal),
+            "next fiscal" => Some(ReportScope::NextFiscal),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "error", rename_all = "snake_case")]
+pub enum TimelineError {
+    InvalidDate { field: &'static str, value: String },
+    InvalidFiscalMonth { month: u8 },
+    UnknownReportScope { value: String },
+    TargetOutsideAgmWindow { target: NaiveDate, window_start: NaiveDate, window_end: NaiveDate },
+    OutOfRange { field: &'static str },
+}
+
+impl fmt::Display for TimelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimelineError::InvalidDate { field, value } => write!(f, "{field} is not a YYYY-MM-DD date: {value:?}"),
+            TimelineError::InvalidFiscalMonth { month } => write!(f, "fiscal year start month must be 1-12, got {month}"),
+            TimelineError::UnknownReportScope { value } => {
+                write!(f, "report scope must be \"This Fiscal\" or \"Next Fiscal\", got {value:?}")
+            }
+            TimelineError::TargetOutsideAgmWindow { target, window_start, window_end } => write!(
+                f,
+                "target AGM date {target} is outside the statutory window {window_start} to {window_end}"
+            ),
+            TimelineError::OutOfRange { field } => write!(f, "{field} is out of the supported date range"),
+        }
+    }
+}
+
+// Dates are optional where the form allows leaving them blank
+#[derive(Debug, Clone)]
+pub struct TimelineInput {
+    pub file_opened: NaiveDate,
+    pub fiscal_year_start_month: u8,
+    pub last_agm: Option<NaiveDate>,
+    pub last_depr_report: Option<NaiveDate>,
+    pub target_agm: Option<NaiveDate>,
+    pub report_scope: ReportScope,
+    pub today: NaiveDate,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeline {
+    pub fiscal_year_start: NaiveDate, // fiscal year containing today
+    pub fiscal_year_end: NaiveDate,
+    pub report_fiscal_year_end: NaiveDate, // fiscal year the report is prepared for
+    pub agm_window_start: NaiveDate,
+    pub agm_window_end: NaiveDate,
+    pub next_projected_agm: NaiveDate,
+    pub draft_deadline: NaiveDate,
+    pub review_deadline: NaiveDate, // last day to meet with the council about the draft
+    pub depreciation_report_due: Option<NaiveDate>,
+    pub depreciation_report_overdue: bool,
+    pub days_into_fiscal: i64,
+    pub days_remaining_in_fiscal: i64,
+    pub days_since_agm: Option<i64>,
+    pub days_since_file_opened: i64,
+}
+
+pub fn parse_date(field: &'static str, value: &str) -> Result<NaiveDate, TimelineError> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| TimelineError::InvalidDate {
+        field,
+        value: value.to_string(),
+    })
+}
+
+// Blank means not provided; anything else has to be a real date
+pub fn parse_optional_date(field: &'static str, value: Option<&str>) -> Result<Option<NaiveDate>, TimelineError> {
+    match value.map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(value) => parse_date(field, value).map(Some),
+    }
+}
+
+fn add_months(field: &'static str, date: NaiveDate, months: u32) -> Result<NaiveDate, TimelineError> {
+    date.checked_add_months(Months::new(months))
+        .ok_or(TimelineError::OutOfRange { field })
+}
+
+// First day of the fiscal year that contains `date`
+fn fiscal_year_start(date: NaiveDate, start_month: u32) -> Result<NaiveDate, TimelineError> {
+    let year = if date.month() >= start_month { date.year() } else { date.year() - 1 };
+    NaiveDate::from_ymd_opt(year, start_month, 1).ok_or(TimelineError::OutOfRange { field: "today" })
+}
+
+// Deadlines that land on a weekend, holiday or closure move back to the
+// previous working day of `calendar`
+pub fn compute(input: &TimelineInput, calendar: &BusinessCalendar) -> Result<Timeline, TimelineError> {
+    if !(1..=12).contains(&input.fiscal_year_start_month) {
+        return Err(TimelineError::InvalidFiscalMonth { month: input.fiscal_year_start_month });
+    }
+
+    let fiscal_start = fiscal_year_start(input.today, input.fiscal_year_start_month as u32)?;
+    let fiscal_end = add_months("today", fiscal_start, 12)? - Duration::days(1);
+    let report_fiscal_year_end = match input.report_scope {
+        ReportScope::ThisFiscal => fiscal_end,
+        ReportScope::NextFiscal => add_months("today", fiscal_start, 24)? - Duration::days(1),
+    };
+
+    // Adding months to the day after year end keeps month ends aligned
+    let agm_window_start = report_fiscal_year_end + Duration::days(1);
+    let agm_window_end = add_months("today", agm_window_start, AGM_WINDOW_MONTHS)? - Duration::days(1);
+
+    let next_projected_agm = match (input.target_agm, input.last_agm) {
+        (Some(target), _) => {
+            if target < agm_window_start || target > agm_window_end {
+                return Err(TimelineError::TargetOutsideAgmWindow {
+                    target,
+                    window_start: agm_window_start,
+                    window_end: agm_window_end,
+                });
+            }
+            target
+        }
+        // The anniversary of the last AGM, moved into the statutory window
+        (None, Some(last_agm)) => {
+            let mut anniversary = last_agm;
+            while anniversary < agm_window_start {
+                anniversary = add_months("last_agm_date", anniversary, 12)?;
+            }
+            anniversary.clamp(agm_window_start, agm_window_end)
+        }
+        // No history: plan for the latest date the Act allows
+        (None, None) => agm_window_end,
+    };
+
+    let depreciation_report_due = input
+        .last_depr_report
+        .map(|last| add_months("last_depr_report", last, 12 * DEPRECIATION_RENEWAL_YEARS))
+        .transpose()?
+        .map(|due| calendar.on_or_before(due));
+
+    Ok(Timeline {
+        fiscal_year_start: fiscal_start,
+        fiscal_year_end: fiscal_end,
+        report_fiscal_year_end,
+        agm_window_start,
+        agm_window_end,
+        next_projected_agm,
+        draft_deadline: calendar.on_or_before(next_projected_agm - Duration::days(DRAFT_LEAD_DAYS)),
+        review_deadline: calendar.on_or_before(next_projected_agm - Duration::days(AGM_NOTICE_DAYS)),
+        depreciation_report_due,
+        depreciation_report_overdue: depreciation_report_due.is_some_and(|due| due < input.today),
+        days_into_fiscal: (input.today - fiscal_start).num_days(),
+        days_remaining_in_fiscal: (fiscal_end - input.today).num_days(),
+        days_since_agm: input
+            .last_agm
+            .filter(|last| *last <= input.today)
+            .map(|last| (input.today - last).num_days()),
+        days_since_file_opened: (input.today - input.file_opened).num_days(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn input(fiscal_year_start_month: u8, today: &str) -> TimelineInput {
+        TimelineInput {
+            file_opened: date("2026-01-05"),
+            fiscal_year_start_month,
+            last_agm: None,
+            last_depr_report: None,
+            target_agm: None,
+            report_scope: ReportScope::ThisFiscal,
+            today: date(today),
+        }
+    }
+
+    fn calendar() -> BusinessCalendar {
+        BusinessCalendar::new(Some("BC"), Vec::new())
+    }
+
+    #[test]
+    fn agm_window_follows_fiscal_year_end() {
+        let timeline = compute(&input(4, "2026-10-19"), &calendar()).unwrap();
+        assert_eq!(timeline.fiscal_year_end, date("2027-03-31"));
+        assert_eq!(timeline.agm_window_start, date("2027-04-01"));
+        assert_eq!(timeline.agm_window_end, date("2027-05-31"));
+        // No AGM history: the latest date the Act allows
+        assert_eq!(timeline.next_projected_agm, date("2027-05-31"));
+    }
+
+    #[test]
+    fn next_fiscal_scope_moves_the_window_a_year() {
+        let mut next = input(4, "2026-10-19");
+        next.report_scope = ReportScope::NextFiscal;
+        let timeline = compute(&next, &calendar()).unwrap();
+        assert_eq!(timeline.report_fiscal_year_end, date("2028-03-31"));
+        assert_eq!(timeline.agm_window_start, date("2028-04-01"));
+        assert_eq!(timeline.agm_window_end, date("2028-05-31"));
+    }
+
+    #[test]
+    fn february_year_end_keeps_month_ends_aligned() {
+        // Fiscal year March 2027 to February 2028, a leap year
+        let timeline = compute(&input(3, "2027-06-01"), &calendar()).unwrap();
+        assert_eq!(timeline.fiscal_year_end, date("2028-02-29"));
+        assert_eq!(timeline.agm_window_end, date("2028-04-30"));
+    }
+
+    #[test]
+    fn draft_and_review_deadlines_count_back_from_the_agm() {
+        let mut target = input(4, "2026-10-19");
+        target.target_agm = Some(date("2027-05-20"));
+        let timeline = compute(&target, &calendar()).unwrap();
+        assert_eq!(timeline.draft_deadline, date("2027-05-20") - Duration::days(DRAFT_LEAD_DAYS));
+        assert_eq!(timeline.review_deadline, date("2027-05-20") - Duration::days(AGM_NOTICE_DAYS));
+
+        // 30 days before Monday 2027-05-17 is a Saturday; the deadline moves to Friday
+        target.target_agm = Some(date("2027-05-17"));
+        let timeline = compute(&target, &calendar()).unwrap();
+        assert_eq!(timeline.draft_deadline, date("2027-04-16"));
+    }
+
+    #[test]
+    fn target_outside_the_window_is_rejected() {
+        let mut target = input(4, "2026-10-19");
+        target.target_agm = Some(date("2027-06-15"));
+        assert!(matches!(
+            compute(&target, &calendar()),
+            Err(TimelineError::TargetOutsideAgmWindow { .. })
+        ));
+    }
+
+    #[test]
+    fn report_scope_parse_ignores_case_and_spacing() {
+        assert_eq!(ReportScope::parse("This Fiscal"), Some(ReportScope::ThisFiscal));
+        assert_eq!(ReportScope::parse("next fiscal"), Some(ReportScope::NextFiscal));
+        assert_eq!(ReportScope::parse("  NEXT FISCAL "), Some(ReportScope::NextFiscal));
+        assert_eq!(ReportScope::parse("Last Fiscal"), None);
+        assert_eq!(ReportScope::parse(""), None);
+    }
+}
@@ -1,87 +1,74 @@
-use bevy_ecs::prelude::*;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
 use axum::Json;
 use axum::routing::post;
 use axum::Router;
-use chrono::{NaiveDate, Datelike, Duration};
+use chrono::NaiveDate;
+use crate::api_handlers::business_calendar::{BusinessCalendar, COMPANY_PROVINCE};
+use crate::api_handlers::clock;
+use crate::api_handlers::timeline_engine::{self, ReportScope, TimelineError, TimelineInput};
+use crate::db::AppState;
 use crate::models::{TimelineRequest, TimelineResponse};
+use std::sync::Arc;
 
-// Components
-#[derive(Component, Debug, Clone)]
-struct ProjectDates {
-    file_opened: NaiveDate,
-    last_agm: NaiveDate,
-    today: NaiveDate,
+pub fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/calculate-timeline", post(calculate_timeline))
+        .with_state(state)
 }
 
-#[derive(Component, Debug, Clone)]
-struct StrataRules {
-    fiscal_start_month: u32,
+// 422 with the error kind, its details and a readable message
+impl IntoResponse for TimelineError {
+    fn into_response(self) -> Response {
+        let mut body = serde_json::to_value(&self).unwrap_or_default();
+        if let Some(fields) = body.as_object_mut() {
+            fields.insert("message".to_string(), self.to_string().into());
+        }
+        (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response()
+    }
 }
 
-pub fn router() -> Router {
-    Router::new()
-        .route("/calculate-timeline", post(calculate_timeline))
+fn format(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
 }
 
 async fn calculate_timeline(
+    State(state): State<Arc<AppState>>,
     Json(payload): Json<TimelineRequest>,
-) -> Json<TimelineResponse> {
-    let mut world = World::new();
-
-    let file_opened = NaiveDate::parse_from_str(&payload.file_opened, "%Y-%m-%d")
-        .unwrap_or_else(|_| NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
-    
-    let last_agm = NaiveDate::parse_from_str(&payload.last_agm_date, "%Y-%m-%d")
-        .unwrap_or_else(|_| NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
-        
-    let today = chrono::Local::now().naive_local().date();
-
-    // Spawn entity by component Insertion (avoids Tuple Bundle issues if trait impls are tricky)
-    world.spawn_empty()
-        .insert(ProjectDates { file_opened, last_agm, today })
-        .insert(StrataRules { fiscal_start_month: payload.fiscal_year_start_month as u32 });
-
-    // Run the calculation "System" manually for this request
-    let mut query = world.query::<(&ProjectDates, &StrataRules)>();
-    let (dates, rules) = query.single(&world).unwrap();
-
-    // 1. Calculate Next Projected AGM
-    let next_projected_agm = NaiveDate::from_ymd_opt(
-        dates.last_agm.year() + 1,
-        dates.last_agm.month(),
-        dates.last_agm.day(),
-    ).unwrap_or(dates.last_agm + Duration::days(365));
-    
-    // 2. Draft Deadline
-    let draft_deadline = next_projected_agm - Duration::days(30);
-    
-    // 3. Fiscal Year Info
-    let current_year = dates.today.year();
-    let mut fiscal_start = NaiveDate::from_ymd_opt(current_year, rules.fiscal_start_month, 1).unwrap();
-    if fiscal_start > dates.today {
-        fiscal_start = NaiveDate::from_ymd_opt(current_year - 1, rules.fiscal_start_month, 1).unwrap();
-    }
-    
-    let days_into_fiscal = (dates.today - fiscal_start).num_days();
-    let fiscal_end = NaiveDate::from_ymd_opt(fiscal_start.year() + 1, fiscal_start.month(), fiscal_start.day()).unwrap() - Duration::days(1);
-    let days_remaining_in_fiscal = (fiscal_end - dates.today).num_days();
-
-    // 4. Days since last AGM
-    let days_since_agm = if dates.today >= dates.last_agm {
-        Some((dates.today - dates.last_agm).num_days())
-    } else {
-        None
+) -> Result<Json<TimelineResponse>, TimelineError> {
+    let input = TimelineInput {
+        file_opened: timeline_engine::parse_date("file_opened", &payload.file_opened)?,
+        fiscal_year_start_month: payload.fiscal_year_start_month,
+        last_agm: timeline_engine::parse_optional_date("last_agm_date", Some(&payload.last_agm_date))?,
+        last_depr_report: timeline_engine::parse_optional_date("last_depr_report", payload.last_depr_report.as_deref())?,
+        target_agm: timeline_engine::parse_optional_date("target_date", payload.target_date.as_deref())?,
+        report_scope: ReportScope::parse(&payload.report_scope).ok_or_else(|| TimelineError::UnknownReportScope {
+            value: payload.report_scope.clone(),
+        })?,
+        today: state.clock.today_in(clock::DEFAULT_TIME_ZONE),
+    };
+    // Statutory holidays still apply if the company closures cannot be read
+    let calendar = {
+        let conn = state.conn.lock().unwrap();
+        BusinessCalendar::load(&conn, Some(COMPANY_PROVINCE))
+            .unwrap_or_else(|_| BusinessCalendar::new(Some(COMPANY_PROVINCE), Vec::new()))
     };
-    
-    // 5. Days since file opened
-    let days_since_file_opened = (dates.today - dates.file_opened).num_days();
+    let timeline = timeline_engine::compute(&input, &calendar)?;
 
-    Json(TimelineResponse {
-        next_projected_agm: next_projected_agm.format("%Y-%m-%d").to_string(),
-        draft_deadline: draft_deadline.format("%Y-%m-%d").to_string(),
-        days_into_fiscal,
-        days_remaining_in_fiscal,
-        days_since_agm,
-        days_since_file_opened,
-    })
+    Ok(Json(TimelineResponse {
+        next_projected_agm: format(timeline.next_projected_agm),
+        draft_deadline: format(timeline.draft_deadline),
+        days_into_fiscal: timeline.days_into_fiscal,
+        days_remaining_in_fiscal: timeline.days_remaining_in_fiscal,
+        days_since_agm: timeline.days_since_agm,
+        days_since_file_opened: timeline.days_since_file_opened,
+        fiscal_year_start: format(timeline.fiscal_year_start),
+        fiscal_year_end: format(timeline.fiscal_year_end),
+        report_fiscal_year_end: format(timeline.report_fiscal_year_end),
+        agm_window_start: format(timeline.agm_window_start),
+        agm_window_end: format(timeline.agm_window_end),
+        depreciation_report_due: timeline.depreciation_report_due.map(format),
+        depreciation_report_overdue: timeline.depreciation_report_overdue,
+    }))
 }
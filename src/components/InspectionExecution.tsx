@@ -1,12 +1,40 @@
-import { useState } from 'react';
+import { useEffect, useState, type ChangeEvent, type FormEvent } from 'react';
 import { Card, CardContent } from '@/components/ui/card';
 import { Button } from '@/components/ui/button';
 import { Badge } from '@/components/ui/badge';
-import { Loader2, Wand2, ArrowLeft, Save, FileCheck } from 'lucide-react';
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import { Textarea } from '@/components/ui/textarea';
+import { Loader2, Wand2, ArrowLeft, Plus, Trash2, FileCheck } from 'lucide-react';
 import { withBase } from '@/lib/base';
 import { emitEmailEvent } from '@/lib/email';
+import { authHeaders } from '@/lib/store';
 
+// The simulator invents findings, so it is only offered in demo builds
+const isDemo = import.meta.env.PUBLIC_DEMO_MODE === '1';
+
+interface Appointment {
+    id: string;
+    serviceRequestId: string;
+    strataPlan: string;
+    confirmedDate?: string;
+    confirmedTime?: string;
+}
+
+// As stored by /api/findings
 interface Finding {
+    id: string;
+    serviceRequestId: string;
+    component: string;
+    category?: string;
+    conditionScore: number;
+    observation: string;
+    recommendation: string;
+    estimatedCost?: number;
+}
+
+// As returned by the demo simulator
+interface SimulatedFinding {
     element: {
         name: string;
         category: string;
@@ -20,34 +48,170 @@ interface Finding {
     };
 }
 
+interface Draft {
+    component: string;
+    category: string;
+    conditionScore: string;
+    observation: string;
+    recommendation: string;
+    estimatedCost: string;
+}
+
+const EMPTY_DRAFT: Draft = {
+    component: '',
+    category: '',
+    conditionScore: '',
+    observation: '',
+    recommendation: '',
+    estimatedCost: '',
+};
+
 export function InspectionExecution({ jobId }: { jobId: string }) {
+    const [appointment, setAppointment] = useState<Appointment | null>(null);
     const [findings, setFindings] = useState<Finding[]>([]);
-    const [loading, setLoading] = useState(false);
-    const [saved, setSaved] = useState(false);
+    const [status, setStatus] = useState<'loading' | 'ready' | 'error'>('loading');
+    const [draft, setDraft] = useState<Draft>(EMPTY_DRAFT);
+    const [saving, setSaving] = useState(false);
+    const [formError, setFormError] = useState<string | null>(null);
+    const [suggestions, setSuggestions] = useState<SimulatedFinding[]>([]);
+    const [simulating, setSimulating] = useState(false);
+
+    // The job is an inspection appointment; its findings belong to the service request
+    useEffect(() => {
+        let cancelled = false;
+        const load = async () => {
+            setStatus('loading');
+            try {
+                const res = await fetch(`/api/logistics/appointments/${encodeURIComponent(jobId)}`, { headers: authHeaders() });
+                if (!res.ok) throw new Error(`Appointment ${jobId}: ${res.status}`);
+                const loaded: Appointment = await res.json();
+                const found = await fetch(`/api/findings?serviceRequestId=${encodeURIComponent(loaded.serviceRequestId)}`, {
+                    headers: authHeaders(),
+                });
+                if (!found.ok) throw new Error(`Findings: ${found.status}`);
+                const recorded: Finding[] = await found.json();
+                if (cancelled) return;
+                setAppointment(loaded);
+                setFindings(recorded);
+                setStatus('ready');
+            } catch (err) {
+                console.error(err);
+                if (!cancelled) setStatus('error');
+            }
+        };
+        load();
+        return () => {
+            cancelled = true;
+        };
+    }, [jobId]);
+
+    const update = (field: keyof Draft) => (e: ChangeEvent<HTMLInputElement | HTMLTextAreaElement>) =>
+        setDraft(prev => ({ ...prev, [field]: e.target.value }));
+
+    const recordFinding = async (e: FormEvent) => {
+        e.preventDefault();
+        if (!appointment) return;
+        const score = Number(draft.conditionScore);
+        if (!draft.component.trim() || draft.conditionScore === '' || !Number.isInteger(score) || score < 0 || score > 100) {
+            setFormError('A component and a condition score from 0 to 100 are required.');
+            return;
+        }
+        setSaving(true);
+        setFormError(null);
+        try {
+            const res = await fetch('/api/findings', {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json', ...authHeaders() },
+                body: JSON.stringify({
+                    serviceRequestId: appointment.serviceRequestId,
+                    component: draft.component.trim(),
+                    category: draft.category.trim() || null,
+                    conditionScore: score,
+                    observation: draft.observation.trim(),
+                    recommendation: draft.recommendation.trim(),
+                    estimatedCost: draft.estimatedCost === '' ? null : Math.round(Number(draft.estimatedCost)),
+                }),
+            });
+            if (!res.ok) throw new Error(`Record finding: ${res.status}`);
+            const saved: Finding = await res.json();
+            setFindings(prev => [...prev, saved].sort((a, b) => a.conditionScore - b.conditionScore));
+            setDraft(EMPTY_DRAFT);
+        } catch (err) {
+            console.error(err);
+            setFormError('The finding could not be saved. Please try again.');
+        } finally {
+            setSaving(false);
+        }
+    };
+
+    const removeFinding = async (id: string) => {
+        try {
+            const res = await fetch(`/api/findings/${encodeURIComponent(id)}`, { method: 'DELETE', headers: authHeaders() });
+            if (!res.ok) throw new Error(`Delete finding: ${res.status}`);
+            setFindings(prev => prev.filter(f => f.id !== id));
+        } catch (err) {
+            console.error(err);
+        }
+    };
 
     const runSimulation = async () => {
-        setLoading(true);
+        setSimulating(true);
         try {
-            // Call our new Rust ECS endpoint
             const res = await fetch('/api/ecs/inspection/simulate', { method: 'POST' });
-            const data = await res.json();
-            setFindings(data);
+            if (!res.ok) throw new Error(`Simulation: ${res.status}`);
+            setSuggestions(await res.json());
         } catch (err) {
             console.error(err);
         } finally {
-            setLoading(false);
+            setSimulating(false);
         }
     };
 
-    const handleSave = () => {
-        setSaved(true);
-        // In a real app, this would POST the findings back to the DB
-        setTimeout(() => {
-            emitEmailEvent('inspection_completed', { strataPlan: 'VIS 2345', jobId }).catch(() => undefined);
-            window.location.href = withBase('/inspector/dashboard');
-        }, 1500);
+    // A suggestion only fills the form; the inspector still reviews and records it
+    const applySuggestion = (item: SimulatedFinding) => {
+        setDraft({
+            component: item.element.name,
+            category: item.element.category,
+            conditionScore: String(item.finding.condition_score),
+            observation: item.finding.observation,
+            recommendation: item.finding.recommendation,
+            estimatedCost: item.finding.estimated_cost > 0 ? String(item.finding.estimated_cost) : '',
+        });
+        setSuggestions(prev => prev.filter(s => s !== item));
     };
 
+    const finish = () => {
+        if (!appointment) return;
+        emitEmailEvent('inspection_completed', { strataPlan: appointment.strataPlan, jobId }).catch(() => undefined);
+        window.location.href = withBase('/inspector/dashboard');
+    };
+
+    if (status !== 'ready' || !appointment) {
+        return (
+            <div className="max-w-4xl mx-auto">
+                <Card className="border-dashed border-2 border-gray-200">
+                    <CardContent className="py-20 flex flex-col items-center text-center space-y-4">
+                        {status === 'loading' ? (
+                            <Loader2 className="h-8 w-8 animate-spin text-gray-400" />
+                        ) : (
+                            <>
+                                <h3 className="text-xl font-bold text-gray-900">Inspection not available</h3>
+                                <p className="text-gray-500 max-w-md">
+                                    This job could not be loaded, or it is not assigned to you.
+                                </p>
+                                <a href={withBase('/inspector/dashboard')} className="text-[#6B8E5F] font-medium hover:underline">
+                                    Back to dashboard
+                                </a>
+                            </>
+                        )}
+                    </CardContent>
+                </Card>
+            </div>
+        );
+    }
+
+    const visit = [appointment.confirmedDate, appointment.confirmedTime].filter(Boolean).join(' at ');
+
     return (
         <div className="max-w-4xl mx-auto space-y-6 animate-in fade-in duration-500">
             <div className="flex items-center gap-4 mb-8">
@@ -56,97 +220,156 @@ export function InspectionExecution({ jobId }: { jobId: string }) {
                 </a>
                 <div>
                     <h1 className="text-3xl font-black text-gray-900 tracking-tight">Inspection Execution</h1>
-                    <p className="text-gray-500">Job #{jobId} • Vancouver Heights (VIS 2345)</p>
+                    <p className="text-gray-500">
+                        Job #{jobId} • {appointment.strataPlan}
+                        {visit && ` • ${visit}`}
+                    </p>
                 </div>
             </div>
 
-            {findings.length === 0 ? (
-                <Card className="border-dashed border-2 border-gray-200">
-                    <CardContent className="py-20 flex flex-col items-center text-center space-y-4">
-                        <div className="p-4 bg-purple-50 text-purple-600 rounded-full mb-2">
-                            <Wand2 size={32} />
+            <Card className="border-gray-200">
+                <CardContent className="p-6">
+                    <form onSubmit={recordFinding} className="space-y-4">
+                        <h3 className="text-lg font-bold">Record a finding</h3>
+                        <div className="grid md:grid-cols-3 gap-4">
+                            <div className="space-y-1 md:col-span-2">
+                                <Label htmlFor="component">Component</Label>
+                                <Input id="component" value={draft.component} onChange={update('component')} placeholder="Asphalt Shingle Roof" />
+                            </div>
+                            <div className="space-y-1">
+                                <Label htmlFor="category">Category</Label>
+                                <Input id="category" value={draft.category} onChange={update('category')} placeholder="Envelope" />
+                            </div>
+                            <div className="space-y-1">
+                                <Label htmlFor="conditionScore">Condition (0-100)</Label>
+                                <Input
+                                    id="conditionScore"
+                                    type="number"
+                                    min={0}
+                                    max={100}
+                                    value={draft.conditionScore}
+                                    onChange={update('conditionScore')}
+                                />
+                            </div>
+                            <div className="space-y-1">
+                                <Label htmlFor="estimatedCost">Estimated cost ($)</Label>
+                                <Input id="estimatedCost" type="number" min={0} value={draft.estimatedCost} onChange={update('estimatedCost')} />
+                            </div>
+                        </div>
+                        <div className="grid md:grid-cols-2 gap-4">
+                            <div className="space-y-1">
+                                <Label htmlFor="observation">Observation</Label>
+                                <Textarea id="observation" value={draft.observation} onChange={update('observation')} />
+                            </div>
+                            <div className="space-y-1">
+                                <Label htmlFor="recommendation">Recommendation</Label>
+                                <Textarea id="recommendation" value={draft.recommendation} onChange={update('recommendation')} />
+                            </div>
+                        </div>
+                        {formError && <p className="text-sm text-red-600">{formError}</p>}
+                        <div className="flex justify-end gap-3">
+                            {isDemo && (
+                                <Button type="button" variant="outline" onClick={runSimulation} disabled={simulating}>
+                                    {simulating ? <Loader2 className="mr-2 h-4 w-4 animate-spin" /> : <Wand2 className="mr-2 h-4 w-4" />}
+                                    Suggest findings (demo)
+                                </Button>
+                            )}
+                            <Button type="submit" disabled={saving} className="bg-[#6B8E5F] hover:bg-[#5a7850]">
+                                {saving ? <Loader2 className="mr-2 h-4 w-4 animate-spin" /> : <Plus className="mr-2 h-4 w-4" />}
+                                Record Finding
+                            </Button>
                         </div>
-                        <h3 className="text-xl font-bold text-gray-900">AI Simulation Ready</h3>
-                        <p className="text-gray-500 max-w-md">
-                            Use the ECS Engine to simulate field observations based on the building's age and component lifecycle models.
-                        </p>
-                        <Button
-                            size="lg"
-                            onClick={runSimulation}
-                            disabled={loading}
-                            className="bg-purple-600 hover:bg-purple-700 text-white mt-4"
-                        >
-                            {loading ? <Loader2 className="mr-2 h-4 w-4 animate-spin" /> : <Wand2 className="mr-2 h-4 w-4" />}
-                            Run ECS Simulation
-                        </Button>
+                    </form>
+                </CardContent>
+            </Card>
+
+            {suggestions.length > 0 && (
+                <Card className="border-dashed border-2 border-purple-200">
+                    <CardContent className="p-6 space-y-3">
+                        <p className="text-xs font-bold text-purple-600 uppercase tracking-wider">Simulated suggestions</p>
+                        {suggestions.map((item, idx) => (
+                            <div key={idx} className="flex items-center justify-between gap-4">
+                                <div>
+                                    <span className="font-medium">{item.element.name}</span>
+                                    <span className="text-xs text-gray-400 ml-2">Score {item.finding.condition_score}</span>
+                                </div>
+                                <Button size="sm" variant="outline" onClick={() => applySuggestion(item)}>
+                                    Use
+                                </Button>
+                            </div>
+                        ))}
                     </CardContent>
                 </Card>
+            )}
+
+            {findings.length === 0 ? (
+                <p className="text-center text-gray-500 py-8">No findings recorded for this inspection yet.</p>
             ) : (
-                <div className="space-y-6">
-                    <div className="grid gap-4">
-                        {findings.map((item, idx) => (
-                            <Card key={idx} className="overflow-hidden border-gray-200 hover:shadow-md transition-shadow">
-                                <div className={`h-2 w-full ${getScoreColor(item.finding.condition_score)}`} />
-                                <CardContent className="p-6">
-                                    <div className="flex justify-between items-start mb-4">
-                                        <div>
+                <div className="grid gap-4">
+                    {findings.map(item => (
+                        <Card key={item.id} className="overflow-hidden border-gray-200 hover:shadow-md transition-shadow">
+                            <div className={`h-2 w-full ${getScoreColor(item.conditionScore)}`} />
+                            <CardContent className="p-6">
+                                <div className="flex justify-between items-start mb-4">
+                                    <div>
+                                        {item.category && (
                                             <div className="flex items-center gap-2 mb-1">
-                                                <Badge variant="outline">{item.element.category}</Badge>
-                                                <span className="text-xs text-gray-400">Age: {item.element.age_years} yrs</span>
+                                                <Badge variant="outline">{item.category}</Badge>
                                             </div>
-                                            <h3 className="text-lg font-bold">{item.element.name}</h3>
-                                        </div>
+                                        )}
+                                        <h3 className="text-lg font-bold">{item.component}</h3>
+                                    </div>
+                                    <div className="flex items-start gap-4">
                                         <div className="text-right">
-                                            <span className="text-2xl font-black text-gray-900">{item.finding.condition_score}</span>
+                                            <span className="text-2xl font-black text-gray-900">{item.conditionScore}</span>
                                             <span className="text-xs text-gray-400 block uppercase tracking-wider">Score</span>
                                         </div>
+                                        <Button
+                                            size="sm"
+                                            variant="ghost"
+                                            onClick={() => removeFinding(item.id)}
+                                            aria-label={`Delete finding for ${item.component}`}
+                                        >
+                                            <Trash2 className="h-4 w-4 text-gray-400" />
+                                        </Button>
                                     </div>
+                                </div>
 
-                                    <div className="grid md:grid-cols-2 gap-6 p-4 bg-gray-50 rounded-lg">
-                                        <div>
-                                            <p className="text-xs font-bold text-gray-400 uppercase tracking-wider mb-1">Observation</p>
-                                            <p className="text-sm font-medium text-gray-700">{item.finding.observation}</p>
-                                        </div>
-                                        <div>
-                                            <p className="text-xs font-bold text-gray-400 uppercase tracking-wider mb-1">Recommendation</p>
-                                            <p className="text-sm font-medium text-gray-700">{item.finding.recommendation}</p>
-                                        </div>
+                                <div className="grid md:grid-cols-2 gap-6 p-4 bg-gray-50 rounded-lg">
+                                    <div>
+                                        <p className="text-xs font-bold text-gray-400 uppercase tracking-wider mb-1">Observation</p>
+                                        <p className="text-sm font-medium text-gray-700">{item.observation}</p>
+                                    </div>
+                                    <div>
+                                        <p className="text-xs font-bold text-gray-400 uppercase tracking-wider mb-1">Recommendation</p>
+                                        <p className="text-sm font-medium text-gray-700">{item.recommendation}</p>
                                     </div>
+                                </div>
 
-                                    {item.finding.estimated_cost > 0 && (
-                                        <div className="mt-4 flex justify-end">
-                                            <Badge className="bg-gray-900 text-white hover:bg-gray-800">
-                                                Est. Cost: ${item.finding.estimated_cost.toLocaleString()}
-                                            </Badge>
-                                        </div>
-                                    )}
-                                </CardContent>
-                            </Card>
-                        ))}
-                    </div>
-
-                    <div className="flex justify-end pt-4">
-                        <Button
-                            size="lg"
-                            onClick={handleSave}
-                            disabled={saved}
-                            className={`min-w-[200px] ${saved ? 'bg-green-600 hover:bg-green-700' : 'bg-[#6B8E5F] hover:bg-[#5a7850]'}`}
-                        >
-                            {saved ? (
-                                <>
-                                    <FileCheck className="mr-2 h-4 w-4" />
-                                    Saved to Database
-                                </>
-                            ) : (
-                                <>
-                                    <Save className="mr-2 h-4 w-4" />
-                                    Save Findings
-                                </>
-                            )}
-                        </Button>
-                    </div>
+                                {item.estimatedCost != null && item.estimatedCost > 0 && (
+                                    <div className="mt-4 flex justify-end">
+                                        <Badge className="bg-gray-900 text-white hover:bg-gray-800">
+                                            Est. Cost: ${item.estimatedCost.toLocaleString()}
+                                        </Badge>
+                                    </div>
+                                )}
+                            </CardContent>
+                        </Card>
+                    ))}
                 </div>
             )}
+
+            <div className="flex justify-end pt-4">
+                <Button
+                    size="lg"
+                    onClick={finish}
+                    disabled={findings.length === 0}
+                    className="min-w-[200px] bg-[#6B8E5F] hover:bg-[#5a7850]"
+                >
+                    <FileCheck className="mr-2 h-4 w-4" />
+                    Finish Inspection
+                </Button>
+            </div>
         </div>
     );
 }
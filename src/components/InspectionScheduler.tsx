@@ -17,6 +17,7 @@ interface Slot {
     date: string;
     time: string;
     id: string;
+    startTime?: string; // HH:MM from the slot generator
 }
 
 export function InspectionScheduler() {
@@ -32,23 +33,32 @@ export function InspectionScheduler() {
     const isLocked = !allApproved;
 
     const [selectedSlots, setSelectedSlots] = useState<Slot[]>([]);
-    const [status, setStatus] = useState<'idle' | 'loading' | 'submitted' | 'confirmed'>('loading');
+    const [status, setStatus] = useState<'idle' | 'loading' | 'error' | 'submitted' | 'confirmed'>('loading');
     const [availableSlots, setAvailableSlots] = useState<Slot[]>([]);
 
+    // Slots depend on the site and estimate of the client's own service request
+    const serviceRequestId = getCurrentUserServiceRequest()?.id;
+
     useEffect(() => {
+        if (!serviceRequestId) return;
         const fetchSlots = async () => {
+            setStatus('loading');
             try {
-                const response = await fetch('/api/logistics/available-slots');
+                const response = await fetch(
+                    `/api/logistics/available-slots?serviceRequestId=${encodeURIComponent(serviceRequestId)}`
+                );
+                if (!response.ok) throw new Error(`HTTP ${response.status}`);
                 const data = await response.json();
                 setAvailableSlots(data);
                 setStatus('idle');
             } catch (error) {
                 console.error('Failed to fetch slots:', error);
-                setStatus('idle');
+                setAvailableSlots([]);
+                setStatus('error');
             }
         };
         fetchSlots();
-    }, []);
+    }, [serviceRequestId]);
 
     // Group slots by date
     const groupedSlots = useMemo(() => {
@@ -73,10 +83,10 @@ export function InspectionScheduler() {
     };
 
     const handleSubmit = async () => {
+        if (!serviceRequestId) return;
         setStatus('submitted');
 
-        const strataPlan = getCurrentUserStrata()?.strataPlan || 'VIS 2345';
-        const serviceRequestId = getCurrentUserServiceRequest()?.id || 'req-1';
+        const strataPlan = getCurrentUserStrata()?.strataPlan ?? '';
 
         const mapTime = (t: string): '10:00' | '14:00' | '18:00' => {
             const s = t.toLowerCase();
@@ -95,9 +105,9 @@ export function InspectionScheduler() {
                 strataPlan,
                 appointmentType: 'inspection',
                 requestedDate1: first.date,
-                requestedTime1: mapTime(first.time),
+                requestedTime1: first.startTime ?? mapTime(first.time),
                 requestedDate2: alt?.date,
-                requestedTime2: alt ? mapTime(alt.time) : undefined,
+                requestedTime2: alt ? alt.startTime ?? mapTime(alt.time) : undefined,
                 status: 'pending',
                 createdAt: new Date().toISOString(),
                 updatedAt: new Date().toISOString(),
@@ -135,6 +145,23 @@ export function InspectionScheduler() {
         );
     }
 
+    if (!serviceRequestId || status === 'error') {
+        return (
+            <Card className="max-w-3xl mx-auto border-dashed border-gray-200 bg-gray-50/50">
+                <CardContent className="p-12 text-center space-y-2">
+                    <h2 className="text-2xl font-black text-gray-400">
+                        {serviceRequestId ? 'Inspection times unavailable' : 'No service request found'}
+                    </h2>
+                    <p className="text-gray-500 max-w-sm mx-auto text-sm">
+                        {serviceRequestId
+                            ? 'We could not load the available inspection times. Please try again shortly.'
+                            : 'Inspection times are offered once your strata has an open service request.'}
+                    </p>
+                </CardContent>
+            </Card>
+        );
+    }
+
     if (status === 'submitted') {
         return (
             <Card className="max-w-2xl mx-auto border-[#6B8E5F]/20 bg-[#6B8E5F]/5">
@@ -186,6 +213,12 @@ export function InspectionScheduler() {
                 </div>
             </div>
 
+            {status === 'idle' && groupedSlots.length === 0 && (
+                <p className="text-center text-sm text-gray-500 py-12">
+                    No inspection times are open in the next few weeks. Please check back later.
+                </p>
+            )}
+
             <div className="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-4 gap-4">
                 {groupedSlots.map(({ date, slots }) => (
                     <Card key={date.toISOString()} className="overflow-hidden border-gray-100">
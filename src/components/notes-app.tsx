@@ -1,6 +1,7 @@
 import * as React from 'react'
 import { motion, AnimatePresence } from 'framer-motion'
 import { Plus, Trash2, Edit3, Save, X, Search, Pin, PinOff } from 'lucide-react'
+import { authHeaders, useStore } from '@/lib/store'
 
 interface Note {
   id: number
@@ -35,6 +36,11 @@ export function NotesApp(): React.JSX.Element {
 
   const API_BASE = `/api`
 
+  // Notes are private to their author, so every call identifies the signed-in user
+  const token = useStore((s) => s.auth.token)
+  const userHeaders = React.useMemo(() => authHeaders(), [token])
+  const jsonHeaders = { 'Content-Type': 'application/json', ...userHeaders }
+
   // Debounced search
   const [debouncedSearch, setDebouncedSearch] = React.useState('')
 
@@ -49,13 +55,13 @@ export function NotesApp(): React.JSX.Element {
       const url = debouncedSearch
         ? `${API_BASE}/notes?q=${encodeURIComponent(debouncedSearch)}`
         : `${API_BASE}/notes`
-      const res = await fetch(url)
+      const res = await fetch(url, { headers: userHeaders })
       const data: Note[] = await res.json()
       setNotes(data)
     } catch (err) {
       console.error('Failed to fetch notes:', err)
     }
-  }, [API_BASE, debouncedSearch])
+  }, [API_BASE, debouncedSearch, userHeaders])
 
   React.useEffect(() => {
     fetchNotes()
@@ -67,7 +73,7 @@ export function NotesApp(): React.JSX.Element {
     try {
       await fetch(`${API_BASE}/notes`, {
         method: 'POST',
-        headers: { 'Content-Type': 'application/json' },
+        headers: jsonHeaders,
         body: JSON.stringify({ title, content }),
       })
       setTitle('')
@@ -83,7 +89,7 @@ export function NotesApp(): React.JSX.Element {
     try {
       await fetch(`${API_BASE}/notes/update`, {
         method: 'PUT',
-        headers: { 'Content-Type': 'application/json' },
+        headers: jsonHeaders,
         body: JSON.stringify({ id, title: editTitle, content: editContent }),
       })
       setEditingId(null)
@@ -98,7 +104,7 @@ export function NotesApp(): React.JSX.Element {
     try {
       await fetch(`${API_BASE}/notes/pin`, {
         method: 'PUT',
-        headers: { 'Content-Type': 'application/json' },
+        headers: jsonHeaders,
         body: JSON.stringify({ id }),
       })
       fetchNotes()
@@ -112,7 +118,7 @@ export function NotesApp(): React.JSX.Element {
     try {
       await fetch(`${API_BASE}/notes/delete`, {
         method: 'DELETE',
-        headers: { 'Content-Type': 'application/json' },
+        headers: jsonHeaders,
         body: JSON.stringify({ id }),
       })
       fetchNotes()
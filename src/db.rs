@@ -1,13 +1,40 @@
-use rusqlite::{Connection, Result};
-use std::sync::Mutex;
+use rusqlite::{Connection, OptionalExtension, Result};
+use crate::api_handlers::clock::{self, Clock};
+use crate::api_handlers::deadline_alerts::AlertThresholds;
+use std::sync::{Arc, Mutex};
+
+// Bump whenever the search triggers change what they index, so existing
+// databases rebuild search_index once on the next start.
+const SEARCH_INDEX_VERSION: i64 = 1;
+
+// Every trigger create_search_triggers defines, dropped by name before recreating
+const SEARCH_TRIGGERS: &[&str] = &[
+    "stratas_search_ai",
+    "stratas_search_au",
+    "stratas_search_ad",
+    "survey_answers_search_ai",
+    "survey_answers_search_au",
+    "survey_answers_search_ad",
+    "document_analysis_search_au",
+    "documents_search_ad",
+    "notes_search_ai",
+    "notes_search_au",
+    "notes_search_ad",
+];
 
 pub struct AppState {
     pub conn: Mutex<Connection>,
+    pub clock: Arc<dyn Clock>,
+    pub alert_thresholds: AlertThresholds,
 }
 
 impl AppState {
     pub fn new() -> Result<Self> {
-        let conn = Connection::open("srp_portal.db")?;
+        Self::with_connection(Connection::open("srp_portal.db")?)
+    }
+
+    // Brings the schema up to date on `conn` and seeds it; tests pass an in-memory database
+    pub fn with_connection(conn: Connection) -> Result<Self> {
 
         // Users Table
         conn.execute(
@@ -26,6 +53,31 @@ impl AppState {
             [],
         )?;
 
+        // Inspector profile used by the scheduler: skill (1-5) and home base
+        Self::add_column_if_missing(&conn, "users", "skill_level", "INTEGER")?;
+        Self::add_column_if_missing(&conn, "users", "home_address", "TEXT")?;
+        Self::add_column_if_missing(&conn, "users", "home_city", "TEXT")?;
+        Self::add_column_if_missing(&conn, "users", "home_postal_code", "TEXT")?;
+        // Set once an admin saves the inspector's week, so an empty week is not
+        // mistaken for one that was never configured
+        Self::add_column_if_missing(&conn, "users", "working_hours_set", "INTEGER NOT NULL DEFAULT 0")?;
+        // @mention handle, unique across the portal
+        Self::add_column_if_missing(&conn, "users", "handle", "TEXT")?;
+        Self::backfill_user_handles(&conn)?;
+        conn.execute("CREATE UNIQUE INDEX IF NOT EXISTS idx_users_handle ON users(handle)", [])?;
+
+        // Bearer tokens handed out at login; every authenticated call names one
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS sessions (
+                token TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                expires_at TEXT NOT NULL,
+                FOREIGN KEY(user_id) REFERENCES users(id)
+            )",
+            [],
+        )?;
+
         // Stratas Table
         conn.execute(
             "CREATE TABLE IF NOT EXISTS stratas (
@@ -44,6 +96,7 @@ impl AppState {
             )",
             [],
         )?;
+        Self::add_column_if_missing(&conn, "stratas", "time_zone", "TEXT NOT NULL DEFAULT 'America/Vancouver'")?;
 
         // Service Requests Table
         conn.execute(
@@ -62,6 +115,16 @@ impl AppState {
             [],
         )?;
 
+        Self::add_column_if_missing(&conn, "service_requests", "estimated_duration_hours", "REAL")?;
+        Self::add_column_if_missing(&conn, "service_requests", "agm_date", "TEXT")?; // most recent AGM
+        Self::add_column_if_missing(&conn, "service_requests", "draft_sent_date", "TEXT")?;
+        // Timeline inputs; see timeline_engine
+        Self::add_column_if_missing(&conn, "service_requests", "file_opened_date", "TEXT")?;
+        Self::add_column_if_missing(&conn, "service_requests", "fiscal_year_start_month", "INTEGER")?;
+        Self::add_column_if_missing(&conn, "service_requests", "last_depreciation_report_date", "TEXT")?;
+        Self::add_column_if_missing(&conn, "service_requests", "target_date", "TEXT")?; // target AGM
+        Self::add_column_if_missing(&conn, "service_requests", "report_scope", "TEXT")?;
+
         // Survey Answers Table
         conn.execute(
             "CREATE TABLE IF NOT EXISTS survey_answers (
@@ -74,18 +137,409 @@ impl AppState {
             [],
         )?;
 
+        // Documents Table
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS documents (
+                id TEXT PRIMARY KEY,
+                service_request_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                file_name TEXT NOT NULL,
+                document_type TEXT NOT NULL,
+                category TEXT NOT NULL,
+                status TEXT NOT NULL,
+                uploaded_by TEXT NOT NULL,
+                uploaded_at TEXT,
+                reviewed_by TEXT,
+                reviewed_at TEXT,
+                file_size INTEGER,
+                mime_type TEXT,
+                FOREIGN KEY(service_request_id) REFERENCES service_requests(id)
+            )",
+            [],
+        )?;
+
+        // Uploaded file bytes, kept apart so listing documents stays cheap
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS document_files (
+                document_id TEXT PRIMARY KEY,
+                content BLOB NOT NULL,
+                FOREIGN KEY(document_id) REFERENCES documents(id)
+            )",
+            [],
+        )?;
+
+        // Processing progress and results from the ECS document pipeline
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS document_analysis (
+                document_id TEXT PRIMARY KEY,
+                processing_status TEXT NOT NULL,
+                file_type TEXT,
+                analysis TEXT,
+                classification TEXT,
+                error TEXT,
+                extracted_text TEXT,
+                updated_at TEXT NOT NULL,
+                FOREIGN KEY(document_id) REFERENCES documents(id)
+            )",
+            [],
+        )?;
+
+        // Notes Table: private to the author (and admins), optionally filed
+        // against a strata or a service request
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS notes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                owner_id TEXT NOT NULL,
+                strata_id TEXT,
+                service_request_id TEXT,
+                title TEXT NOT NULL,
+                content TEXT NOT NULL,
+                content_html TEXT NOT NULL DEFAULT '',
+                tags TEXT NOT NULL DEFAULT '',
+                pinned INTEGER NOT NULL DEFAULT 0,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                FOREIGN KEY(owner_id) REFERENCES users(id),
+                FOREIGN KEY(strata_id) REFERENCES stratas(id),
+                FOREIGN KEY(service_request_id) REFERENCES service_requests(id)
+            )",
+            [],
+        )?;
+
+        Self::add_column_if_missing(&conn, "notes", "content_html", "TEXT NOT NULL DEFAULT ''")?;
+        Self::add_column_if_missing(&conn, "notes", "tags", "TEXT NOT NULL DEFAULT ''")?;
+
+        // External-content FTS over notes, kept in sync by the triggers below.
+        // Tags are space-separated so each one is its own FTS token.
+        let fts_has_tags: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('notes_fts') WHERE name = 'tags'",
+            [],
+            |row| row.get(0),
+        )?;
+        if fts_has_tags == 0 {
+            conn.execute_batch(
+                "DROP TRIGGER IF EXISTS notes_fts_ai;
+                DROP TRIGGER IF EXISTS notes_fts_ad;
+                DROP TRIGGER IF EXISTS notes_fts_au;
+                DROP TABLE IF EXISTS notes_fts;",
+            )?;
+        }
+        conn.execute_batch(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS notes_fts USING fts5(
+                title,
+                content,
+                tags,
+                content='notes',
+                content_rowid='id'
+            );
+            CREATE TRIGGER IF NOT EXISTS notes_fts_ai AFTER INSERT ON notes BEGIN
+                INSERT INTO notes_fts (rowid, title, content, tags) VALUES (new.id, new.title, new.content, new.tags);
+            END;
+            CREATE TRIGGER IF NOT EXISTS notes_fts_ad AFTER DELETE ON notes BEGIN
+                INSERT INTO notes_fts (notes_fts, rowid, title, content, tags) VALUES ('delete', old.id, old.title, old.content, old.tags);
+            END;
+            CREATE TRIGGER IF NOT EXISTS notes_fts_au AFTER UPDATE ON notes BEGIN
+                INSERT INTO notes_fts (notes_fts, rowid, title, content, tags) VALUES ('delete', old.id, old.title, old.content, old.tags);
+                INSERT INTO notes_fts (rowid, title, content, tags) VALUES (new.id, new.title, new.content, new.tags);
+            END;",
+        )?;
+        if fts_has_tags == 0 {
+            conn.execute("INSERT INTO notes_fts (notes_fts) VALUES ('rebuild')", [])?;
+        }
+
+        // Files and inspection photos attached to a note. Either the bytes live
+        // here, or the attachment points at an already uploaded document.
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS note_attachments (
+                id TEXT PRIMARY KEY,
+                note_id INTEGER NOT NULL,
+                kind TEXT NOT NULL,
+                document_id TEXT,
+                file_name TEXT NOT NULL,
+                mime_type TEXT,
+                file_size INTEGER,
+                content BLOB,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY(note_id) REFERENCES notes(id) ON DELETE CASCADE,
+                FOREIGN KEY(document_id) REFERENCES documents(id)
+            )",
+            [],
+        )?;
+
+        // Users @mentioned in a note, who may read it but not edit it
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS note_mentions (
+                note_id INTEGER NOT NULL,
+                user_id TEXT NOT NULL,
+                PRIMARY KEY(note_id, user_id),
+                FOREIGN KEY(note_id) REFERENCES notes(id) ON DELETE CASCADE,
+                FOREIGN KEY(user_id) REFERENCES users(id)
+            )",
+            [],
+        )?;
+
+        // Notifications Table (in-app inbox)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS notifications (
+                id TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL,
+                title TEXT NOT NULL,
+                message TEXT NOT NULL,
+                type TEXT NOT NULL,
+                link TEXT,
+                read INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY(user_id) REFERENCES users(id)
+            )",
+            [],
+        )?;
+
+        // Appointments Table (inspections and draft review meetings)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS appointments (
+                id TEXT PRIMARY KEY,
+                service_request_id TEXT NOT NULL,
+                strata_plan TEXT NOT NULL,
+                appointment_type TEXT NOT NULL,
+                requested_date_1 TEXT NOT NULL,
+                requested_time_1 TEXT NOT NULL,
+                requested_date_2 TEXT,
+                requested_time_2 TEXT,
+                confirmed_date TEXT,
+                confirmed_time TEXT,
+                inspector_id TEXT,
+                status TEXT NOT NULL,
+                meeting_type TEXT,
+                notes TEXT,
+                cancellation_reason TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                FOREIGN KEY(service_request_id) REFERENCES service_requests(id),
+                FOREIGN KEY(inspector_id) REFERENCES users(id)
+            )",
+            [],
+        )?;
+        Self::add_column_if_missing(&conn, "appointments", "cancellation_reason", "TEXT")?;
+        // Local date and time stay as agreed; this is the instant they mean
+        Self::add_column_if_missing(&conn, "appointments", "starts_at", "TEXT")?;
+
+        // Appointment audit trail
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS appointment_events (
+                id TEXT PRIMARY KEY,
+                appointment_id TEXT NOT NULL,
+                action TEXT NOT NULL,
+                from_status TEXT,
+                to_status TEXT NOT NULL,
+                actor_id TEXT,
+                reason TEXT,
+                date TEXT,
+                time TEXT,
+                inspector_id TEXT,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY(appointment_id) REFERENCES appointments(id)
+            )",
+            [],
+        )?;
+
+        // Who is expected at a draft review meeting
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS appointment_attendees (
+                appointment_id TEXT NOT NULL,
+                user_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                email TEXT NOT NULL,
+                required INTEGER NOT NULL DEFAULT 1,
+                PRIMARY KEY(appointment_id, user_id),
+                FOREIGN KEY(appointment_id) REFERENCES appointments(id),
+                FOREIGN KEY(user_id) REFERENCES users(id)
+            )",
+            [],
+        )?;
+
+        // Inspector weekly working hours
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS inspector_working_hours (
+                inspector_id TEXT NOT NULL,
+                weekday INTEGER NOT NULL,
+                start_time TEXT NOT NULL,
+                end_time TEXT NOT NULL,
+                PRIMARY KEY(inspector_id, weekday),
+                FOREIGN KEY(inspector_id) REFERENCES users(id)
+            )",
+            [],
+        )?;
+
+        // Inspector unavailability blocks (leave, training, ...)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS inspector_unavailability (
+                id TEXT PRIMARY KEY,
+                inspector_id TEXT NOT NULL,
+                start_date TEXT NOT NULL,
+                end_date TEXT NOT NULL,
+                reason TEXT,
+                FOREIGN KEY(inspector_id) REFERENCES users(id)
+            )",
+            [],
+        )?;
+
+        // Company closures and statutory holidays
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS company_closures (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                date TEXT NOT NULL,
+                recurring INTEGER NOT NULL DEFAULT 0
+            )",
+            [],
+        )?;
+
+        // Secret tokens for iCalendar subscription feeds
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS calendar_tokens (
+                user_id TEXT PRIMARY KEY,
+                token TEXT NOT NULL UNIQUE,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY(user_id) REFERENCES users(id)
+            )",
+            [],
+        )?;
+
+        // Admin pins and exclusions the scheduler honours on every run
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS scheduler_overrides (
+                id TEXT PRIMARY KEY,
+                service_request_id TEXT NOT NULL,
+                inspector_id TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                date TEXT,
+                reason TEXT,
+                created_by TEXT,
+                created_at TEXT NOT NULL,
+                UNIQUE(service_request_id, inspector_id, kind),
+                FOREIGN KEY(service_request_id) REFERENCES service_requests(id),
+                FOREIGN KEY(inspector_id) REFERENCES users(id)
+            )",
+            [],
+        )?;
+
+        // Milestones computed for every open service request, refreshed by /api/timelines
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS timeline_milestones (
+                service_request_id TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                due_date TEXT NOT NULL,
+                completed_date TEXT,
+                projected INTEGER NOT NULL DEFAULT 0,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY(service_request_id, kind),
+                FOREIGN KEY(service_request_id) REFERENCES service_requests(id)
+            )",
+            [],
+        )?;
+
+        // Deadline risk raised by the monitor; one row per request and rule,
+        // resolved rather than deleted once the risk clears
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS deadline_alerts (
+                id TEXT PRIMARY KEY,
+                service_request_id TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                severity TEXT NOT NULL,
+                message TEXT NOT NULL,
+                due_date TEXT NOT NULL,
+                days_remaining INTEGER NOT NULL,
+                raised_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                resolved_at TEXT,
+                acknowledged_by TEXT,
+                acknowledged_at TEXT,
+                UNIQUE(service_request_id, kind),
+                FOREIGN KEY(service_request_id) REFERENCES service_requests(id)
+            )",
+            [],
+        )?;
+
+        // Findings recorded on site, one per component inspected
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS inspection_findings (
+                id TEXT PRIMARY KEY,
+                service_request_id TEXT NOT NULL,
+                component TEXT NOT NULL,
+                category TEXT,
+                condition_score INTEGER NOT NULL CHECK (condition_score BETWEEN 0 AND 100),
+                observation TEXT NOT NULL,
+                recommendation TEXT NOT NULL,
+                estimated_cost INTEGER,
+                inspector_id TEXT,
+                recorded_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                FOREIGN KEY(service_request_id) REFERENCES service_requests(id),
+                FOREIGN KEY(inspector_id) REFERENCES users(id)
+            )",
+            [],
+        )?;
+
+        // Reserve components each strata owns, usually seeded from the catalogue
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS component_inventory (
+                id TEXT PRIMARY KEY,
+                strata_id TEXT NOT NULL,
+                catalogue_code TEXT,
+                component_type TEXT NOT NULL,
+                category TEXT NOT NULL,
+                quantity REAL,
+                unit TEXT NOT NULL,
+                install_year INTEGER,
+                expected_useful_life INTEGER NOT NULL,
+                replacement_unit_cost REAL NOT NULL,
+                location TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                FOREIGN KEY(strata_id) REFERENCES stratas(id)
+            )",
+            [],
+        )?;
+        Self::add_column_if_missing(&conn, "inspection_findings", "component_id", "TEXT")?;
+
         // FTS for Search (Optional but useful for Admin search)
+        // Earlier builds created the index without title/scope columns and never
+        // wrote to it, so an old-shaped table can simply be replaced.
+        let has_scope: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('search_index') WHERE name = 'strata_id'",
+            [],
+            |row| row.get(0),
+        )?;
+        if has_scope == 0 {
+            conn.execute("DROP TABLE IF EXISTS search_index", [])?;
+        }
         conn.execute(
             "CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+                title,
                 content,
-                source_id,
-                source_type
+                source_id UNINDEXED,
+                source_type UNINDEXED,
+                strata_id UNINDEXED,
+                owner_id UNINDEXED
+            )",
+            [],
+        )?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS schema_versions (
+                name TEXT PRIMARY KEY,
+                version INTEGER NOT NULL
             )",
             [],
         )?;
+        Self::create_search_triggers(&conn)?;
+        if Self::search_index_is_stale(&conn)? {
+            Self::rebuild_search_index(&conn)?;
+        }
 
         let state = Self {
             conn: Mutex::new(conn),
+            clock: clock::from_env(),
+            alert_thresholds: AlertThresholds::from_env(),
         };
 
         state.seed_data()?;
@@ -93,9 +547,165 @@ impl AppState {
         Ok(state)
     }
 
+    // For tables that predate a column: CREATE TABLE IF NOT EXISTS leaves them as they were
+    fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+        let exists: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM pragma_table_info('{}') WHERE name = ?1", table),
+            [column],
+            |row| row.get(0),
+        )?;
+        if exists == 0 {
+            conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+        }
+        Ok(())
+    }
+
+    // Users without a handle get their email's local part, or their id where
+    // two accounts share a local part.
+    fn backfill_user_handles(conn: &Connection) -> Result<()> {
+        conn.execute(
+            "UPDATE users
+             SET handle = CASE
+                 WHEN EXISTS (
+                     SELECT 1 FROM users other
+                     WHERE other.id <> users.id
+                       AND lower(substr(other.email, 1, instr(other.email, '@') - 1))
+                         = lower(substr(users.email, 1, instr(users.email, '@') - 1))
+                 ) THEN lower(id)
+                 ELSE lower(substr(email, 1, instr(email, '@') - 1))
+             END
+             WHERE handle IS NULL",
+            [],
+        )?;
+        Ok(())
+    }
+
+    // Keep search_index in step with the tables it covers. Survey answers are
+    // saved with INSERT OR REPLACE, which does not fire delete triggers, so the
+    // insert triggers clear any previous entry for the same key first.
+    // The triggers are recreated on every start so edits here reach existing databases.
+    fn create_search_triggers(conn: &Connection) -> Result<()> {
+        for name in SEARCH_TRIGGERS {
+            conn.execute(&format!("DROP TRIGGER IF EXISTS {}", name), [])?;
+        }
+
+        conn.execute_batch(
+            "CREATE TRIGGER IF NOT EXISTS stratas_search_ai AFTER INSERT ON stratas BEGIN
+                INSERT INTO search_index (title, content, source_id, source_type, strata_id)
+                VALUES (new.complex_name || ' (' || new.strata_plan || ')',
+                        concat_ws(' ', new.strata_plan, new.address, new.city, new.province, new.postal_code),
+                        new.id, 'strata', new.id);
+            END;
+            CREATE TRIGGER IF NOT EXISTS stratas_search_au AFTER UPDATE ON stratas BEGIN
+                DELETE FROM search_index WHERE source_type = 'strata' AND source_id = old.id;
+                INSERT INTO search_index (title, content, source_id, source_type, strata_id)
+                VALUES (new.complex_name || ' (' || new.strata_plan || ')',
+                        concat_ws(' ', new.strata_plan, new.address, new.city, new.province, new.postal_code),
+                        new.id, 'strata', new.id);
+            END;
+            CREATE TRIGGER IF NOT EXISTS stratas_search_ad AFTER DELETE ON stratas BEGIN
+                DELETE FROM search_index WHERE source_type = 'strata' AND source_id = old.id;
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS survey_answers_search_ai AFTER INSERT ON survey_answers BEGIN
+                DELETE FROM search_index WHERE source_type = 'survey_answer'
+                    AND source_id = new.service_request_id || '/' || new.question_id;
+                INSERT INTO search_index (title, content, source_id, source_type, strata_id)
+                VALUES (new.question_id, new.value, new.service_request_id || '/' || new.question_id, 'survey_answer',
+                        (SELECT strata_id FROM service_requests WHERE id = new.service_request_id));
+            END;
+            CREATE TRIGGER IF NOT EXISTS survey_answers_search_au AFTER UPDATE ON survey_answers BEGIN
+                DELETE FROM search_index WHERE source_type = 'survey_answer'
+                    AND source_id = old.service_request_id || '/' || old.question_id;
+                INSERT INTO search_index (title, content, source_id, source_type, strata_id)
+                VALUES (new.question_id, new.value, new.service_request_id || '/' || new.question_id, 'survey_answer',
+                        (SELECT strata_id FROM service_requests WHERE id = new.service_request_id));
+            END;
+            CREATE TRIGGER IF NOT EXISTS survey_answers_search_ad AFTER DELETE ON survey_answers BEGIN
+                DELETE FROM search_index WHERE source_type = 'survey_answer'
+                    AND source_id = old.service_request_id || '/' || old.question_id;
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS document_analysis_search_au
+            AFTER UPDATE OF extracted_text ON document_analysis
+            WHEN new.extracted_text IS NOT NULL BEGIN
+                DELETE FROM search_index WHERE source_type = 'document' AND source_id = new.document_id;
+                INSERT INTO search_index (title, content, source_id, source_type, strata_id)
+                SELECT d.name, new.extracted_text, d.id, 'document', r.strata_id
+                FROM documents d LEFT JOIN service_requests r ON r.id = d.service_request_id
+                WHERE d.id = new.document_id;
+            END;
+            CREATE TRIGGER IF NOT EXISTS documents_search_ad AFTER DELETE ON documents BEGIN
+                DELETE FROM search_index WHERE source_type = 'document' AND source_id = old.id;
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS notes_search_ai AFTER INSERT ON notes BEGIN
+                INSERT INTO search_index (title, content, source_id, source_type, strata_id, owner_id)
+                VALUES (new.title, concat_ws(' ', new.content, new.tags), CAST(new.id AS TEXT), 'note', new.strata_id, new.owner_id);
+            END;
+            CREATE TRIGGER IF NOT EXISTS notes_search_au AFTER UPDATE ON notes BEGIN
+                DELETE FROM search_index WHERE source_type = 'note' AND source_id = CAST(old.id AS TEXT);
+                INSERT INTO search_index (title, content, source_id, source_type, strata_id, owner_id)
+                VALUES (new.title, concat_ws(' ', new.content, new.tags), CAST(new.id AS TEXT), 'note', new.strata_id, new.owner_id);
+            END;
+            CREATE TRIGGER IF NOT EXISTS notes_search_ad AFTER DELETE ON notes BEGIN
+                DELETE FROM search_index WHERE source_type = 'note' AND source_id = CAST(old.id AS TEXT);
+            END;",
+        )
+    }
+
+    // The triggers keep the index current, so it only needs filling when it is
+    // new or was built by triggers from an older SEARCH_INDEX_VERSION.
+    fn search_index_is_stale(conn: &Connection) -> Result<bool> {
+        let built: Option<i64> = conn
+            .query_row("SELECT version FROM schema_versions WHERE name = 'search_index'", [], |row| row.get(0))
+            .optional()?;
+        let empty: bool = conn.query_row("SELECT NOT EXISTS (SELECT 1 FROM search_index)", [], |row| row.get(0))?;
+        Ok(empty || built != Some(SEARCH_INDEX_VERSION))
+    }
+
+    // Repopulate the index from the source tables, picking up rows written
+    // before the triggers existed.
+    fn rebuild_search_index(conn: &Connection) -> Result<()> {
+        conn.execute_batch(&format!(
+            "BEGIN;
+            DELETE FROM search_index;
+            INSERT INTO search_index (title, content, source_id, source_type, strata_id)
+            SELECT complex_name || ' (' || strata_plan || ')',
+                   concat_ws(' ', strata_plan, address, city, province, postal_code),
+                   id, 'strata', id
+            FROM stratas;
+            INSERT INTO search_index (title, content, source_id, source_type, strata_id)
+            SELECT a.question_id, a.value, a.service_request_id || '/' || a.question_id, 'survey_answer', r.strata_id
+            FROM survey_answers a LEFT JOIN service_requests r ON r.id = a.service_request_id;
+            INSERT INTO search_index (title, content, source_id, source_type, strata_id)
+            SELECT d.name, a.extracted_text, d.id, 'document', r.strata_id
+            FROM document_analysis a
+            JOIN documents d ON d.id = a.document_id
+            LEFT JOIN service_requests r ON r.id = d.service_request_id
+            WHERE a.extracted_text IS NOT NULL;
+            INSERT INTO search_index (title, content, source_id, source_type, strata_id, owner_id)
+            SELECT title, concat_ws(' ', content, tags), CAST(id AS TEXT), 'note', strata_id, owner_id
+            FROM notes;
+            INSERT OR REPLACE INTO schema_versions (name, version) VALUES ('search_index', {SEARCH_INDEX_VERSION});
+            COMMIT;",
+        ))
+    }
+
     fn seed_data(&self) -> Result<()> {
         let conn = self.conn.lock().unwrap();
 
+        // Standing company closures; safe to run on every start
+        for (id, name, date) in [
+            ("closure-christmas", "Christmas Day", "2024-12-25"),
+            ("closure-canada-day", "Canada Day", "2024-07-01"),
+        ] {
+            conn.execute(
+                "INSERT OR IGNORE INTO company_closures (id, name, date, recurring) VALUES (?, ?, ?, 1)",
+                [id, name, date],
+            )?;
+        }
+
         // Check if users table is empty
         let count: i64 = conn.query_row("SELECT COUNT(*) FROM users", [], |row| row.get(0))?;
         if count > 0 {
@@ -133,6 +743,12 @@ impl AppState {
                 "user-inspector-1", "Jane Inspector", "inspector@srp.com", "inspector", "", "Senior Field Inspector", "2024-03-01T10:00:00Z"
             ],
         )?;
+        conn.execute(
+            "UPDATE users SET skill_level = 4, home_city = 'Burnaby', home_postal_code = 'V5H 2E2' WHERE id = ?",
+            ["user-inspector-1"],
+        )?;
+
+        Self::backfill_user_handles(&conn)?;
 
         // 3. Seed Service Requests
         conn.execute(
@@ -143,6 +759,14 @@ impl AppState {
             ],
         )?;
 
+        // 4. Seed the component inventory from the catalogue
+        crate::api_handlers::components::seed_inventory(
+            &conn,
+            "strata-1",
+            crate::models::PropertyType::Apartment,
+            "2024-03-01T10:00:00Z",
+        )?;
+
         Ok(())
     }
 }
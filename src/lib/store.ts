@@ -109,7 +109,7 @@ export const useStore = create<AppState>()(
 
             checkAuth: async () => {
                 try {
-                    const response = await fetch('/api/auth/me');
+                    const response = await fetch('/api/auth/me', { headers: authHeaders() });
                     if (response.ok) {
                         const user = await response.json();
                         set((state) => ({
@@ -124,6 +124,7 @@ export const useStore = create<AppState>()(
             },
 
             logout: () => {
+                fetch('/api/auth/logout', { method: 'POST', headers: authHeaders() }).catch(() => {});
                 set({
                     auth: {
                         isAuthenticated: false,
@@ -228,7 +229,10 @@ export const useStore = create<AppState>()(
 
             fetchNotifications: async () => {
                 try {
-                    const response = await fetch('/api/notifications');
+                    if (!get().auth.token) return;
+                    const response = await fetch('/api/notifications', {
+                        headers: authHeaders(),
+                    });
                     if (!response.ok) return;
                     const data = await response.json();
                     set({ notifications: data });
@@ -365,3 +369,9 @@ export const useStore = create<AppState>()(
         }
     )
 );
+
+// Bearer token from login; the API resolves the signed-in user from it
+export function authHeaders(): Record<string, string> {
+    const token = useStore.getState().auth.token;
+    return token ? { Authorization: `Bearer ${token}` } : {};
+}
@@ -20,7 +20,7 @@ export type ServiceRequestStatus =
 
 export type DocumentStatus = 'pending' | 'uploaded' | 'reviewed' | 'rejected';
 
-export type AppointmentStatus = 'pending' | 'confirmed' | 'completed' | 'cancelled';
+export type AppointmentStatus = 'pending' | 'offered' | 'confirmed' | 'completed' | 'cancelled' | 'rejected';
 
 export type SurveySectionId =
     | 'exterior'
@@ -129,6 +129,7 @@ export interface Appointment {
     status: AppointmentStatus;
     meetingType?: 'zoom' | 'in-person';
     notes?: string;
+    cancellationReason?: string;
     createdAt: string;
     updatedAt: string;
 }
@@ -136,7 +137,8 @@ export interface Appointment {
 export interface InspectorUnavailability {
     id: string;
     inspectorId: string;
-    unavailableDate: string;
+    startDate: string;
+    endDate: string;
     reason?: string;
 }
 
@@ -144,6 +146,7 @@ export interface CompanyHoliday {
     id: string;
     date: string;
     name: string;
+    recurring: boolean;
 }
 
 // UI State Types
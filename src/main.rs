@@ -2,12 +2,13 @@ mod api_handlers;
 mod db;
 mod models;
 
+use crate::api_handlers::document_worker::DocumentQueue;
 use crate::api_handlers::server_time::get_time;
 use crate::db::AppState;
 use axum::{
     extract::{OriginalUri, Request},
     response::{Html, IntoResponse},
-    routing::{get, post, put},
+    routing::{get, post},
     Json,
     RequestPartsExt, Router,
 };
@@ -25,6 +26,15 @@ async fn main() {
     // Initialize database
     let app_state = Arc::new(AppState::new().expect("Failed to initialize database"));
 
+    // Background document analysis owns its ECS world for the life of the server
+    let document_queue = DocumentQueue::start(app_state.clone());
+
+    // Hourly check of every open request against its deadlines
+    api_handlers::deadline_alerts::start_monitor(app_state.clone());
+
+    // DEMO_MODE=1 exposes the inspection simulator, which invents findings
+    let demo_mode = std::env::var("DEMO_MODE").is_ok_and(|value| value == "1" || value == "true");
+
     let api_host = std::env::var("PUBLIC_HOST").unwrap_or_else(|_| {
         println!("\x1b[38;2;217;194;140mWarning\x1b[0m PUBLIC_HOST not set");
         "localhost:3000".to_string()
@@ -59,13 +69,22 @@ async fn main() {
         .nest("/api", api_handlers::notes::router(app_state.clone()))
         .nest("/api/auth", api_handlers::auth::router(app_state.clone()))
         .nest("/api/stratas", api_handlers::stratas::router(app_state.clone()))
-        .nest("/api/stratas", api_handlers::timelines::router())
+        .nest("/api/stratas", api_handlers::timelines::router(app_state.clone()))
+        .nest("/api/timelines", api_handlers::milestones::router(app_state.clone()))
         .nest("/api/surveys", api_handlers::surveys::router(app_state.clone()))
-        .nest("/api/logistics", api_handlers::logistics::router())
-        .route("/api/ecs/documents/process", get(process_documents_ecs))
-        .route("/api/ecs/scheduler/optimize", put(run_scheduler_ecs))
-        .route("/api/ecs/inspection/simulate", post(simulate_inspection_ecs))
-        .route("/api/time/", get(get_time))
+        .nest("/api/logistics", api_handlers::logistics::router(app_state.clone()))
+        .nest("/api/documents", api_handlers::documents::router(app_state.clone(), document_queue))
+        .nest("/api/search", api_handlers::search::router(app_state.clone()))
+        .nest("/api/draft-meetings", api_handlers::draft_meetings::router(app_state.clone()))
+        .nest("/api/calendar", api_handlers::calendar::router(app_state.clone()))
+        .nest("/api/availability", api_handlers::availability::router(app_state.clone()))
+        .nest("/api/alerts", api_handlers::deadline_alerts::router(app_state.clone()))
+        .nest("/api/notifications", api_handlers::notifications::router(app_state.clone()))
+        .nest("/api/ecs/scheduler", api_handlers::scheduler::router(app_state.clone()))
+        .nest("/api/findings", api_handlers::findings::router(app_state.clone()))
+        .nest("/api/components", api_handlers::components::router(app_state.clone()))
+        .merge(demo_routes(demo_mode))
+        .route("/api/time/", get(get_time).with_state(app_state.clone()))
         .layer(compression_layer)
         .layer(cors_layer);
 
@@ -73,36 +92,13 @@ async fn main() {
     axum::serve(listener, app).await.unwrap();
 }
 
-async fn process_documents_ecs() -> impl IntoResponse {
-    use crate::api_handlers::ecs_documents::DocumentWorld;
-    
-    // Spin up an ECS world for this request
-    let mut ecs = DocumentWorld::new();
-    
-    // Ingest some mock documents
-    ecs.add_document("doc-1".into(), "Budget_2024.pdf".into(), "This is the Strata Budget for 2024...".into());
-    ecs.add_document("doc-2".into(), "Minutes_AGM.docx".into(), "Minutes of the Annual General Meeting...".into());
-    ecs.add_document("doc-3".into(), "Engineering_Report.txt".into(), "Structural analysis reveals critical failures...".into());
-    
-    // Run the systems (Parallel Analysis)
-    ecs.run();
-    
-    // Get results
-    let results = ecs.get_results();
-    
-    Json(results)
-}
-
-async fn run_scheduler_ecs() -> impl IntoResponse {
-    use crate::api_handlers::ecs_scheduler::SchedulerWorld;
-    
-    let mut ecs = SchedulerWorld::new();
-    ecs.seed_simulation();
-    ecs.run();
-    
-    let assignments = ecs.get_assignments();
-    
-    Json(assignments)
+// Demo-only endpoints; real findings are recorded through /api/findings
+fn demo_routes(enabled: bool) -> Router {
+    if enabled {
+        Router::new().route("/api/ecs/inspection/simulate", post(simulate_inspection_ecs))
+    } else {
+        Router::new()
+    }
 }
 
 async fn simulate_inspection_ecs() -> impl IntoResponse {
@@ -27,11 +27,13 @@ pub struct Strata {
     pub province: String,
     pub postal_code: String,
     pub country: String,
-    pub property_type: String, // Bare Land, Townhouse, Apartment, etc.
+    pub property_type: String, // one of PropertyType
     pub legal_type: String, // Standard, Air-Parcel
     pub company_id: String,
     pub property_manager_id: Option<String>,
     pub user_ids: Vec<String>,
+    #[serde(default)]
+    pub time_zone: String, // IANA name, e.g. America/Vancouver
     pub created_at: String,
 }
 
@@ -75,9 +77,85 @@ pub struct Document {
     pub mime_type: Option<String>,
 }
 
+// Kinds of strata property the portal is offered for
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyType {
+    #[serde(rename = "Bare Land")]
+    BareLand,
+    Townhouse,
+    Apartment,
+    #[serde(rename = "Mixed-Use")]
+    MixedUse,
+    Industrial,
+}
+
+impl PropertyType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PropertyType::BareLand => "Bare Land",
+            PropertyType::Townhouse => "Townhouse",
+            PropertyType::Apartment => "Apartment",
+            PropertyType::MixedUse => "Mixed-Use",
+            PropertyType::Industrial => "Industrial",
+        }
+    }
+
+    // Stored values were typed in by staff, so case and spacing vary
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().replace(['-', '_'], " ").as_str() {
+            "bare land" => Some(PropertyType::BareLand),
+            "townhouse" => Some(PropertyType::Townhouse),
+            "apartment" => Some(PropertyType::Apartment),
+            "mixed use" => Some(PropertyType::MixedUse),
+            "industrial" => Some(PropertyType::Industrial),
+            _ => None,
+        }
+    }
+}
+
+// Client requests (pending); the admin either confirms one of the requested
+// slots, counter-offers (offered, awaiting the client) or rejects.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AppointmentStatus {
+    #[default]
+    Pending,
+    Offered,
+    Confirmed,
+    Completed,
+    Cancelled,
+    Rejected,
+}
+
+impl AppointmentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AppointmentStatus::Pending => "pending",
+            AppointmentStatus::Offered => "offered",
+            AppointmentStatus::Confirmed => "confirmed",
+            AppointmentStatus::Completed => "completed",
+            AppointmentStatus::Cancelled => "cancelled",
+            AppointmentStatus::Rejected => "rejected",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(AppointmentStatus::Pending),
+            "offered" => Some(AppointmentStatus::Offered),
+            "confirmed" => Some(AppointmentStatus::Confirmed),
+            "completed" => Some(AppointmentStatus::Completed),
+            "cancelled" => Some(AppointmentStatus::Cancelled),
+            "rejected" => Some(AppointmentStatus::Rejected),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Appointment {
+    #[serde(default)] // assigned on booking when omitted
     pub id: String,
     pub service_request_id: String,
     pub strata_plan: String,
@@ -89,20 +167,44 @@ pub struct Appointment {
     pub confirmed_date: Option<String>,
     pub confirmed_time: Option<String>,
     pub inspector_id: Option<String>,
-    pub status: String,
+    #[serde(default)]
+    pub status: AppointmentStatus,
     pub meeting_type: Option<String>,
     pub notes: Option<String>,
+    #[serde(default)]
+    pub cancellation_reason: Option<String>,
+    #[serde(default)]
     pub created_at: String,
+    #[serde(default)]
     pub updated_at: String,
+    #[serde(default)]
+    pub starts_at: Option<String>, // UTC instant of the confirmed, else first requested, slot
+}
+
+// One audit entry per appointment status change or reschedule
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AppointmentEvent {
+    pub id: String,
+    pub appointment_id: String,
+    pub action: String, // requested, offered, confirmed, accepted, adjusted, rejected, cancelled
+    pub from_status: Option<AppointmentStatus>,
+    pub to_status: AppointmentStatus,
+    pub actor_id: Option<String>,
+    pub reason: Option<String>,
+    pub date: Option<String>, // slot in effect after the change
+    pub time: Option<String>,
+    pub inspector_id: Option<String>,
+    pub created_at: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TimelineRequest {
     pub file_opened: String,
     pub fiscal_year_start_month: u8,
-    pub last_agm_date: String,
+    pub last_agm_date: String, // blank when unknown
     pub last_depr_report: Option<String>,
-    pub target_date: Option<String>,
+    pub target_date: Option<String>, // AGM date the council is aiming for
     pub report_scope: String, // "This Fiscal" or "Next Fiscal"
 }
 
@@ -114,6 +216,13 @@ pub struct TimelineResponse {
     pub days_remaining_in_fiscal: i64,
     pub days_since_agm: Option<i64>,
     pub days_since_file_opened: i64,
+    pub fiscal_year_start: String,
+    pub fiscal_year_end: String,
+    pub report_fiscal_year_end: String,
+    pub agm_window_start: String, // statutory: within two months of fiscal year end
+    pub agm_window_end: String,
+    pub depreciation_report_due: Option<String>,
+    pub depreciation_report_overdue: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -173,6 +282,181 @@ pub struct LogisticsSlot {
     pub date: String,
     pub time: String,
     pub id: String,
+    #[serde(default)]
+    pub start_time: String, // HH:MM, `time` is the display label
+    #[serde(default)]
+    pub end_time: String,
+    #[serde(default)]
+    pub inspector_ids: Vec<String>, // inspectors free for this slot
+}
+
+// Scheduling attributes of an inspector; routes start and end at the home base
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectorProfile {
+    pub id: String,
+    pub name: String,
+    pub skill_level: u8, // 1-5, matched against the site's complexity
+    pub home_address: Option<String>,
+    pub home_city: Option<String>,
+    pub home_postal_code: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OverrideKind {
+    Pin,
+    Exclude,
+}
+
+impl OverrideKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OverrideKind::Pin => "pin",
+            OverrideKind::Exclude => "exclude",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pin" => Some(OverrideKind::Pin),
+            "exclude" => Some(OverrideKind::Exclude),
+            _ => None,
+        }
+    }
+}
+
+impl rusqlite::types::FromSql for OverrideKind {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        let text = value.as_str()?;
+        Self::parse(text).ok_or_else(|| rusqlite::types::FromSqlError::Other(format!("unknown override kind {text:?}").into()))
+    }
+}
+
+impl rusqlite::types::ToSql for OverrideKind {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+// Pin a service request to an inspector (optionally on a date), or keep an
+// inspector off it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SchedulerOverride {
+    #[serde(default)]
+    pub id: String,
+    pub service_request_id: String,
+    pub inspector_id: String,
+    pub kind: OverrideKind,
+    pub date: Option<String>,
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub created_by: Option<String>,
+    #[serde(default)]
+    pub created_at: String,
+}
+
+// One line of a strata's reserve component inventory
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildingComponent {
+    #[serde(default)]
+    pub id: String,
+    pub strata_id: String,
+    #[serde(default)]
+    pub catalogue_code: Option<String>, // e.g. "ROOF-SBS"
+    pub component_type: String,
+    pub category: String, // e.g. "Envelope", "Mechanical"
+    #[serde(default)]
+    pub quantity: Option<f64>, // unknown until measured on site
+    pub unit: String, // "m2", "lm", "ea" or "ls"
+    #[serde(default)]
+    pub install_year: Option<i32>,
+    pub expected_useful_life: u32, // years
+    pub replacement_unit_cost: f64, // dollars per unit
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub created_at: String,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+// What an inspector recorded about one building component on a service request
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectionFinding {
+    #[serde(default)]
+    pub id: String,
+    pub service_request_id: String,
+    #[serde(default)]
+    pub component_id: Option<String>, // entry in the strata's component inventory
+    #[serde(default)]
+    pub component: String, // e.g. "Asphalt Shingle Roof"; taken from the inventory when blank
+    pub category: Option<String>, // e.g. "Envelope", "Mechanical"
+    pub condition_score: u8, // 0 (failed) to 100 (as new)
+    pub observation: String,
+    pub recommendation: String,
+    pub estimated_cost: Option<u32>, // dollars
+    #[serde(default)]
+    pub inspector_id: Option<String>,
+    #[serde(default)]
+    pub recorded_at: String,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+// Regular weekly hours; weekday 0 = Monday
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkingHours {
+    pub weekday: u8,
+    #[serde(with = "hhmm")]
+    pub start_time: chrono::NaiveTime,
+    #[serde(with = "hhmm")]
+    pub end_time: chrono::NaiveTime,
+}
+
+// Times of day as "HH:MM" on the wire and in the database
+pub mod hhmm {
+    use chrono::NaiveTime;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub const FORMAT: &str = "%H:%M";
+
+    pub fn serialize<S: Serializer>(time: &NaiveTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&time.format(FORMAT).to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        NaiveTime::parse_from_str(&value, FORMAT).map_err(serde::de::Error::custom)
+    }
+}
+
+// Leave or other time off, inclusive of both dates
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectorUnavailability {
+    #[serde(default)]
+    pub id: String,
+    pub inspector_id: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub reason: Option<String>,
+}
+
+// Company-wide closure; recurring closures repeat on the same day every year
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanyClosure {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub date: String,
+    #[serde(default)]
+    pub recurring: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -186,3 +470,17 @@ pub struct AuthRequest {
     pub email: String,
     pub password: String,
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    pub id: String,
+    pub user_id: String,
+    pub title: String,
+    pub message: String,
+    #[serde(rename = "type")]
+    pub kind: String, // info, warning, error, success
+    pub link: Option<String>,
+    pub read: bool,
+    pub created_at: String,
+}